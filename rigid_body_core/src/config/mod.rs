@@ -1,8 +1,9 @@
 pub mod test;
 
 use crate::{
+    error::{Error, Result},
     math::{matrix::Matrix3x3, moment_of_inertia, vector::Vector3d},
-    mesh::polyhedron_meshes,
+    mesh::{polyhedron_meshes, Mesh},
     render::{Color, RenderOption},
     CameraMode, RigidBody, RigidBodySimulationTrait,
 };
@@ -10,7 +11,7 @@ use crate::{
 pub fn default(
     n: usize,
     rigid_body_simulation: &mut impl RigidBodySimulationTrait,
-) -> Result<(), String> {
+) -> Result<()> {
     let bb_dim = 50.;
     let bb_dim = Vector3d::new(bb_dim, bb_dim, bb_dim);
     let bb_min = bb_dim.scale(-0.5);
@@ -33,15 +34,12 @@ pub fn default(
 
     let mass = 1.;
     let mass_inv = 1. / mass;
-    let sphere_mi_inv = moment_of_inertia::solid_sphere(radius, mass)
-        .inverse()
-        .expect("sphere_mi_inv");
-    let tetrahedron_mi_inv = moment_of_inertia::regular_tetrahedron(
+    let sphere_mi_inv =
+        inverse(&moment_of_inertia::solid_sphere(radius, mass))?;
+    let tetrahedron_mi_inv = inverse(&moment_of_inertia::regular_tetrahedron(
         tetrahedron_mesh.vertices[0].dist(&tetrahedron_mesh.vertices[1]),
         mass,
-    )
-    .inverse()
-    .expect("tetrahedron_mi_inv");
+    ))?;
 
     let get_pos = |axis: usize, index: usize, current: &mut f64| {
         let gap = (bb_dim[axis] - dim[axis] * n as f64) / (n + 1) as f64;
@@ -67,7 +65,7 @@ pub fn default(
                             &Matrix3x3::identity(),
                             &Vector3d::new(0., -4., 0.),
                             &Vector3d::new(0., 0., 0.),
-                        ),
+                        )?,
                         RenderOption::Mesh {
                             mesh: polyhedron_meshes::cuboid(&dim),
                             color: color_increment.get(),
@@ -112,6 +110,21 @@ pub fn default(
     Ok(())
 }
 
+fn inverse(inertia_body: &Matrix3x3) -> Result<Matrix3x3> {
+    inertia_body.inverse().ok_or_else(|| {
+        Error::SingularInertia(format!("Body inertia {}.", inertia_body))
+    })
+}
+
+fn shape_mesh(rigid_body: &RigidBody) -> Result<Mesh> {
+    rigid_body.shape().mesh().ok_or_else(|| {
+        Error::InvalidValue(format!(
+            "Shape {:?} has no mesh.",
+            rigid_body.shape()
+        ))
+    })
+}
+
 pub struct ColorIncrement {
     n: usize,
     colors: Vec<Color>,
@@ -120,10 +133,10 @@ pub struct ColorIncrement {
 
 impl ColorIncrement {
     pub fn new(n: usize) -> Self {
-        let mut colors = Vec::with_capacity(1021);
+        let cap4 = u8::MAX as usize;
+        let cap = cap4 * 4;
+        let mut colors = Vec::with_capacity(cap);
         let mut color = Color::rgb(255, 0, 0);
-        let cap = colors.capacity();
-        let cap4 = cap / 4;
         for i in 0..cap {
             colors.push(color);
             if i < cap4 {
@@ -1,16 +1,20 @@
 use crate::{
+    bounding_box::PeriodicDomain,
+    force_manager::{FluidVolume, Gravity},
+    half_space::HalfSpace,
     math::{
-        matrix::Matrix3x3, moment_of_inertia, rotation_matrix, vector::Vector3d,
+        matrix::Matrix3x3, matrix_vector, moment_of_inertia, rotation_matrix,
+        vector::Vector3d,
     },
     mesh::polyhedron_meshes,
     render::{Color, RenderOption},
-    RigidBody, RigidBodySimulationTrait,
+    Integrator, Result, RigidBody, RigidBodySimulationTrait,
 };
 use std::f64::consts::PI;
 
 pub fn bounding_box_external(
     rigid_body_simulation: &mut impl RigidBodySimulationTrait,
-) -> Result<(), String> {
+) -> Result<()> {
     rigid_body_simulation.camera_mut().position = Vector3d::new(0., 0., -30.);
     let bbhd = 15.;
     rigid_body_simulation.set_bounding_box(Some((
@@ -23,9 +27,10 @@ pub fn bounding_box_external(
 
     let radius = 2.25;
     let mass_inv = 1.;
-    let mi_inv = moment_of_inertia::solid_sphere(radius, 1. / mass_inv)
-        .inverse()
-        .expect("mi_inv");
+    let mi_inv = super::inverse(&moment_of_inertia::solid_sphere(
+        radius,
+        1. / mass_inv,
+    ))?;
 
     let mut dim = Vector3d::new(3., 3., 3.);
     let color = Color::rgb(0, 255, 0);
@@ -61,7 +66,7 @@ pub fn bounding_box_external(
                             &Matrix3x3::identity(),
                             &Vector3d::new(0., -4., 0.),
                             &Vector3d::new(0., 0., 0.),
-                        ),
+                        )?,
                         RenderOption::Mesh {
                             mesh: polyhedron_meshes::cuboid(&dim),
                             color,
@@ -81,7 +86,7 @@ pub fn bounding_box_external(
             &Matrix3x3::identity(),
             &Vector3d::new(0., -4., 0.),
             &Vector3d::new(0., 0., 0.),
-        ),
+        )?,
         RenderOption::Mesh {
             mesh: polyhedron_meshes::cuboid(&dim),
             color: Color::rgb(0, 255, 0),
@@ -95,7 +100,7 @@ pub fn bounding_box_external(
             &Matrix3x3::identity(),
             &Vector3d::new(0., -4., 0.),
             &Vector3d::new(0., 0., 0.),
-        ),
+        )?,
         RenderOption::Mesh {
             mesh: polyhedron_meshes::cuboid(&dim),
             color: Color::rgb(0, 255, 0),
@@ -109,7 +114,7 @@ pub fn bounding_box_external(
             &Matrix3x3::identity(),
             &Vector3d::new(0., -4., 0.),
             &Vector3d::new(0., 0., 0.),
-        ),
+        )?,
         RenderOption::Mesh {
             mesh: polyhedron_meshes::cuboid(&dim),
             color: Color::rgb(0, 255, 0),
@@ -119,7 +124,9 @@ pub fn bounding_box_external(
     Ok(())
 }
 
-pub fn coincident(rigid_body_simulation: &mut impl RigidBodySimulationTrait) {
+pub fn coincident(
+    rigid_body_simulation: &mut impl RigidBodySimulationTrait,
+) -> Result<()> {
     rigid_body_simulation.camera_mut().position = Vector3d::new(0., 0., -30.);
     let dim = Vector3d::new(5., 5., 5.);
     rigid_body_simulation.add_rigid_body(
@@ -130,7 +137,7 @@ pub fn coincident(rigid_body_simulation: &mut impl RigidBodySimulationTrait) {
             &Matrix3x3::identity(),
             &Vector3d::new(0., 0., 0.),
             &Vector3d::new(0., 0., 0.),
-        ),
+        )?,
         RenderOption::Mesh {
             mesh: polyhedron_meshes::cuboid(&dim),
             color: Color::rgb(255, 0, 0),
@@ -144,24 +151,24 @@ pub fn coincident(rigid_body_simulation: &mut impl RigidBodySimulationTrait) {
             &rotation_matrix::x(1.2).mult(&rotation_matrix::z(0.3)),
             &Vector3d::new(0., 0., 0.),
             &Vector3d::new(0., 0., 0.),
-        ),
+        )?,
         RenderOption::Mesh {
             mesh: polyhedron_meshes::cuboid(&dim),
             color: Color::rgb(0, 255, 0),
         },
     );
+    Ok(())
 }
 
 pub fn icosphere(
     n: u8,
     rigid_body_simulation: &mut impl RigidBodySimulationTrait,
-) -> Result<(), String> {
+) -> Result<()> {
     rigid_body_simulation.camera_mut().position = Vector3d::new(0., 0., -10.);
     let mesh = polyhedron_meshes::icosphere(5., n);
     let mass_inv = 1.;
-    let mi_inv = moment_of_inertia::solid_sphere(5., 1. / mass_inv)
-        .inverse()
-        .expect("mi_inv");
+    let mi_inv =
+        super::inverse(&moment_of_inertia::solid_sphere(5., 1. / mass_inv))?;
 
     rigid_body_simulation.add_rigid_body(
         RigidBody::from_mesh(
@@ -181,7 +188,9 @@ pub fn icosphere(
     Ok(())
 }
 
-pub fn immovable(rigid_body_simulation: &mut impl RigidBodySimulationTrait) {
+pub fn immovable(
+    rigid_body_simulation: &mut impl RigidBodySimulationTrait,
+) -> Result<()> {
     rigid_body_simulation.camera_mut().position = Vector3d::new(0., 0., -10.);
     let mass_inv = 1.;
     let dim = Vector3d::new(5., 5., 5.);
@@ -193,7 +202,7 @@ pub fn immovable(rigid_body_simulation: &mut impl RigidBodySimulationTrait) {
             &rotation_matrix::y(PI / 4.),
             &Vector3d::new(0., 2., 2.),
             &Vector3d::new(2., 0., 0.),
-        ),
+        )?,
         RenderOption::Mesh {
             mesh: polyhedron_meshes::cuboid(&dim),
             color: Color::rgb(0, 255, 0),
@@ -207,20 +216,20 @@ pub fn immovable(rigid_body_simulation: &mut impl RigidBodySimulationTrait) {
             &Matrix3x3::identity(),
             &Vector3d::new(0., 0., 0.),
             &Vector3d::new(0., 0., 0.),
-        ),
+        )?,
         RenderOption::None,
     );
+    Ok(())
 }
 
 pub fn regular_icosahedron(
     rigid_body_simulation: &mut impl RigidBodySimulationTrait,
-) -> Result<(), String> {
+) -> Result<()> {
     rigid_body_simulation.camera_mut().position = Vector3d::new(0., 0., -10.);
     let mesh = polyhedron_meshes::regular_icosahedron(5.);
     let mass_inv = 1.;
-    let mi_inv = moment_of_inertia::solid_sphere(5., 1. / mass_inv)
-        .inverse()
-        .expect("mi_inv");
+    let mi_inv =
+        super::inverse(&moment_of_inertia::solid_sphere(5., 1. / mass_inv))?;
 
     rigid_body_simulation.add_rigid_body(
         RigidBody::from_mesh(
@@ -257,13 +266,12 @@ pub fn regular_icosahedron(
 
 pub fn regular_tetrahedron(
     rigid_body_simulation: &mut impl RigidBodySimulationTrait,
-) -> Result<(), String> {
+) -> Result<()> {
     rigid_body_simulation.camera_mut().position = Vector3d::new(0., 0., -10.);
     let mesh = polyhedron_meshes::regular_tetrahedron(5.);
     let mass_inv = 1.;
-    let mi_inv = moment_of_inertia::solid_sphere(5., 1. / mass_inv)
-        .inverse()
-        .expect("mi_inv");
+    let mi_inv =
+        super::inverse(&moment_of_inertia::solid_sphere(5., 1. / mass_inv))?;
 
     rigid_body_simulation.add_rigid_body(
         RigidBody::from_mesh(
@@ -297,3 +305,347 @@ pub fn regular_tetrahedron(
     );
     Ok(())
 }
+
+pub fn floor_debris(
+    n: usize,
+    rigid_body_simulation: &mut impl RigidBodySimulationTrait,
+) -> Result<()> {
+    debris_on_floor(n, None, rigid_body_simulation)
+}
+
+pub fn container_debris(
+    n: usize,
+    rigid_body_simulation: &mut impl RigidBodySimulationTrait,
+) -> Result<()> {
+    debris_on_floor(n, Some(0.5), rigid_body_simulation)
+}
+
+fn debris_on_floor(
+    n: usize,
+    gap_opt: Option<f64>,
+    rigid_body_simulation: &mut impl RigidBodySimulationTrait,
+) -> Result<()> {
+    let bb_dim = Vector3d::new(60., 20., 60.);
+    let bb_min = bb_dim.scale(-0.5);
+    let bb_max = bb_dim.scale(0.5);
+    rigid_body_simulation.set_bounding_box(Some((
+        &bb_min,
+        &bb_max,
+        RenderOption::PolyhedronEdges {
+            color: Color::rgb(255, 0, 0),
+        },
+    )));
+    rigid_body_simulation.camera_mut().position =
+        Vector3d::new(0., 0., -bb_dim[2]);
+
+    let dim = Vector3d::new(1., 0.5, 1.);
+    let (gap, offset) = match gap_opt {
+        Some(gap) => (gap, ((gap + dim[0]) * n as f64 - gap) * -0.5),
+        None => {
+            let gap = (bb_dim[0] - dim[0] * n as f64) / (n + 1) as f64;
+            (gap, bb_min[0] + gap)
+        }
+    };
+    for i in 0..n {
+        let x = offset + (gap + dim[0]) * i as f64 + dim[0] * 0.5;
+        for k in 0..n {
+            let z = offset + (gap + dim[2]) * k as f64 + dim[2] * 0.5;
+            let phase = (i * n + k) as f64;
+            rigid_body_simulation.add_rigid_body(
+                RigidBody::cuboid(
+                    &dim,
+                    1.,
+                    &Vector3d::new(x, bb_min[1] + dim[1], z),
+                    &rotation_matrix::y(phase),
+                    &Vector3d::new(phase.sin(), -1., phase.cos()),
+                    &Vector3d::new(0., 0.1 * phase.cos(), 0.),
+                )?,
+                RenderOption::Mesh {
+                    mesh: polyhedron_meshes::cuboid(&dim),
+                    color: Color::rgb(0, 255, 0),
+                },
+            );
+        }
+    }
+    Ok(())
+}
+
+pub fn aligned_debris(
+    n: usize,
+    rigid_body_simulation: &mut impl RigidBodySimulationTrait,
+) -> Result<()> {
+    let spacing = 2.;
+    let positions = (0..n)
+        .map(|i| {
+            Vector3d::new((i as f64 - (n - 1) as f64 / 2.) * spacing, 0., 0.)
+        })
+        .collect::<Vec<_>>();
+    debris(
+        &Vector3d::new(spacing * n as f64 + 4., 10., 10.),
+        &positions,
+        rigid_body_simulation,
+    )
+}
+
+pub fn clustered_debris(
+    n: usize,
+    rigid_body_simulation: &mut impl RigidBodySimulationTrait,
+) -> Result<()> {
+    debris(
+        &Vector3d::new(60., 60., 60.),
+        &grid_positions(n, 1.5),
+        rigid_body_simulation,
+    )
+}
+
+pub fn uniform_debris(
+    n: usize,
+    rigid_body_simulation: &mut impl RigidBodySimulationTrait,
+) -> Result<()> {
+    let bb_dim = 60.;
+    let m = (n as f64).cbrt().ceil();
+    debris(
+        &Vector3d::new(bb_dim, bb_dim, bb_dim),
+        &grid_positions(n, (bb_dim - 4.) / m),
+        rigid_body_simulation,
+    )
+}
+
+fn grid_positions(n: usize, spacing: f64) -> Vec<Vector3d> {
+    let m = (n as f64).cbrt().ceil() as usize;
+    let offset = (m - 1) as f64 * spacing / 2.;
+    (0..n)
+        .map(|index| {
+            Vector3d::new(
+                (index % m) as f64 * spacing - offset,
+                (index / m % m) as f64 * spacing - offset,
+                (index / (m * m)) as f64 * spacing - offset,
+            )
+        })
+        .collect()
+}
+
+fn debris(
+    bb_dim: &Vector3d,
+    positions: &[Vector3d],
+    rigid_body_simulation: &mut impl RigidBodySimulationTrait,
+) -> Result<()> {
+    let bb_min = bb_dim.scale(-0.5);
+    let bb_max = bb_dim.scale(0.5);
+    rigid_body_simulation.set_bounding_box(Some((
+        &bb_min,
+        &bb_max,
+        RenderOption::PolyhedronEdges {
+            color: Color::rgb(255, 0, 0),
+        },
+    )));
+    rigid_body_simulation.camera_mut().position =
+        Vector3d::new(0., 0., -bb_dim[2]);
+
+    let dim = Vector3d::new(1., 1., 1.);
+    for (i, position) in positions.iter().enumerate() {
+        let phase = i as f64;
+        rigid_body_simulation.add_rigid_body(
+            RigidBody::cuboid(
+                &dim,
+                1.,
+                position,
+                &rotation_matrix::y(phase),
+                &Vector3d::new(phase.sin(), phase.cos(), (2. * phase).sin()),
+                &Vector3d::new(0., 0.1 * phase.cos(), 0.),
+            )?,
+            RenderOption::Mesh {
+                mesh: polyhedron_meshes::cuboid(&dim),
+                color: Color::rgb(0, 255, 0),
+            },
+        );
+    }
+    Ok(())
+}
+
+pub fn floating_crates(
+    n: usize,
+    rigid_body_simulation: &mut impl RigidBodySimulationTrait,
+) -> Result<()> {
+    let bb_dim = Vector3d::new(20., 20., 20.);
+    let bb_min = bb_dim.scale(-0.5);
+    rigid_body_simulation.set_bounding_box(Some((
+        &bb_min,
+        &bb_dim.scale(0.5),
+        RenderOption::PolyhedronEdges {
+            color: Color::rgb(255, 0, 0),
+        },
+    )));
+    rigid_body_simulation.add_force_generator(Box::new(Gravity::earth()));
+    rigid_body_simulation
+        .add_force_generator(Box::new(FluidVolume::new(0., 1., 0.2)));
+    rigid_body_simulation.camera_mut().position =
+        Vector3d::new(0., 0., -bb_dim[2]);
+
+    let dim = Vector3d::new(2., 1., 1.5);
+    let spacing = bb_dim[0] / n as f64;
+    for i in 0..n {
+        for k in 0..n {
+            let phase = (i * n + k) as f64;
+            rigid_body_simulation.add_rigid_body(
+                RigidBody::cuboid(
+                    &dim,
+                    1. / (1. + phase.sin().abs()),
+                    &Vector3d::new(
+                        bb_min[0] + spacing * (i as f64 + 0.5),
+                        3. + phase.cos(),
+                        bb_min[2] + spacing * (k as f64 + 0.5),
+                    ),
+                    &rotation_matrix::x(phase),
+                    &Vector3d::default(),
+                    &Vector3d::new(0., 0., phase.sin()),
+                )?,
+                RenderOption::Mesh {
+                    mesh: polyhedron_meshes::cuboid(&dim),
+                    color: Color::rgb(0, 255, 0),
+                },
+            );
+        }
+    }
+    Ok(())
+}
+
+pub fn periodic_flow(
+    n: usize,
+    rigid_body_simulation: &mut impl RigidBodySimulationTrait,
+) -> Result<()> {
+    let domain_dim = Vector3d::new(20., 20., 20.);
+    let domain_min = domain_dim.scale(-0.5);
+    rigid_body_simulation.set_periodic_domain(Some(PeriodicDomain::new(
+        &domain_min,
+        &domain_dim.scale(0.5),
+        [true, false, true],
+    )?));
+    rigid_body_simulation.add_half_space(
+        HalfSpace::new(&domain_min, &Vector3d::new(0.2, 1., 0.))?,
+        RenderOption::None,
+    );
+    rigid_body_simulation.add_force_generator(Box::new(Gravity::earth()));
+    rigid_body_simulation.camera_mut().position =
+        Vector3d::new(0., 0., -domain_dim[2] * 1.5);
+
+    let spacing = domain_dim[0] / n as f64;
+    for i in 0..n {
+        for k in 0..n {
+            let phase = (i * n + k) as f64;
+            let rigid_body = RigidBody::sphere(
+                spacing * 0.3,
+                1.,
+                &Vector3d::new(
+                    domain_min[0] + spacing * (i as f64 + 0.5),
+                    domain_min[1] + domain_dim[1] * 0.5 + phase.sin(),
+                    domain_min[2] + spacing * (k as f64 + 0.5),
+                ),
+                &Matrix3x3::identity(),
+                &Vector3d::default(),
+                &Vector3d::default(),
+            )?;
+            let mesh = super::shape_mesh(&rigid_body)?;
+            rigid_body_simulation.add_rigid_body(
+                rigid_body,
+                RenderOption::Mesh {
+                    mesh,
+                    color: Color::rgb(0, 255, 0),
+                },
+            );
+        }
+    }
+    Ok(())
+}
+
+pub fn spinning_body(
+    integrator: Integrator,
+    rigid_body_simulation: &mut impl RigidBodySimulationTrait,
+) -> Result<()> {
+    rigid_body_simulation.set_integrator(integrator);
+    rigid_body_simulation.camera_mut().position = Vector3d::new(0., 0., -15.);
+
+    let dim = Vector3d::new(1., 3., 0.3);
+    rigid_body_simulation.add_rigid_body(
+        RigidBody::cuboid(
+            &dim,
+            1.,
+            &Vector3d::default(),
+            &Matrix3x3::identity(),
+            &Vector3d::default(),
+            &matrix_vector::mult_3(
+                &moment_of_inertia::aligned_cuboid(&dim, 1.),
+                &Vector3d::new(5., 0., 0.05),
+            ),
+        )?,
+        RenderOption::Mesh {
+            mesh: polyhedron_meshes::cuboid(&dim),
+            color: Color::rgb(0, 255, 0),
+        },
+    );
+    Ok(())
+}
+
+pub fn shapes(
+    rigid_body_simulation: &mut impl RigidBodySimulationTrait,
+) -> Result<()> {
+    let bb_dim = Vector3d::new(20., 20., 20.);
+    rigid_body_simulation.set_bounding_box(Some((
+        &bb_dim.scale(-0.5),
+        &bb_dim.scale(0.5),
+        RenderOption::PolyhedronEdges {
+            color: Color::rgb(255, 0, 0),
+        },
+    )));
+    rigid_body_simulation.camera_mut().position =
+        Vector3d::new(0., 0., -bb_dim[2]);
+
+    for i in 0..9 {
+        let phase = i as f64;
+        let position = Vector3d::new(
+            -6. + 6. * (i % 3) as f64,
+            0.,
+            -6. + 6. * (i / 3) as f64,
+        );
+        let rotation = rotation_matrix::x(phase);
+        let momentum = Vector3d::new(phase.sin(), phase.cos(), 1.).scale(4.);
+        let angular_momentum = Vector3d::new(0., 0.5, 0.);
+        let rigid_body = match i % 3 {
+            0 => RigidBody::sphere(
+                1.,
+                1.,
+                &position,
+                &rotation,
+                &momentum,
+                &angular_momentum,
+            ),
+            1 => RigidBody::capsule(
+                0.75,
+                2.,
+                1.,
+                &position,
+                &rotation,
+                &momentum,
+                &angular_momentum,
+            ),
+            _ => RigidBody::cylinder(
+                1.,
+                2.,
+                1.,
+                &position,
+                &rotation,
+                &momentum,
+                &angular_momentum,
+            ),
+        }?;
+        let mesh = super::shape_mesh(&rigid_body)?;
+        rigid_body_simulation.add_rigid_body(
+            rigid_body,
+            RenderOption::Mesh {
+                mesh,
+                color: Color::rgb(0, 255, 0),
+            },
+        );
+    }
+    Ok(())
+}
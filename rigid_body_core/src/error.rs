@@ -0,0 +1,92 @@
+use crate::simulation::health::HealthIssue;
+use std::{
+    fmt::{self, Display, Formatter},
+    io,
+    path::Path,
+};
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+    Context {
+        context: String,
+        source: Box<Error>,
+    },
+    DegenerateGeometry(String),
+    Format(String),
+    Health(HealthIssue),
+    InvalidValue(String),
+    Io {
+        kind: io::ErrorKind,
+        message: String,
+    },
+    Parse {
+        line: usize,
+        message: String,
+    },
+    SingularInertia(String),
+}
+
+impl Error {
+    pub fn context(self, context: impl Display) -> Self {
+        Self::Context {
+            context: context.to_string(),
+            source: Box::new(self),
+        }
+    }
+
+    pub fn path(self, path: &Path) -> Self {
+        self.context(path.display())
+    }
+
+    pub fn root(&self) -> &Self {
+        match self {
+            Self::Context { source, .. } => source.root(),
+            _ => self,
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Context { context, source } => {
+                write!(f, "{}: {}", context, source)
+            }
+            Self::DegenerateGeometry(message) => {
+                write!(f, "degenerate geometry: {}", message)
+            }
+            Self::Format(message) => write!(f, "invalid format: {}", message),
+            Self::Health(issue) => write!(f, "{}", issue),
+            Self::InvalidValue(message) => {
+                write!(f, "invalid value: {}", message)
+            }
+            Self::Io { message, .. } => write!(f, "io: {}", message),
+            Self::Parse { line, message } => {
+                write!(f, "line {}: {}", line, message)
+            }
+            Self::SingularInertia(message) => {
+                write!(f, "singular inertia: {}", message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Context { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Self::Io {
+            kind: error.kind(),
+            message: error.to_string(),
+        }
+    }
+}
@@ -22,11 +22,16 @@ pub enum Keycode {
     R,
     S,
     W,
+    X,
     Z,
     Return,
     Space,
     Tab,
     Escape,
+    Left,
+    Right,
+    Up,
+    Down,
     LCtrl,
     LShift,
     Last,
@@ -10,8 +10,11 @@ pub struct InputCore {
     pub mouse_state: MouseState,
     pub advance_simulation: bool,
     pub debug: bool,
+    pub explode: bool,
     pub reset: bool,
     pub tick: bool,
+    pub seek: i32,
+    pub speed: i32,
     pub quit: bool,
 }
 
@@ -22,8 +25,11 @@ impl InputCore {
             mouse_state: MouseState::default(),
             advance_simulation: true,
             debug: false,
+            explode: false,
             reset: false,
             tick: false,
+            seek: 0,
+            speed: 0,
             quit: false,
         }
     }
@@ -54,8 +60,11 @@ impl InputCore {
 
     pub fn clear(&mut self) {
         self.mouse_state.reset();
+        self.explode = false;
         self.reset = false;
         self.tick = false;
+        self.seek = 0;
+        self.speed = 0;
     }
 
     pub fn key_up(&mut self, key: Keycode) {
@@ -64,7 +73,12 @@ impl InputCore {
                 Keycode::Escape => self.quit = true,
                 Keycode::Z => self.debug = !self.debug,
                 Keycode::R => self.reset = true,
+                Keycode::X => self.explode = true,
                 Keycode::Return => self.tick = true,
+                Keycode::Left => self.seek -= 1,
+                Keycode::Right => self.seek += 1,
+                Keycode::Up => self.speed += 1,
+                Keycode::Down => self.speed -= 1,
                 Keycode::Space => {
                     self.advance_simulation = !self.advance_simulation
                 }
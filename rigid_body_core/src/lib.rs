@@ -1,41 +1,47 @@
 pub mod config;
+mod error;
 pub mod input;
 pub mod math;
 pub mod mesh;
+pub mod offscreen;
+pub mod recording;
 pub mod render;
+#[cfg(feature = "scene")]
+pub mod scene;
 mod simulation;
 pub mod utility;
 
+pub use error::{Error, Result};
 use input::{
     camera_mover::{CameraMode, CameraMover},
     InputCore,
 };
 use math::vector::Vector3d;
+use recording::{Playback, Recorder};
 use render::{
-    Camera, Color, Draw3dTrait, RenderOption, RendererCore, ScreenBufferTrait,
+    Camera, Color, Draw3dTrait, RenderConfig, RenderOption, RendererCore,
+    ScreenBufferTrait,
 };
 use rigid_body::RigidBody;
-pub use simulation::{rigid_body, Contact, SeparatingPlane, Simulation};
-use std::cell::Cell;
+pub use simulation::{
+    bounding_box, diagnostics, event_log, explosion, force_manager, half_space,
+    health, island, profiler, rigid_body, shape, BroadphaseKind, Contact,
+    Integrator, SeparatingPlane, Simulation, SimulationConfig, Snapshot,
+};
 use utility::FPSManager;
 
 pub type UID = usize;
 
-fn get_new_uid() -> UID {
-    thread_local!(static CURRENT_UID: Cell<UID> = Cell::new(0));
-    CURRENT_UID.with(|thread_id| {
-        let uid = thread_id.get();
-        thread_id.set(uid + 1);
-        uid
-    })
-}
-
 pub struct RigidBodySimulationCore {
     pub input: InputCore,
     pub camera_mover: CameraMover,
+    pub explosion: explosion::Explosion,
     pub renderer: RendererCore,
     simulation: Simulation,
     fps_manager_opt: Option<FPSManager>,
+    recorder_opt: Option<Recorder>,
+    playback_opt: Option<Playback>,
+    health_issue_opt: Option<health::HealthIssue>,
 }
 
 impl RigidBodySimulationCore {
@@ -52,14 +58,34 @@ impl RigidBodySimulationCore {
                 move_fact: 0.1,
                 mode: CameraMode::Fps,
             },
+            explosion: explosion::Explosion::default(),
             fps_manager_opt: None,
+            recorder_opt: None,
+            playback_opt: None,
+            health_issue_opt: None,
         }
     }
 
+    pub fn health_issue(&self) -> Option<&health::HealthIssue> {
+        self.health_issue_opt.as_ref()
+    }
+
     pub fn set_window_size(&mut self, window_size: (u32, u32)) {
         self.renderer.set_window_size(window_size);
     }
 
+    pub fn simulation(&self) -> &Simulation {
+        &self.simulation
+    }
+
+    pub fn simulation_mut(&mut self) -> &mut Simulation {
+        &mut self.simulation
+    }
+
+    pub fn take_health_issue(&mut self) -> Option<health::HealthIssue> {
+        self.health_issue_opt.take()
+    }
+
     pub fn tick(&mut self) -> bool {
         self.handle_input();
         self.render();
@@ -73,24 +99,78 @@ impl RigidBodySimulationCore {
         self.set_debug(self.input.debug);
         self.camera_mover
             .move_camera(&self.input, self.renderer.camera_mut());
-        if self.input.reset {
-            self.simulation.reset();
-        }
-        if self.input.advance_simulation || self.input.tick {
-            self.simulation.tick(
-                if let Some(fps_manager) = &mut self.fps_manager_opt {
-                    fps_manager.frame_duration.as_micros() as f64 / 1_000_000.
-                } else {
-                    1. / 60.
-                },
-            );
+        let delta_t = if let Some(fps_manager) = &self.fps_manager_opt {
+            fps_manager.frame_duration.as_micros() as f64 / 1_000_000.
+        } else {
+            self.simulation.config().delta_t
+        };
+        if let Some(playback) = &mut self.playback_opt {
+            Self::handle_playback_input(&self.input, delta_t, playback);
+            playback.apply(self.simulation.rigid_bodies_mut());
+        } else {
+            if self.input.reset {
+                self.simulation.reset();
+                self.health_issue_opt = None;
+            }
+            if self.input.explode {
+                self.simulation.explode(
+                    &self.camera_mover.center,
+                    self.explosion.radius,
+                    self.explosion.strength,
+                    self.explosion.falloff,
+                );
+            }
+            if self.input.advance_simulation || self.input.tick {
+                match self.simulation.tick(delta_t) {
+                    Ok(()) => {
+                        if let Some(recorder) = &mut self.recorder_opt {
+                            recorder.advance(delta_t);
+                            recorder.record(self.simulation.rigid_bodies());
+                        }
+                    }
+                    Err(error) => {
+                        if let Error::Health(issue) = error {
+                            self.health_issue_opt = Some(issue);
+                        }
+                        self.input.advance_simulation = false;
+                    }
+                }
+            }
         }
         self.input.clear();
     }
 
-    fn render(&mut self) {
+    fn handle_playback_input(
+        input: &InputCore,
+        delta_t: f64,
+        playback: &mut Playback,
+    ) {
+        playback.set_paused(!input.advance_simulation);
+        if input.speed != 0 {
+            playback.set_speed(playback.speed() * 2f64.powi(input.speed));
+        }
+        if input.reset {
+            playback.seek(playback.recording().start_time());
+        }
+        if input.seek != 0 {
+            playback.seek(playback.time() + f64::from(input.seek));
+        }
+        if input.tick {
+            playback.step(1);
+        }
+        playback.advance(delta_t);
+    }
+
+    pub fn render(&mut self) {
+        if let Some(profiler) = self.simulation.profiler_mut() {
+            profiler.begin(profiler::ProfileStage::Rendering);
+        }
         self.renderer.clear(Color::rgb(0, 0, 0));
         self.renderer.render_simulation(&self.simulation);
+        if let Some(profiler) = self.simulation.profiler_mut() {
+            profiler.end();
+            profiler.amend_frame();
+        }
     }
 }
 
@@ -101,14 +181,32 @@ pub trait RigidBodySimulationCoreAccess {
 }
 
 pub trait RigidBodySimulationTrait: RigidBodySimulationCoreAccess {
+    fn add_force_generator(
+        &mut self,
+        force_generator: Box<dyn force_manager::ForceGenerator>,
+    ) {
+        self.rigid_body_simulation_core_access()
+            .simulation
+            .add_force_generator(force_generator);
+    }
+
+    fn add_half_space(
+        &mut self,
+        half_space: half_space::HalfSpace,
+        render_opt: RenderOption,
+    ) {
+        let core = self.rigid_body_simulation_core_access();
+        let uid = core.simulation.add_half_space(half_space);
+        core.renderer.set_uid(uid, render_opt);
+    }
+
     fn add_rigid_body(
         &mut self,
         rigid_body: RigidBody,
         render_opt: RenderOption,
     ) {
         let core = self.rigid_body_simulation_core_access();
-        let uid = rigid_body.uid();
-        core.simulation.add_rigid_body(rigid_body);
+        let uid = core.simulation.add_rigid_body(rigid_body);
         core.renderer.set_uid(uid, render_opt);
     }
 
@@ -138,7 +236,7 @@ pub trait RigidBodySimulationTrait: RigidBodySimulationCoreAccess {
                     .inner_opt
                     .as_ref()
                     .unwrap()
-                    .rigid_body_uids
+                    .half_space_uids
                 {
                     core.renderer.set_uid(*uid, RenderOption::Invisible);
                 }
@@ -147,6 +245,36 @@ pub trait RigidBodySimulationTrait: RigidBodySimulationCoreAccess {
         }
     }
 
+    fn set_bounding_box_motion(
+        &mut self,
+        motion_opt: Option<Box<dyn bounding_box::BoundingBoxMotion>>,
+    ) {
+        self.rigid_body_simulation_core_access()
+            .simulation
+            .set_bounding_box_motion(motion_opt);
+    }
+
+    fn set_broadphase(
+        &mut self,
+        broadphase_kind: BroadphaseKind,
+    ) -> Result<()> {
+        self.rigid_body_simulation_core_access()
+            .simulation
+            .set_broadphase(broadphase_kind)
+    }
+
+    fn restore(&mut self, snapshot: &Snapshot) {
+        self.rigid_body_simulation_core_access()
+            .simulation
+            .restore(snapshot);
+    }
+
+    fn snapshot(&mut self) -> Snapshot {
+        self.rigid_body_simulation_core_access()
+            .simulation
+            .snapshot()
+    }
+
     fn set_debug(&mut self, set: bool) {
         let core = self.rigid_body_simulation_core_access();
         core.simulation.set_debug(set);
@@ -161,6 +289,65 @@ pub trait RigidBodySimulationTrait: RigidBodySimulationCoreAccess {
             core.fps_manager_opt = Some(FPSManager::new(fps));
         }
     }
+
+    fn set_health_policy(&mut self, health_policy: health::HealthPolicy) {
+        self.rigid_body_simulation_core_access()
+            .simulation
+            .set_health_policy(health_policy);
+    }
+
+    fn set_integrator(&mut self, integrator: Integrator) {
+        self.rigid_body_simulation_core_access()
+            .simulation
+            .set_integrator(integrator);
+    }
+
+    fn set_periodic_domain(
+        &mut self,
+        periodic_domain_opt: Option<bounding_box::PeriodicDomain>,
+    ) {
+        self.rigid_body_simulation_core_access()
+            .simulation
+            .set_periodic_domain(periodic_domain_opt);
+    }
+
+    fn set_playback(&mut self, playback_opt: Option<Playback>) {
+        let core = self.rigid_body_simulation_core_access();
+        if let Some(playback) = &playback_opt {
+            playback.apply(core.simulation.rigid_bodies_mut());
+        }
+        core.playback_opt = playback_opt;
+    }
+
+    fn set_profiler(&mut self, profiler_opt: Option<profiler::Profiler>) {
+        self.rigid_body_simulation_core_access()
+            .simulation
+            .set_profiler(profiler_opt);
+    }
+
+    fn set_render_config(&mut self, config: &RenderConfig) {
+        self.rigid_body_simulation_core_access()
+            .renderer
+            .set_render_config(config);
+    }
+
+    fn set_recorder(
+        &mut self,
+        recorder_opt: Option<Recorder>,
+    ) -> Option<Recorder> {
+        let core = self.rigid_body_simulation_core_access();
+        let mut recorder_opt = recorder_opt;
+        if let Some(recorder) = &mut recorder_opt {
+            recorder.record(core.simulation.rigid_bodies());
+        }
+        std::mem::replace(&mut core.recorder_opt, recorder_opt)
+    }
+
+    fn set_simulation_config(&mut self, config: SimulationConfig) {
+        self.rigid_body_simulation_core_access()
+            .simulation
+            .set_config(config);
+    }
 }
 
 impl RigidBodySimulationCoreAccess for RigidBodySimulationCore {
@@ -1,26 +1,595 @@
 use rigid_body_core::{
-    math::{matrix::Matrix3x3, vector::Vector3d},
-    rigid_body::RigidBody,
-    Simulation,
+    config,
+    diagnostics::{Diagnostics, DiagnosticsQuantity, DriftStatistics},
+    math::vector::Vector3d,
+    offscreen::{FrameOutput, RigidBodySimulationOffscreen},
+    profiler::{ProfileCounter, ProfileStage, Profiler},
+    render::{Camera, ImageFormat},
+    Error, Integrator, Result, RigidBodySimulationCoreAccess,
+    RigidBodySimulationTrait, Simulation,
 };
-use std::f64::consts::PI;
-
-fn main() -> Result<(), String> {
-    let mut rbs = Simulation::new();
-    rbs.add_rigid_body(RigidBody::cuboid(
-        &Vector3d::new(5., 5., 5.),
-        1.,
-        &Vector3d::new(0., 0., 0.),
-        &Matrix3x3::identity(),
-        &Vector3d::new(
-            20. * (30. * PI / 180.).cos(),
-            20. * (30. * PI / 180.).sin(),
-            0.,
+use std::{
+    convert::TryFrom,
+    env, fs,
+    path::Path,
+    process,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+const USAGE: &str = "\
+Usage: rigid_body_core [OPTIONS]
+
+Options:
+  --preset NAME        Built-in configuration (default: default)
+  --scene PATH         Scene file, requires the scene feature
+  -n, --count N        Body count parameter of the preset
+  --steps N            Number of ticks to run (default: 200)
+  --dt DT              Tick duration (default: simulation config delta_t)
+  --integrator NAME    euler, exponential_map or implicit_gyroscopic
+  --format FORMAT      json or csv (default: json)
+  --output PATH        Write the report to PATH instead of stdout
+  --frames DIR         Render frames to image files in DIR
+  --every K            Render every K-th tick (default: 1)
+  --image-format NAME  png or ppm (default: png)
+  --resolution WxH     Rendered frame size (default: 640x480)
+  --camera X,Y,Z[,THETA_X,THETA_Y]
+                       Camera position and rotation in radians
+  -h, --help           Print this message
+
+Presets:
+  default, bounding_box_external, coincident, container_debris,
+  floating_crates, floor_debris, icosphere, immovable, periodic_flow,
+  regular_icosahedron, regular_tetrahedron, shapes, spinning_body";
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Format {
+    Csv,
+    Json,
+}
+
+#[derive(Clone, Debug)]
+enum Source {
+    Preset(String),
+    Scene(String),
+}
+
+#[derive(Clone)]
+struct Options {
+    source: Source,
+    count_opt: Option<usize>,
+    steps: usize,
+    delta_t_opt: Option<f64>,
+    integrator_opt: Option<Integrator>,
+    format: Format,
+    output_opt: Option<String>,
+    frame_output_opt: Option<FrameOutput>,
+    resolution: (u32, u32),
+    camera_opt: Option<Camera>,
+}
+
+impl Options {
+    fn parse(args: impl Iterator<Item = String>) -> Result<Option<Self>> {
+        let mut ret = Self {
+            source: Source::Preset("default".into()),
+            count_opt: None,
+            steps: 200,
+            delta_t_opt: None,
+            integrator_opt: None,
+            format: Format::Json,
+            output_opt: None,
+            frame_output_opt: None,
+            resolution: (640, 480),
+            camera_opt: None,
+        };
+        let mut frames_opt = None;
+        let mut every = 1;
+        let mut image_format = ImageFormat::default();
+        let mut args = args;
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next().ok_or_else(|| {
+                    Error::InvalidValue(format!("{} requires a value.", arg))
+                })
+            };
+            match arg.as_str() {
+                "--preset" => ret.source = Source::Preset(value()?),
+                "--scene" => ret.source = Source::Scene(value()?),
+                "-n" | "--count" => ret.count_opt = Some(parse(&value()?)?),
+                "--steps" => ret.steps = parse(&value()?)?,
+                "--dt" => ret.delta_t_opt = Some(parse(&value()?)?),
+                "--integrator" => {
+                    ret.integrator_opt = Some(match value()?.as_str() {
+                        "euler" => Integrator::Euler,
+                        "exponential_map" => Integrator::ExponentialMap,
+                        "implicit_gyroscopic" => Integrator::ImplicitGyroscopic,
+                        name => {
+                            return Err(Error::InvalidValue(format!(
+                                "Unknown integrator {}.",
+                                name
+                            )))
+                        }
+                    })
+                }
+                "--format" => {
+                    ret.format = match value()?.as_str() {
+                        "csv" => Format::Csv,
+                        "json" => Format::Json,
+                        name => {
+                            return Err(Error::InvalidValue(format!(
+                                "Unknown format {}.",
+                                name
+                            )))
+                        }
+                    }
+                }
+                "--output" => ret.output_opt = Some(value()?),
+                "--frames" => frames_opt = Some(value()?),
+                "--every" => {
+                    every = parse(&value()?)?;
+                    if every == 0 {
+                        return Err(Error::InvalidValue(format!(
+                            "Invalid every {}.",
+                            every
+                        )));
+                    }
+                }
+                "--image-format" => {
+                    image_format = match value()?.as_str() {
+                        "png" => ImageFormat::Png,
+                        "ppm" => ImageFormat::Ppm,
+                        name => {
+                            return Err(Error::InvalidValue(format!(
+                                "Unknown image format {}.",
+                                name
+                            )))
+                        }
+                    }
+                }
+                "--resolution" => {
+                    let resolution = value()?;
+                    ret.resolution = match resolution
+                        .split('x')
+                        .map(parse)
+                        .collect::<Result<Vec<u32>>>()?
+                        .as_slice()
+                    {
+                        [width, height] if *width > 0 && *height > 0 => {
+                            (*width, *height)
+                        }
+                        _ => {
+                            return Err(Error::InvalidValue(format!(
+                                "Invalid resolution {}.",
+                                resolution
+                            )))
+                        }
+                    }
+                }
+                "--camera" => {
+                    let camera = value()?;
+                    ret.camera_opt = Some(
+                        match camera
+                            .split(',')
+                            .map(parse)
+                            .collect::<Result<Vec<f64>>>()?
+                            .as_slice()
+                        {
+                            [x, y, z] => {
+                                Camera::new(&Vector3d::new(*x, *y, *z), 0., 0.)
+                            }
+                            [x, y, z, theta_x, theta_y] => Camera::new(
+                                &Vector3d::new(*x, *y, *z),
+                                *theta_x,
+                                *theta_y,
+                            ),
+                            _ => {
+                                return Err(Error::InvalidValue(format!(
+                                    "Invalid camera {}.",
+                                    camera
+                                )))
+                            }
+                        },
+                    )
+                }
+                "-h" | "--help" => return Ok(None),
+                _ => {
+                    return Err(Error::InvalidValue(format!(
+                        "Unknown argument {}.",
+                        arg
+                    )))
+                }
+            }
+        }
+        ret.frame_output_opt = frames_opt.map(|directory| FrameOutput {
+            every,
+            ..FrameOutput::new(directory, image_format)
+        });
+        match ret.delta_t_opt {
+            Some(delta_t) if !(delta_t > 0. && delta_t.is_finite()) => {
+                Err(Error::InvalidValue(format!("Invalid dt {}.", delta_t)))
+            }
+            _ => Ok(Some(ret)),
+        }
+    }
+}
+
+fn parse<T: FromStr>(value: &str) -> Result<T> {
+    value
+        .parse()
+        .map_err(|_| Error::InvalidValue(format!("Invalid number {}.", value)))
+}
+
+struct Report {
+    steps: usize,
+    delta_t: f64,
+    wall_time: Duration,
+}
+
+struct StageTiming {
+    name: String,
+    total_ms: f64,
+    mean_ms: f64,
+}
+
+struct CounterTotal {
+    name: String,
+    total: u64,
+}
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    }
+}
+
+fn run() -> Result<()> {
+    let options = match Options::parse(env::args().skip(1))? {
+        Some(options) => options,
+        None => {
+            println!("{}", USAGE);
+            return Ok(());
+        }
+    };
+    let mut offscreen = RigidBodySimulationOffscreen::new(options.resolution);
+    load(&options, &mut offscreen)?;
+    if let Some(integrator) = options.integrator_opt {
+        offscreen.set_integrator(integrator);
+    }
+    if let Some(camera) = &options.camera_opt {
+        *offscreen.camera_mut() = *camera;
+    }
+    if let Some(frame_output) = &options.frame_output_opt {
+        fs::create_dir_all(&frame_output.directory)
+            .map_err(|e| Error::from(e).path(&frame_output.directory))?;
+    }
+    offscreen.set_frame_output(options.frame_output_opt.clone());
+    let simulation = offscreen
+        .rigid_body_simulation_core_access()
+        .simulation_mut();
+    let delta_t = options
+        .delta_t_opt
+        .unwrap_or_else(|| simulation.config().delta_t);
+    simulation.set_diagnostics(Some(Diagnostics::default()));
+    simulation.set_profiler(Some(Profiler::new(options.steps.max(1))));
+    let start = Instant::now();
+    offscreen.render_frame()?;
+    for _ in 0..options.steps {
+        offscreen.tick(delta_t)?;
+        let simulation = offscreen
+            .rigid_body_simulation_core_access()
+            .simulation_mut();
+        for entry in simulation.event_log_mut().drain() {
+            eprintln!("{}", entry);
+        }
+    }
+    let report = Report {
+        steps: options.steps,
+        delta_t,
+        wall_time: start.elapsed(),
+    };
+    let simulation = offscreen
+        .rigid_body_simulation_core_access()
+        .simulation_mut();
+    let text = match options.format {
+        Format::Csv => csv(&report, simulation),
+        Format::Json => json(&report, simulation),
+    };
+    match &options.output_opt {
+        Some(path) => fs::write(path, text)
+            .map_err(|e| Error::from(e).path(Path::new(path))),
+        None => {
+            print!("{}", text);
+            Ok(())
+        }
+    }
+}
+
+fn load(
+    options: &Options,
+    core: &mut impl RigidBodySimulationTrait,
+) -> Result<()> {
+    let name = match &options.source {
+        Source::Preset(name) => name,
+        #[cfg(feature = "scene")]
+        Source::Scene(path) => {
+            return rigid_body_core::scene::load(path, core);
+        }
+        #[cfg(not(feature = "scene"))]
+        Source::Scene(path) => {
+            return Err(Error::InvalidValue(format!(
+                "Scene file {} requires the scene feature.",
+                path
+            )));
+        }
+    };
+    let count = |default: usize| options.count_opt.unwrap_or(default);
+    match name.as_str() {
+        "default" => config::default(count(8), core),
+        "bounding_box_external" => config::test::bounding_box_external(core),
+        "coincident" => config::test::coincident(core),
+        "container_debris" => config::test::container_debris(count(20), core),
+        "floating_crates" => config::test::floating_crates(count(4), core),
+        "floor_debris" => config::test::floor_debris(count(20), core),
+        "icosphere" => {
+            let n = u8::try_from(count(2)).map_err(|_| {
+                Error::InvalidValue("Icosphere count exceeds 255.".into())
+            })?;
+            config::test::icosphere(n, core)
+        }
+        "immovable" => config::test::immovable(core),
+        "periodic_flow" => config::test::periodic_flow(count(4), core),
+        "regular_icosahedron" => config::test::regular_icosahedron(core),
+        "regular_tetrahedron" => config::test::regular_tetrahedron(core),
+        "shapes" => config::test::shapes(core),
+        "spinning_body" => config::test::spinning_body(
+            options.integrator_opt.unwrap_or_default(),
+            core,
         ),
-        &Vector3d::new(0., 0., 0.),
-    ));
-    for _ in 0..200 {
-        rbs.tick(0.01);
+        _ => Err(Error::InvalidValue(format!("Unknown preset {}.", name))),
+    }
+}
+
+fn json(report: &Report, simulation: &Simulation) -> String {
+    let vector = |vector: &Vector3d| {
+        format!(
+            "[{}, {}, {}]",
+            json_number(vector[0]),
+            json_number(vector[1]),
+            json_number(vector[2])
+        )
+    };
+    let rigid_bodies = simulation
+        .rigid_bodies()
+        .iter()
+        .map(|rigid_body| {
+            let quarternion = &rigid_body.quarternion;
+            format!(
+                "    {{\"uid\": {}, \"position\": {}, \"quarternion\": \
+                 [{}, {}, {}, {}], \"momentum\": {}, \"angular_momentum\": {}}}",
+                rigid_body.uid(),
+                vector(&rigid_body.position),
+                json_number(quarternion.s()),
+                json_number(quarternion.v()[0]),
+                json_number(quarternion.v()[1]),
+                json_number(quarternion.v()[2]),
+                vector(&rigid_body.momentum),
+                vector(&rigid_body.angular_momentum),
+            )
+        })
+        .collect::<Vec<_>>();
+    let mut diagnostics = Vec::new();
+    if let Some(sample) = simulation
+        .diagnostics()
+        .and_then(|diagnostics| diagnostics.latest())
+    {
+        diagnostics.push(format!(
+            "    \"kinetic_energy\": {}",
+            json_number(sample.kinetic_energy)
+        ));
+        diagnostics.push(format!(
+            "    \"potential_energy\": {}",
+            json_number(sample.potential_energy)
+        ));
+        diagnostics.push(format!(
+            "    \"total_energy\": {}",
+            json_number(sample.total_energy())
+        ));
+        diagnostics.push(format!(
+            "    \"linear_momentum\": {}",
+            vector(&sample.linear_momentum)
+        ));
+        diagnostics.push(format!(
+            "    \"angular_momentum\": {}",
+            vector(&sample.angular_momentum)
+        ));
+    }
+    for (name, statistics) in drift_statistics(simulation) {
+        diagnostics.push(format!(
+            "    \"{}_drift\": {{\"current\": {}, \"max\": {}, \"mean\": {}, \
+             \"rms\": {}}}",
+            name,
+            json_number(statistics.current),
+            json_number(statistics.max),
+            json_number(statistics.mean),
+            json_number(statistics.rms)
+        ));
+    }
+    let (stages, counters) = timings(simulation);
+    let stages = stages
+        .iter()
+        .map(|stage| {
+            format!(
+                "    \"{}\": {{\"total_ms\": {}, \"mean_ms\": {}}}",
+                stage.name, stage.total_ms, stage.mean_ms
+            )
+        })
+        .collect::<Vec<_>>();
+    let counters = counters
+        .iter()
+        .map(|counter| format!("    \"{}\": {}", counter.name, counter.total))
+        .collect::<Vec<_>>();
+    format!(
+        "{{\n  \"steps\": {},\n  \"delta_t\": {},\n  \"time\": {},\n  \
+         \"wall_time_ms\": {},\n  \"state_hash\": {},\n  \"rigid_bodies\": \
+         [\n{}\n  ],\n  \"diagnostics\": {{\n{}\n  }},\n  \"stages\": \
+         {{\n{}\n  }},\n  \"counters\": {{\n{}\n  }}\n}}\n",
+        report.steps,
+        json_number(report.delta_t),
+        json_number(simulation.time()),
+        millis(report.wall_time),
+        simulation.state_hash(),
+        rigid_bodies.join(",\n"),
+        diagnostics.join(",\n"),
+        stages.join(",\n"),
+        counters.join(",\n"),
+    )
+}
+
+fn csv(report: &Report, simulation: &Simulation) -> String {
+    let mut ret = String::from(
+        "uid,position_x,position_y,position_z,quarternion_s,quarternion_x,\
+         quarternion_y,quarternion_z,momentum_x,momentum_y,momentum_z,\
+         angular_momentum_x,angular_momentum_y,angular_momentum_z\n",
+    );
+    for rigid_body in simulation.rigid_bodies() {
+        let quarternion = &rigid_body.quarternion;
+        let values = [
+            &rigid_body.position,
+            quarternion.v(),
+            &rigid_body.momentum,
+            &rigid_body.angular_momentum,
+        ];
+        ret += &rigid_body.uid().to_string();
+        for (i, vector) in values.iter().enumerate() {
+            if i == 1 {
+                ret += &format!(",{}", csv_number(quarternion.s()));
+            }
+            for value in vector.iter() {
+                ret += &format!(",{}", csv_number(*value));
+            }
+        }
+        ret.push('\n');
+    }
+    ret += "\nquantity,value\n";
+    ret += &format!("steps,{}\n", report.steps);
+    ret += &format!("delta_t,{}\n", csv_number(report.delta_t));
+    ret += &format!("time,{}\n", csv_number(simulation.time()));
+    ret += &format!("wall_time_ms,{}\n", millis(report.wall_time));
+    ret += &format!("state_hash,{}\n", simulation.state_hash());
+    if let Some(sample) = simulation
+        .diagnostics()
+        .and_then(|diagnostics| diagnostics.latest())
+    {
+        ret +=
+            &format!("kinetic_energy,{}\n", csv_number(sample.kinetic_energy));
+        ret += &format!(
+            "potential_energy,{}\n",
+            csv_number(sample.potential_energy)
+        );
+        ret += &format!("total_energy,{}\n", csv_number(sample.total_energy()));
+    }
+    for (name, statistics) in drift_statistics(simulation) {
+        for (suffix, value) in &[
+            ("current", statistics.current),
+            ("max", statistics.max),
+            ("mean", statistics.mean),
+            ("rms", statistics.rms),
+        ] {
+            ret +=
+                &format!("{}_drift_{},{}\n", name, suffix, csv_number(*value));
+        }
+    }
+    let (stages, counters) = timings(simulation);
+    ret += "\nstage,total_ms,mean_ms\n";
+    for stage in stages {
+        ret +=
+            &format!("{},{},{}\n", stage.name, stage.total_ms, stage.mean_ms);
+    }
+    ret += "\ncounter,total\n";
+    for counter in counters {
+        ret += &format!("{},{}\n", counter.name, counter.total);
+    }
+    ret
+}
+
+fn drift_statistics(simulation: &Simulation) -> Vec<(String, DriftStatistics)> {
+    simulation
+        .diagnostics()
+        .map(|diagnostics| {
+            DiagnosticsQuantity::ALL
+                .iter()
+                .map(|quantity| {
+                    (snake_case(quantity), diagnostics.statistics(*quantity))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn timings(simulation: &Simulation) -> (Vec<StageTiming>, Vec<CounterTotal>) {
+    let profiler = match simulation.profiler() {
+        Some(profiler) => profiler,
+        None => return (Vec::new(), Vec::new()),
+    };
+    let average = profiler.average();
+    let stages = ProfileStage::ALL
+        .iter()
+        .map(|stage| {
+            let total = profiler
+                .frames()
+                .map(|frame| frame.duration(*stage))
+                .sum::<Duration>();
+            StageTiming {
+                name: snake_case(stage),
+                total_ms: millis(total),
+                mean_ms: millis(average.duration(*stage)),
+            }
+        })
+        .collect();
+    let counters = ProfileCounter::ALL
+        .iter()
+        .map(|counter| {
+            let total = profiler
+                .frames()
+                .map(|frame| frame.count(*counter))
+                .sum::<u64>();
+            CounterTotal {
+                name: snake_case(counter),
+                total,
+            }
+        })
+        .collect();
+    (stages, counters)
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.
+}
+
+fn csv_number(value: f64) -> String {
+    if value.is_finite() {
+        value.to_string()
+    } else {
+        String::new()
+    }
+}
+
+fn json_number(value: f64) -> String {
+    if value.is_finite() {
+        value.to_string()
+    } else {
+        String::from("null")
+    }
+}
+
+fn snake_case(name: &impl std::fmt::Debug) -> String {
+    let mut ret = String::new();
+    for c in format!("{:?}", name).chars() {
+        if c.is_uppercase() && !ret.is_empty() {
+            ret.push('_');
+        }
+        ret.extend(c.to_lowercase());
     }
-    Ok(())
+    ret
 }
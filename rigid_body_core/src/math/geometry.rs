@@ -82,6 +82,31 @@ pub fn plane_line_intersection(
     )
 }
 
+pub fn cylinder_support(
+    core: &FiniteLine3d,
+    radius: f64,
+    dir: &Vector3d,
+) -> Vector3d {
+    let axis = core.end.sub(&core.start);
+    let cap = if axis.dot(dir) >= 0. {
+        core.end
+    } else {
+        core.start
+    };
+    let axis_mag_sq = axis.mag_sq();
+    let radial = if axis_mag_sq > 0. {
+        dir.sub(&axis.scale(axis.dot(dir) / axis_mag_sq))
+    } else {
+        *dir
+    };
+    let radial_mag = radial.mag();
+    if radial_mag > f64::EPSILON * dir.mag() {
+        cap.add(&radial.scale(radius / radial_mag))
+    } else {
+        cap
+    }
+}
+
 pub fn plane_finite_line_intersection(
     plane: &Plane,
     pos_1: &Vector3d,
@@ -118,6 +143,55 @@ pub fn pos_in_front_of_plane(pos: &Vector3d, plane: &Plane) -> bool {
     pos_plane_signed_dist(pos, plane) > 0.
 }
 
+pub fn pos_cylinder_closest(
+    pos: &Vector3d,
+    core: &FiniteLine3d,
+    radius: f64,
+) -> (f64, Vector3d, Vector3d) {
+    let center = core.start.add(&core.end).scale(0.5);
+    let half_axis = core.end.sub(&center);
+    let half_height = half_axis.mag();
+    let axis = if half_height > 0. {
+        half_axis.scale(1. / half_height)
+    } else {
+        Vector3d::new(0., 1., 0.)
+    };
+    let rel = pos.sub(&center);
+    let axial = rel.dot(&axis);
+    let radial = rel.sub(&axis.scale(axial));
+    let radial_mag = radial.mag();
+    let radial_dir = if radial_mag > f64::EPSILON {
+        radial.scale(1. / radial_mag)
+    } else {
+        let other = if axis[0].abs() < 0.9 {
+            Vector3d::new(1., 0., 0.)
+        } else {
+            Vector3d::new(0., 1., 0.)
+        };
+        axis.cross(&other).normal()
+    };
+    let cap_dir = axis.scale(axial.signum());
+    let cap_dist = axial.abs() - half_height;
+    let side_dist = radial_mag - radius;
+    let cap_closest = center.add(&cap_dir.scale(half_height));
+    if cap_dist > 0. && side_dist > 0. {
+        let closest = cap_closest.add(&radial_dir.scale(radius));
+        let dist = pos.dist(&closest);
+        return (dist, closest, pos.sub(&closest).scale(1. / dist));
+    }
+    if cap_dist > side_dist {
+        (cap_dist, cap_closest.add(&radial), cap_dir)
+    } else {
+        (
+            side_dist,
+            center
+                .add(&axis.scale(axial))
+                .add(&radial_dir.scale(radius)),
+            radial_dir,
+        )
+    }
+}
+
 pub fn raw_finite_line_closest_dist_sq(
     start_1: &Vector3d,
     end_1: &Vector3d,
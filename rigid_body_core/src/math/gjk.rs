@@ -0,0 +1,348 @@
+use super::vector::Vector3d;
+
+const MAX_ITERATIONS: usize = 64;
+const TOLERANCE: f64 = 1e-10;
+
+#[derive(Clone, Copy, Debug)]
+struct SupportPoint {
+    point: Vector3d,
+    position_1: Vector3d,
+    position_2: Vector3d,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Face {
+    indices: [usize; 3],
+    normal: Vector3d,
+    dist: f64,
+}
+
+pub fn signed_dist<S1, S2>(
+    support_1: S1,
+    support_2: S2,
+    direction: &Vector3d,
+) -> (f64, Vector3d, Vector3d, Vector3d)
+where
+    S1: Fn(&Vector3d) -> Vector3d,
+    S2: Fn(&Vector3d) -> Vector3d,
+{
+    let support = |direction: &Vector3d| {
+        let position_1 = support_1(direction);
+        let position_2 = support_2(&direction.scale(-1.));
+        SupportPoint {
+            point: position_1.sub(&position_2),
+            position_1,
+            position_2,
+        }
+    };
+    let direction = if direction.mag_sq() > TOLERANCE {
+        *direction
+    } else {
+        Vector3d::new(1., 0., 0.)
+    };
+    let mut simplex = vec![support(&direction.scale(-1.))];
+    let mut v = simplex[0].point;
+    for _ in 0..MAX_ITERATIONS {
+        if v.mag_sq() <= TOLERANCE * TOLERANCE || simplex.len() == 4 {
+            return penetration(simplex, &support, &direction);
+        }
+        let w = support(&v.scale(-1.));
+        let v_sq = v.mag_sq();
+        if v_sq - v.dot(&w.point) <= TOLERANCE * v_sq.max(1.)
+            || simplex
+                .iter()
+                .any(|e| e.point.dist_sq(&w.point) <= TOLERANCE * TOLERANCE)
+        {
+            break;
+        }
+        simplex.push(w);
+        let (closest, reduced) = closest_on_simplex(&simplex);
+        v = closest;
+        simplex = reduced;
+    }
+    if v.mag_sq() <= TOLERANCE * TOLERANCE {
+        return penetration(simplex, &support, &direction);
+    }
+    let (position_1, position_2) = witness(&simplex, &v);
+    let dist = v.mag();
+    (dist, position_1, position_2, v.scale(1. / dist))
+}
+
+fn closest_on_simplex(
+    simplex: &[SupportPoint],
+) -> (Vector3d, Vec<SupportPoint>) {
+    let mut ret = (simplex[0].point, vec![simplex[0]]);
+    let mut min_dist_sq = ret.0.mag_sq();
+    for subset in 1usize..1 << simplex.len() {
+        let points = (0..simplex.len())
+            .filter(|k| subset & (1 << k) != 0)
+            .map(|k| simplex[k])
+            .collect::<Vec<_>>();
+        let lambdas = match affine_closest(&points) {
+            Some(lambdas) => lambdas,
+            None => continue,
+        };
+        if lambdas.iter().any(|lambda| *lambda <= 0.) {
+            continue;
+        }
+        let mut closest = Vector3d::default();
+        for (point, lambda) in points.iter().zip(&lambdas) {
+            closest.add_assign(&point.point.scale(*lambda));
+        }
+        let dist_sq = closest.mag_sq();
+        if dist_sq < min_dist_sq {
+            min_dist_sq = dist_sq;
+            ret = (closest, points);
+        }
+    }
+    ret
+}
+
+fn affine_closest(points: &[SupportPoint]) -> Option<Vec<f64>> {
+    let base = points[0].point;
+    let edges = points[1..]
+        .iter()
+        .map(|e| e.point.sub(&base))
+        .collect::<Vec<_>>();
+    let mut m = [[0.; 4]; 3];
+    for (row, edge_1) in edges.iter().enumerate() {
+        for (column, edge_2) in edges.iter().enumerate() {
+            m[row][column] = edge_1.dot(edge_2);
+        }
+        m[row][3] = -edge_1.dot(&base);
+    }
+    let mus = solve(&mut m, edges.len())?;
+    let mut ret = vec![1. - mus.iter().sum::<f64>()];
+    ret.extend(mus);
+    Some(ret)
+}
+
+fn solve(m: &mut [[f64; 4]; 3], n: usize) -> Option<Vec<f64>> {
+    let scale = (0..n).map(|k| m[k][k]).fold(0., f64::max);
+    for column in 0..n {
+        let pivot = (column..n).max_by(|a, b| {
+            m[*a][column].abs().total_cmp(&m[*b][column].abs())
+        })?;
+        if m[pivot][column].abs() <= TOLERANCE * scale {
+            return None;
+        }
+        m.swap(column, pivot);
+        let pivot_row = m[column];
+        for (row, values) in m.iter_mut().enumerate().take(n) {
+            if row != column {
+                let factor = values[column] / pivot_row[column];
+                for (value, pivot_value) in
+                    values.iter_mut().zip(&pivot_row).skip(column)
+                {
+                    *value -= factor * pivot_value;
+                }
+            }
+        }
+    }
+    Some((0..n).map(|k| m[k][3] / m[k][k]).collect())
+}
+
+fn witness(simplex: &[SupportPoint], v: &Vector3d) -> (Vector3d, Vector3d) {
+    let lambdas = if simplex.len() == 1 {
+        vec![1.]
+    } else {
+        barycentric(simplex, v)
+    };
+    let mut position_1 = Vector3d::default();
+    let mut position_2 = Vector3d::default();
+    for (point, lambda) in simplex.iter().zip(lambdas) {
+        position_1.add_assign(&point.position_1.scale(lambda));
+        position_2.add_assign(&point.position_2.scale(lambda));
+    }
+    (position_1, position_2)
+}
+
+fn barycentric(simplex: &[SupportPoint], v: &Vector3d) -> Vec<f64> {
+    let base = simplex[0].point;
+    let edges = simplex[1..]
+        .iter()
+        .map(|e| e.point.sub(&base))
+        .collect::<Vec<_>>();
+    let target = v.sub(&base);
+    let mut m = [[0.; 4]; 3];
+    for (row, edge_1) in edges.iter().enumerate() {
+        for (column, edge_2) in edges.iter().enumerate() {
+            m[row][column] = edge_1.dot(edge_2);
+        }
+        m[row][3] = edge_1.dot(&target);
+    }
+    match solve(&mut m, edges.len()) {
+        Some(mus) => {
+            let mut ret = vec![1. - mus.iter().sum::<f64>()];
+            ret.extend(mus);
+            for lambda in &mut ret {
+                *lambda = lambda.max(0.);
+            }
+            let sum = ret.iter().sum::<f64>();
+            ret.iter().map(|lambda| lambda / sum).collect()
+        }
+        None => {
+            let mut ret = vec![0.; simplex.len()];
+            ret[0] = 1.;
+            ret
+        }
+    }
+}
+
+fn penetration<S>(
+    mut simplex: Vec<SupportPoint>,
+    support: &S,
+    direction: &Vector3d,
+) -> (f64, Vector3d, Vector3d, Vector3d)
+where
+    S: Fn(&Vector3d) -> SupportPoint,
+{
+    let touching = |simplex: &[SupportPoint], normal: &Vector3d| {
+        let (closest, reduced) = closest_on_simplex(simplex);
+        let (position_1, position_2) = witness(&reduced, &closest);
+        (0., position_1, position_2, normal.normal().scale(-1.))
+    };
+    if simplex.len() == 1 {
+        return touching(&simplex, &direction.scale(-1.));
+    }
+    if simplex.len() == 2 {
+        let edge = simplex[1].point.sub(&simplex[0].point);
+        let mut axis = Vector3d::default();
+        let min_axis = (0..3)
+            .min_by(|a, b| edge[*a].abs().total_cmp(&edge[*b].abs()))
+            .unwrap_or(0);
+        axis[min_axis] = 1.;
+        let normal = edge.cross(&axis).normal();
+        let mut extended = false;
+        for normal in &[normal, normal.scale(-1.)] {
+            let w = support(normal);
+            if w.point.dot(normal) > TOLERANCE {
+                simplex.push(w);
+                extended = true;
+                break;
+            }
+        }
+        if !extended {
+            return touching(&simplex, &direction.scale(-1.));
+        }
+    }
+    if simplex.len() == 3 {
+        let normal = simplex[1]
+            .point
+            .sub(&simplex[0].point)
+            .cross(&simplex[2].point.sub(&simplex[0].point));
+        if normal.mag_sq() <= TOLERANCE * TOLERANCE {
+            return touching(&simplex, &direction.scale(-1.));
+        }
+        let normal = normal.normal();
+        for normal in &[normal, normal.scale(-1.)] {
+            let w = support(normal);
+            if w.point.dot(normal) <= TOLERANCE {
+                return touching(&simplex[..3], normal);
+            }
+            simplex.push(w);
+        }
+    }
+    let mut centroid = Vector3d::default();
+    for point in &simplex {
+        centroid.add_assign(&point.point.scale(1. / simplex.len() as f64));
+    }
+    let mut faces = Vec::new();
+    if simplex.len() == 4 {
+        for indices in &[[0, 1, 2], [0, 3, 1], [0, 2, 3], [1, 3, 2]] {
+            faces.extend(face(&simplex, *indices, &centroid));
+        }
+    } else {
+        for indices in &[[0, 1, 3], [1, 2, 3], [2, 0, 3]] {
+            faces.extend(face(&simplex, *indices, &centroid));
+            faces.extend(face(
+                &simplex,
+                [indices[1], indices[0], 4],
+                &centroid,
+            ));
+        }
+    }
+    let mut closest_opt = None;
+    for _ in 0..MAX_ITERATIONS {
+        let closest =
+            match faces.iter().min_by(|a, b| a.dist.total_cmp(&b.dist)) {
+                Some(closest) => *closest,
+                None => break,
+            };
+        closest_opt = Some(closest);
+        let w = support(&closest.normal);
+        if w.point.dot(&closest.normal) - closest.dist <= TOLERANCE {
+            break;
+        }
+        let mut horizon: Vec<[usize; 2]> = Vec::new();
+        let before = faces.len();
+        faces.retain(|e| {
+            if e.normal.dot(&w.point.sub(&simplex[e.indices[0]].point)) <= 0. {
+                return true;
+            }
+            for k in 0..3 {
+                let edge = [e.indices[k], e.indices[(k + 1) % 3]];
+                match horizon
+                    .iter()
+                    .position(|other| *other == [edge[1], edge[0]])
+                {
+                    Some(position) => {
+                        horizon.swap_remove(position);
+                    }
+                    None => horizon.push(edge),
+                }
+            }
+            false
+        });
+        if faces.len() == before {
+            break;
+        }
+        simplex.push(w);
+        let index = simplex.len() - 1;
+        for edge in horizon {
+            faces.extend(face(&simplex, [edge[0], edge[1], index], &centroid));
+        }
+    }
+    let closest = match closest_opt {
+        Some(closest) => closest,
+        None => return touching(&simplex, &direction.scale(-1.)),
+    };
+    let triangle = closest
+        .indices
+        .iter()
+        .map(|k| simplex[*k])
+        .collect::<Vec<_>>();
+    let (position_1, position_2) =
+        witness(&triangle, &closest.normal.scale(closest.dist));
+    (
+        -closest.dist,
+        position_1,
+        position_2,
+        closest.normal.scale(-1.),
+    )
+}
+
+fn face(
+    simplex: &[SupportPoint],
+    mut indices: [usize; 3],
+    centroid: &Vector3d,
+) -> Option<Face> {
+    let a = simplex[indices[0]].point;
+    let mut normal = simplex[indices[1]]
+        .point
+        .sub(&a)
+        .cross(&simplex[indices[2]].point.sub(&a));
+    if normal.mag_sq() <= TOLERANCE * TOLERANCE {
+        return None;
+    }
+    normal.normalize();
+    if normal.dot(&a.sub(centroid)) < 0. {
+        normal.scale_assign(-1.);
+        indices.swap(0, 1);
+    }
+    Some(Face {
+        indices,
+        normal,
+        dist: normal.dot(&a),
+    })
+}
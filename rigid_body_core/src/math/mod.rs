@@ -4,6 +4,7 @@ mod quarternion;
 mod test;
 
 pub mod geometry;
+pub mod gjk;
 pub mod matrix;
 pub mod matrix_vector;
 pub mod moment_of_inertia;
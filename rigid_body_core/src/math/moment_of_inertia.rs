@@ -1,4 +1,5 @@
-use super::{matrix::Matrix3x3, vector::Vector3d};
+use super::{matrix::Matrix3x3, polyhedron::Polyhedron, vector::Vector3d};
+use crate::error::{Error, Result};
 
 pub fn aligned_cuboid(dimensions: &Vector3d, mass: f64) -> Matrix3x3 {
     let x2 = dimensions[0] * dimensions[0];
@@ -15,3 +16,90 @@ pub fn solid_sphere(radius: f64, mass: f64) -> Matrix3x3 {
 pub fn regular_tetrahedron(side_length: f64, mass: f64) -> Matrix3x3 {
     Matrix3x3::identity().scale(mass * side_length * side_length / 20.)
 }
+
+pub fn solid_capsule(radius: f64, height: f64, mass: f64) -> Matrix3x3 {
+    let r2 = radius * radius;
+    let cylinder_volume = height;
+    let sphere_volume = radius * 4. / 3.;
+    let cylinder_mass =
+        mass * cylinder_volume / (cylinder_volume + sphere_volume);
+    let sphere_mass = mass - cylinder_mass;
+    let axial = cylinder_mass * r2 / 2. + sphere_mass * r2 * 2. / 5.;
+    let transverse = cylinder_mass * (height * height / 12. + r2 / 4.)
+        + sphere_mass
+            * (r2 * 2. / 5. + height * height / 4. + height * radius * 3. / 8.);
+    Matrix3x3::new(&[
+        [transverse, 0., 0.],
+        [0., axial, 0.],
+        [0., 0., transverse],
+    ])
+}
+
+pub fn solid_cylinder(radius: f64, height: f64, mass: f64) -> Matrix3x3 {
+    let r2 = radius * radius;
+    let transverse = mass * (r2 * 3. + height * height) / 12.;
+    Matrix3x3::new(&[
+        [transverse, 0., 0.],
+        [0., mass * r2 / 2., 0.],
+        [0., 0., transverse],
+    ])
+}
+
+pub fn polyhedron(polyhedron: &Polyhedron, mass: f64) -> Result<Matrix3x3> {
+    let vertices = polyhedron.vertices();
+    let edges = polyhedron.edges();
+    let mut inside = Vector3d::default();
+    for vertex in vertices {
+        inside.add_assign(vertex);
+    }
+    inside.scale_assign(1. / vertices.len() as f64);
+    let mut volume = 0.;
+    let mut first_moment = Vector3d::default();
+    let mut covariance = Matrix3x3::default();
+    for face in polyhedron.faces() {
+        let center = face.center(vertices);
+        let outward = center.sub(&inside);
+        for edge_index in face.edge_indices() {
+            let edge = &edges[*edge_index];
+            let mut b = vertices[edge.start_index()];
+            let mut c = vertices[edge.end_index()];
+            if b.sub(&center).cross(&c.sub(&center)).dot(&outward) < 0. {
+                std::mem::swap(&mut b, &mut c);
+            }
+            let a = center;
+            let det = a.dot(&b.cross(&c));
+            let sum = a.add(&b).add(&c);
+            volume += det / 6.;
+            first_moment.add_assign(&sum.scale(det / 24.));
+            for i in 0..3 {
+                for j in 0..3 {
+                    covariance[i][j] += det / 120.
+                        * (sum[i] * sum[j]
+                            + a[i] * a[j]
+                            + b[i] * b[j]
+                            + c[i] * c[j]);
+                }
+            }
+        }
+    }
+    if volume < f64::EPSILON {
+        return Err(Error::DegenerateGeometry(
+            "Polyhedron encloses no volume.".into(),
+        ));
+    }
+    let density = mass / volume;
+    let centroid = first_moment.scale(1. / volume);
+    for i in 0..3 {
+        for j in 0..3 {
+            covariance[i][j] =
+                covariance[i][j] * density - mass * centroid[i] * centroid[j];
+        }
+    }
+    let mut ret = Matrix3x3::identity().scale(covariance.trace());
+    for i in 0..3 {
+        for j in 0..3 {
+            ret[i][j] -= covariance[i][j];
+        }
+    }
+    Ok(ret)
+}
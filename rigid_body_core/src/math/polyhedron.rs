@@ -1,6 +1,8 @@
 use super::{geometry, vector::Vector3d};
+use crate::error::{Error, Result};
+use std::f64::consts::TAU;
 
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct Polyhedron {
     faces: Vec<Face>,
     edges: Vec<Edge>,
@@ -12,15 +14,34 @@ impl Polyhedron {
         face_vertex_indices: Vec<Vec<usize>>,
         edges: Vec<Edge>,
         vertices: Vec<Vector3d>,
-    ) -> Result<Self, String> {
+    ) -> Result<Self> {
+        if vertices.is_empty() {
+            return Err(Error::DegenerateGeometry(
+                "Polyhedron without vertices.".into(),
+            ));
+        }
+        for edge in &edges {
+            if edge.start_index >= vertices.len()
+                || edge.end_index >= vertices.len()
+            {
+                return Err(Error::DegenerateGeometry(format!(
+                    "Edge vertex index out of range ({}, {}).",
+                    edge.start_index, edge.end_index
+                )));
+            }
+        }
         let mut inside = Vector3d::default();
         for vertex in &vertices {
             inside.add_assign(vertex);
         }
         inside.scale_assign(1. / vertices.len() as f64);
         let mut faces = Vec::with_capacity(face_vertex_indices.len());
-        for indices in face_vertex_indices {
-            faces.push(Face::new(indices, &vertices, &inside, &edges)?);
+        for (face_index, indices) in face_vertex_indices.into_iter().enumerate()
+        {
+            faces.push(
+                Face::new(indices, &vertices, &inside, &edges)
+                    .map_err(|e| e.context(format!("face {}", face_index)))?,
+            );
         }
         Ok(Self {
             faces,
@@ -29,7 +50,7 @@ impl Polyhedron {
         })
     }
 
-    pub fn cuboid(dimensions: &Vector3d) -> Self {
+    pub fn cuboid(dimensions: &Vector3d) -> Result<Self> {
         let hx = dimensions[0] / 2.;
         let hy = dimensions[1] / 2.;
         let hz = dimensions[2] / 2.;
@@ -68,7 +89,136 @@ impl Polyhedron {
             ],
             vertices,
         )
-        .expect("Polyhedron - cuboid")
+        .map_err(|e| e.context("cuboid"))
+    }
+
+    pub fn cylinder(radius: f64, height: f64, segments: usize) -> Result<Self> {
+        let mut vertices = Vec::with_capacity(segments * 2);
+        for y in &[height / 2., -height / 2.] {
+            for i in 0..segments {
+                let theta = TAU * i as f64 / segments as f64;
+                vertices.push(Vector3d::new(
+                    radius * theta.cos(),
+                    *y,
+                    radius * theta.sin(),
+                ));
+            }
+        }
+        let mut faces = vec![
+            (0..segments).collect::<Vec<_>>(),
+            (segments..segments * 2).collect::<Vec<_>>(),
+        ];
+        let mut edges = Vec::with_capacity(segments * 3);
+        for i in 0..segments {
+            let j = (i + 1) % segments;
+            faces.push(vec![i, j, segments + j, segments + i]);
+            edges.push(Edge::new(i, j, &vertices));
+            edges.push(Edge::new(i, segments + i, &vertices));
+            edges.push(Edge::new(segments + i, segments + j, &vertices));
+        }
+        Self::new(faces, edges, vertices).map_err(|e| e.context("cylinder"))
+    }
+
+    pub fn clipped_volume(
+        &self,
+        position: &Vector3d,
+        normal: &Vector3d,
+    ) -> (f64, Vector3d) {
+        let dists = self
+            .vertices
+            .iter()
+            .map(|vertex| vertex.sub(position).dot(normal))
+            .collect::<Vec<_>>();
+        let mut segments = Vec::with_capacity(self.edges.len());
+        let mut crossings = Vec::with_capacity(self.edges.len());
+        for edge in &self.edges {
+            let (start, end) = (edge.start_index, edge.end_index);
+            let (start_dist, end_dist) = (dists[start], dists[end]);
+            let (start_vertex, end_vertex) =
+                (&self.vertices[start], &self.vertices[end]);
+            let crossing = || {
+                start_vertex.add(
+                    &end_vertex
+                        .sub(start_vertex)
+                        .scale(start_dist / (start_dist - end_dist)),
+                )
+            };
+            match (start_dist <= 0., end_dist <= 0.) {
+                (true, true) => {
+                    segments.push(Some((*start_vertex, *end_vertex)));
+                    crossings.push(None);
+                }
+                (true, false) => {
+                    let crossing = crossing();
+                    segments.push(Some((*start_vertex, crossing)));
+                    crossings.push(Some(crossing));
+                }
+                (false, true) => {
+                    let crossing = crossing();
+                    segments.push(Some((crossing, *end_vertex)));
+                    crossings.push(Some(crossing));
+                }
+                (false, false) => {
+                    segments.push(None);
+                    crossings.push(None);
+                }
+            }
+        }
+
+        let mut reference = Vector3d::default();
+        let mut count = 0;
+        for point in self
+            .vertices
+            .iter()
+            .zip(&dists)
+            .filter(|(_, dist)| **dist <= 0.)
+            .map(|(vertex, _)| vertex)
+            .chain(crossings.iter().flatten())
+        {
+            reference.add_assign(point);
+            count += 1;
+        }
+        if count == 0 {
+            return (0., Vector3d::default());
+        }
+        reference.scale_assign(1. / count as f64);
+
+        let mut volume = 0.;
+        let mut moment = Vector3d::default();
+        let mut cap_segments = Vec::new();
+        for face in &self.faces {
+            let mut face_segments = face
+                .edge_indices
+                .iter()
+                .filter_map(|edge_index| segments[*edge_index])
+                .collect::<Vec<_>>();
+            let face_crossings = face
+                .edge_indices
+                .iter()
+                .filter_map(|edge_index| crossings[*edge_index])
+                .collect::<Vec<_>>();
+            let mut cap_segment = None;
+            let mut cap_dist_sq = 0.;
+            for (i, start) in face_crossings.iter().enumerate() {
+                for end in &face_crossings[i + 1..] {
+                    let dist_sq = start.dist_sq(end);
+                    if dist_sq > cap_dist_sq {
+                        cap_dist_sq = dist_sq;
+                        cap_segment = Some((*start, *end));
+                    }
+                }
+            }
+            if let Some(cap_segment) = cap_segment {
+                face_segments.push(cap_segment);
+                cap_segments.push(cap_segment);
+            }
+            add_cone(&reference, &face_segments, &mut volume, &mut moment);
+        }
+        add_cone(&reference, &cap_segments, &mut volume, &mut moment);
+        if volume <= 0. {
+            return (0., Vector3d::default());
+        }
+        (volume, moment.scale(1. / volume))
     }
 
     pub fn update(&mut self) {
@@ -157,10 +307,22 @@ impl Face {
         vertices: &[Vector3d],
         inside: &Vector3d,
         edges: &[Edge],
-    ) -> Result<Self, String> {
-        let face_vertex = &vertices[vertex_indices[0]];
+    ) -> Result<Self> {
+        let face_vertex = match vertex_indices.first() {
+            Some(index) if *index < vertices.len() => &vertices[*index],
+            _ => {
+                return Err(Error::DegenerateGeometry(
+                    "Face without a valid vertex.".into(),
+                ))
+            }
+        };
         let (connected_edge_indices, edge_indices) =
             Self::get_edges(&vertex_indices, edges);
+        if edge_indices.len() < 2 {
+            return Err(Error::DegenerateGeometry(
+                "Face with fewer than two edges.".into(),
+            ));
+        }
         let mut ret = Self {
             direction: Vector3d::default(),
             flip_direction: false,
@@ -178,12 +340,36 @@ impl Face {
             ret.flip_direction = true;
         }
         if ret.direction.is_nan() {
-            Err("Face with undefined direction.".into())
+            Err(Error::DegenerateGeometry(
+                "Face with undefined direction.".into(),
+            ))
         } else {
             Ok(ret)
         }
     }
 
+    pub fn area(&self, vertices: &[Vector3d], edges: &[Edge]) -> f64 {
+        let center = self.center(vertices);
+        let mut ret = 0.;
+        for edge_index in &self.edge_indices {
+            let edge = &edges[*edge_index];
+            ret += vertices[edge.start_index]
+                .sub(&center)
+                .cross(&vertices[edge.end_index].sub(&center))
+                .mag()
+                / 2.;
+        }
+        ret
+    }
+
+    pub fn center(&self, vertices: &[Vector3d]) -> Vector3d {
+        let mut ret = Vector3d::default();
+        for vertex_index in &self.vertex_indices {
+            ret.add_assign(&vertices[*vertex_index]);
+        }
+        ret.scale(1. / self.vertex_indices.len() as f64)
+    }
+
     pub fn direction(&self) -> &Vector3d {
         &self.direction
     }
@@ -289,3 +475,35 @@ impl EnclosingPlane {
         &self.direction
     }
 }
+
+fn add_cone(
+    apex: &Vector3d,
+    segments: &[(Vector3d, Vector3d)],
+    volume: &mut f64,
+    moment: &mut Vector3d,
+) {
+    if segments.is_empty() {
+        return;
+    }
+    let mut center = Vector3d::default();
+    for (start, end) in segments {
+        center.add_assign(start);
+        center.add_assign(end);
+    }
+    center.scale_assign(0.5 / segments.len() as f64);
+    for (start, end) in segments {
+        let tetrahedron_volume = start
+            .sub(apex)
+            .dot(&end.sub(apex).cross(&center.sub(apex)))
+            .abs()
+            / 6.;
+        *volume += tetrahedron_volume;
+        moment.add_assign(
+            &apex
+                .add(&center)
+                .add(start)
+                .add(end)
+                .scale(tetrahedron_volume / 4.),
+        );
+    }
+}
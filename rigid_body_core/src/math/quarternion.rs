@@ -85,6 +85,14 @@ impl Quarternion {
         ])
     }
 
+    pub fn s(&self) -> f64 {
+        self.s
+    }
+
+    pub fn v(&self) -> &Vector3d {
+        &self.v
+    }
+
     pub fn add_assign(&mut self, other: &Self) {
         self.s += other.s;
         self.v.add_assign(&other.v);
@@ -1,4 +1,12 @@
-use super::{matrix::Matrix3x3, rotation_matrix, Quarternion};
+use super::{
+    geometry::{self, FiniteLine3d},
+    gjk,
+    matrix::Matrix3x3,
+    polyhedron::Polyhedron,
+    rotation_matrix,
+    vector::Vector3d,
+    Quarternion,
+};
 
 #[test]
 fn inverse() {
@@ -30,3 +38,115 @@ fn quarternion() {
     println!("{}", m);
     println!("{}", m2);
 }
+
+#[test]
+fn clipped_volume() {
+    let polyhedron = Polyhedron::cuboid(&Vector3d::new(2., 2., 2.)).unwrap();
+    let up = Vector3d::new(0., 1., 0.);
+    let (volume, centroid) =
+        polyhedron.clipped_volume(&Vector3d::new(0., 0.5, 0.), &up);
+    assert!((volume - 6.).abs() < 1e-9);
+    assert!(centroid.sub(&Vector3d::new(0., -0.25, 0.)).mag() < 1e-9);
+
+    let (volume, centroid) =
+        polyhedron.clipped_volume(&Vector3d::new(0., 5., 0.), &up);
+    assert!((volume - 8.).abs() < 1e-9);
+    assert!(centroid.mag() < 1e-9);
+
+    let (volume, _) =
+        polyhedron.clipped_volume(&Vector3d::new(0., -5., 0.), &up);
+    assert!(volume == 0.);
+
+    let diagonal = Vector3d::new(1., 1., 1.).normal();
+    let (volume, centroid) =
+        polyhedron.clipped_volume(&Vector3d::default(), &diagonal);
+    assert!((volume - 4.).abs() < 1e-9);
+    assert!(centroid.dot(&diagonal) < 0.);
+}
+
+#[test]
+fn gjk_signed_dist() {
+    let cuboid = Polyhedron::cuboid(&Vector3d::new(2., 2., 2.)).unwrap();
+    let support = |offset: Vector3d| {
+        let vertices = cuboid.vertices().to_vec();
+        move |direction: &Vector3d| {
+            vertices
+                .iter()
+                .max_by(|a, b| a.dot(direction).total_cmp(&b.dot(direction)))
+                .unwrap()
+                .add(&offset)
+        }
+    };
+    for (x, expected) in &[(3., 1.), (1.75, -0.25), (2., 0.)] {
+        let (dist, position_1, position_2, normal) = gjk::signed_dist(
+            support(Vector3d::new(*x, 0.3, -0.2)),
+            support(Vector3d::default()),
+            &Vector3d::new(1., 0., 0.),
+        );
+        assert!((dist - expected).abs() < 1e-9);
+        assert!(normal.sub(&Vector3d::new(1., 0., 0.)).mag() < 1e-9);
+        assert!((position_1[0] - x + 1.).abs() < 1e-9);
+        assert!((position_2[0] - 1.).abs() < 1e-9);
+    }
+
+    let core = FiniteLine3d::new(
+        &Vector3d::new(0., 1., 0.),
+        &Vector3d::new(0., -1., 0.),
+    );
+    let sphere = |center: Vector3d| {
+        move |direction: &Vector3d| center.add(&direction.normal().scale(0.5))
+    };
+    for center in &[
+        Vector3d::new(2., 0.5, 0.),
+        Vector3d::new(1.2, -0.3, 0.4),
+        Vector3d::new(0.5, 1.6, 0.5),
+        Vector3d::new(0., 1.2, 0.),
+    ] {
+        let (expected, _, _) =
+            geometry::pos_cylinder_closest(center, &core, 1.);
+        let (dist, _, _, _) = gjk::signed_dist(
+            sphere(*center),
+            |direction: &Vector3d| {
+                geometry::cylinder_support(&core, 1., direction)
+            },
+            center,
+        );
+        assert!((dist - (expected - 0.5)).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn cylinder_closest() {
+    let core = FiniteLine3d::new(
+        &Vector3d::new(0., 1., 0.),
+        &Vector3d::new(0., -1., 0.),
+    );
+    for (position, expected_dist, expected_normal) in &[
+        (Vector3d::new(3., 0.5, 0.), 2., Vector3d::new(1., 0., 0.)),
+        (Vector3d::new(0.5, 3., 0.), 2., Vector3d::new(0., 1., 0.)),
+        (
+            Vector3d::new(0., -0.5, 0.8),
+            -0.2,
+            Vector3d::new(0., 0., 1.),
+        ),
+        (
+            Vector3d::new(0.2, -0.9, 0.),
+            -0.1,
+            Vector3d::new(0., -1., 0.),
+        ),
+        (
+            Vector3d::new(4., 5., 0.),
+            5.,
+            Vector3d::new(3., 4., 0.).normal(),
+        ),
+    ] {
+        let (dist, closest, normal) =
+            geometry::pos_cylinder_closest(position, &core, 1.);
+        assert!((dist - expected_dist).abs() < 1e-9);
+        assert!(normal.sub(expected_normal).mag() < 1e-9);
+        assert!(closest.add(&normal.scale(dist)).dist(position) < 1e-9);
+    }
+    let support =
+        geometry::cylinder_support(&core, 1., &Vector3d::new(1., -1., 0.));
+    assert!(support.dist(&Vector3d::new(1., -1., 0.)) < 1e-9);
+}
@@ -50,7 +50,11 @@ impl MeshTriangle {
     ) -> Self {
         Self::new(
             vertex_indices,
-            &Self::normal(&vertices[0], &vertices[1], &vertices[2]),
+            &Self::normal(
+                &vertices[vertex_indices[0]],
+                &vertices[vertex_indices[1]],
+                &vertices[vertex_indices[2]],
+            ),
         )
     }
 }
@@ -1,53 +1,62 @@
 #![allow(dead_code)]
 use super::{Mesh, MeshTriangle};
-use crate::math::vector::Vector3d;
+use crate::{
+    error::{Error, Result},
+    math::vector::Vector3d,
+};
 use std::{
     fs::File,
-    io::{BufRead, BufReader, Error, ErrorKind, Result},
+    io::{BufRead, BufReader},
     path::Path,
 };
 
 pub fn simple<P: AsRef<Path>>(path: P) -> Result<Mesh> {
-    fn get_error(msg: &str) -> Error {
-        Error::new(ErrorKind::Other, msg)
-    }
-    if let Some(os_str) = path.as_ref().extension() {
-        if os_str != "obj" {
-            return Err(get_error("Requires an .obj file."));
-        }
-    } else {
-        return Err(get_error("Requires an .obj file."));
+    let path = path.as_ref();
+    if path.extension().and_then(|os_str| os_str.to_str()) != Some("obj") {
+        return Err(Error::Format("Requires an .obj file.".into()).path(path));
     }
-    let file = File::open(path)?;
-    let reader = BufReader::new(file);
+    let file = File::open(path).map_err(|e| Error::from(e).path(path))?;
+    from_reader(BufReader::new(file)).map_err(|e| e.path(path))
+}
+
+pub fn from_reader<R: BufRead>(reader: R) -> Result<Mesh> {
     let mut vertices = Vec::new();
     let mut mesh_triangles = Vec::new();
 
-    for line in reader.lines() {
+    for (line_index, line) in reader.lines().enumerate() {
         let line = line?;
+        let get_error = |message: &str| Error::Parse {
+            line: line_index + 1,
+            message: message.into(),
+        };
         let mut line_iter = line.split_whitespace();
         if let Some(first) = line_iter.next() {
             if first == "v" {
                 let values = line_iter
                     .map(|s| {
-                        s.parse::<f64>()
-                            .expect("Could not parse file line to f64.")
+                        s.parse::<f64>().map_err(|_| {
+                            get_error(&format!("Invalid vertex value {}.", s))
+                        })
                     })
-                    .collect::<Vec<f64>>();
+                    .collect::<Result<Vec<f64>>>()?;
                 if values.len() != 3 {
-                    return Err(get_error("Unrecognized file format."));
+                    return Err(get_error("Vertex requires three values."));
                 }
                 vertices.push(Vector3d::new(values[0], values[1], values[2]));
             } else if first == "f" {
                 let values = line_iter
-                    .map(|s| {
-                        s.parse::<usize>()
-                            .expect("Could not parse file line to usize.")
-                            - 1
+                    .map(|s| match s.parse::<usize>() {
+                        Ok(index) if index >= 1 && index <= vertices.len() => {
+                            Ok(index - 1)
+                        }
+                        _ => Err(get_error(&format!(
+                            "Invalid face vertex index {}.",
+                            s
+                        ))),
                     })
-                    .collect::<Vec<usize>>();
+                    .collect::<Result<Vec<usize>>>()?;
                 if values.len() != 3 {
-                    return Err(get_error("Unrecognized file format."));
+                    return Err(get_error("Face requires three indices."));
                 }
                 mesh_triangles.push(MeshTriangle::norm_from_vertices(
                     &vertices,
@@ -1,7 +1,26 @@
 #![allow(dead_code)]
 use super::{Mesh, MeshTriangle};
 use crate::math::vector::Vector3d;
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    f64::consts::{FRAC_PI_2, TAU},
+};
+
+pub fn capsule(radius: f64, height: f64, segments: usize) -> Mesh {
+    let rings = (segments / 4).max(1);
+    let mut profile = Vec::with_capacity(rings * 2 + 2);
+    profile.push((0., height / 2. + radius));
+    for k in 1..=rings {
+        let phi = FRAC_PI_2 * k as f64 / rings as f64;
+        profile.push((radius * phi.sin(), height / 2. + radius * phi.cos()));
+    }
+    for k in 0..rings {
+        let phi = FRAC_PI_2 * k as f64 / rings as f64;
+        profile.push((radius * phi.cos(), -height / 2. - radius * phi.sin()));
+    }
+    profile.push((0., -height / 2. - radius));
+    lathe(&profile, segments)
+}
 
 pub fn cuboid(dim: &Vector3d) -> Mesh {
     let min = dim.scale(-0.5);
@@ -33,6 +52,18 @@ pub fn cuboid(dim: &Vector3d) -> Mesh {
     Mesh::new(vertices, mesh_triangles)
 }
 
+pub fn cylinder(radius: f64, height: f64, segments: usize) -> Mesh {
+    lathe(
+        &[
+            (0., height / 2.),
+            (radius, height / 2.),
+            (radius, -height / 2.),
+            (0., -height / 2.),
+        ],
+        segments,
+    )
+}
+
 pub fn icosphere(radius: f64, mut n: u8) -> Mesh {
     let mut ret = regular_icosahedron(radius);
     let mut vertex_map = HashMap::<(usize, usize), usize>::default();
@@ -146,3 +177,45 @@ pub fn regular_tetrahedron(size: f64) -> Mesh {
     ];
     Mesh::new(vertices, mesh_triangles)
 }
+
+fn lathe(profile: &[(f64, f64)], segments: usize) -> Mesh {
+    let rings = profile.len() - 2;
+    let mut vertices = Vec::with_capacity(rings * segments + 2);
+    vertices.push(Vector3d::new(0., profile[0].1, 0.));
+    for (rho, y) in &profile[1..=rings] {
+        for j in 0..segments {
+            let theta = TAU * j as f64 / segments as f64;
+            vertices.push(Vector3d::new(
+                rho * theta.cos(),
+                *y,
+                rho * theta.sin(),
+            ));
+        }
+    }
+    let bottom = vertices.len();
+    vertices.push(Vector3d::new(0., profile[rings + 1].1, 0.));
+
+    let ring = |k: usize, j: usize| 1 + k * segments + j % segments;
+    let mut mesh_triangles = Vec::with_capacity(rings * segments * 2);
+    for j in 0..segments {
+        mesh_triangles.push(MeshTriangle::norm_from_vertices(
+            &vertices,
+            &[0, ring(0, j + 1), ring(0, j)],
+        ));
+        for k in 0..rings - 1 {
+            mesh_triangles.push(MeshTriangle::norm_from_vertices(
+                &vertices,
+                &[ring(k, j), ring(k, j + 1), ring(k + 1, j + 1)],
+            ));
+            mesh_triangles.push(MeshTriangle::norm_from_vertices(
+                &vertices,
+                &[ring(k, j), ring(k + 1, j + 1), ring(k + 1, j)],
+            ));
+        }
+        mesh_triangles.push(MeshTriangle::norm_from_vertices(
+            &vertices,
+            &[bottom, ring(rings - 1, j), ring(rings - 1, j + 1)],
+        ));
+    }
+    Mesh::new(vertices, mesh_triangles)
+}
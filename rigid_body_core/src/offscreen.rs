@@ -0,0 +1,109 @@
+use crate::{
+    error::Result,
+    render::{image, ImageFormat, ScreenBufferTrait},
+    RigidBodySimulationCore, RigidBodySimulationCoreAccess,
+    RigidBodySimulationTrait,
+};
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Debug)]
+pub struct FrameOutput {
+    pub directory: PathBuf,
+    pub format: ImageFormat,
+    pub every: usize,
+}
+
+impl FrameOutput {
+    pub fn new<P: AsRef<Path>>(directory: P, format: ImageFormat) -> Self {
+        Self {
+            directory: directory.as_ref().to_path_buf(),
+            format,
+            every: 1,
+        }
+    }
+
+    pub fn frame_path(&self, frame: usize) -> PathBuf {
+        self.directory.join(format!(
+            "frame_{:05}.{}",
+            frame,
+            self.format.extension()
+        ))
+    }
+}
+
+pub struct RigidBodySimulationOffscreen {
+    rigid_body_simulation_core: RigidBodySimulationCore,
+    window_size: (u32, u32),
+    frame_output_opt: Option<FrameOutput>,
+    frame: usize,
+    next_output_frame: usize,
+}
+
+impl RigidBodySimulationOffscreen {
+    pub fn new(window_size: (u32, u32)) -> Self {
+        Self {
+            rigid_body_simulation_core: RigidBodySimulationCore::new(
+                window_size,
+            ),
+            window_size,
+            frame_output_opt: None,
+            frame: 0,
+            next_output_frame: 0,
+        }
+    }
+
+    pub fn frame(&self) -> usize {
+        self.frame
+    }
+
+    pub fn frame_output(&self) -> Option<&FrameOutput> {
+        self.frame_output_opt.as_ref()
+    }
+
+    pub fn rigid_body_simulation_core(&self) -> &RigidBodySimulationCore {
+        &self.rigid_body_simulation_core
+    }
+
+    pub fn render_frame(&mut self) -> Result<Option<PathBuf>> {
+        let frame_output = match &self.frame_output_opt {
+            Some(frame_output) if self.frame >= self.next_output_frame => {
+                frame_output
+            }
+            _ => return Ok(None),
+        };
+        self.next_output_frame = self.frame + frame_output.every;
+        let path = frame_output.frame_path(self.frame);
+        self.rigid_body_simulation_core.render();
+        image::save(
+            &path,
+            frame_output.format,
+            self.window_size,
+            self.rigid_body_simulation_core.renderer.pixel_buffer(),
+        )?;
+        Ok(Some(path))
+    }
+
+    pub fn set_frame_output(&mut self, frame_output_opt: Option<FrameOutput>) {
+        self.frame_output_opt = frame_output_opt;
+        self.next_output_frame = self.frame;
+    }
+
+    pub fn tick(&mut self, delta_t: f64) -> Result<()> {
+        self.rigid_body_simulation_core
+            .simulation_mut()
+            .tick(delta_t)?;
+        self.frame += 1;
+        self.render_frame()?;
+        Ok(())
+    }
+}
+
+impl RigidBodySimulationCoreAccess for RigidBodySimulationOffscreen {
+    fn rigid_body_simulation_core_access(
+        &mut self,
+    ) -> &mut RigidBodySimulationCore {
+        &mut self.rigid_body_simulation_core
+    }
+}
+
+impl RigidBodySimulationTrait for RigidBodySimulationOffscreen {}
@@ -0,0 +1,310 @@
+#[cfg(test)]
+mod test;
+
+use crate::{
+    error::{Error, Result},
+    math::{vector::Vector3d, Quarternion},
+    rigid_body::RigidBody,
+};
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::Path,
+};
+
+const HEADER: &str = "time,rigid_body,position_x,position_y,position_z,\
+                      quarternion_s,quarternion_x,quarternion_y,quarternion_z";
+const MOMENTA_HEADER: &str = ",momentum_x,momentum_y,momentum_z,\
+                              angular_momentum_x,angular_momentum_y,\
+                              angular_momentum_z";
+
+pub struct Recorder {
+    writer: Box<dyn Write>,
+    momenta: bool,
+    time: f64,
+    header_written: bool,
+    error_opt: Option<io::Error>,
+}
+
+impl Recorder {
+    pub fn new(writer: Box<dyn Write>, momenta: bool) -> Self {
+        Self {
+            writer,
+            momenta,
+            time: 0.,
+            header_written: false,
+            error_opt: None,
+        }
+    }
+
+    pub fn create<P: AsRef<Path>>(path: P, momenta: bool) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::create(path).map_err(|e| Error::from(e).path(path))?;
+        Ok(Self::new(Box::new(BufWriter::new(file)), momenta))
+    }
+
+    pub fn advance(&mut self, delta_t: f64) {
+        self.time += delta_t;
+    }
+
+    pub fn finish(mut self) -> Result<()> {
+        if let Some(error) = self.error_opt.take() {
+            return Err(error.into());
+        }
+        Ok(self.writer.flush()?)
+    }
+
+    pub fn record(&mut self, rigid_bodies: &[RigidBody]) {
+        if self.error_opt.is_none() {
+            if let Err(error) = self.write_frame(rigid_bodies) {
+                self.error_opt = Some(error);
+            }
+        }
+    }
+
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
+    fn write_frame(&mut self, rigid_bodies: &[RigidBody]) -> io::Result<()> {
+        if !self.header_written {
+            write!(self.writer, "{}", HEADER)?;
+            if self.momenta {
+                write!(self.writer, "{}", MOMENTA_HEADER)?;
+            }
+            writeln!(self.writer)?;
+            self.header_written = true;
+        }
+        for (i, rigid_body) in rigid_bodies.iter().enumerate() {
+            if rigid_body.is_immovable() {
+                continue;
+            }
+            let position = &rigid_body.position;
+            let quarternion = &rigid_body.quarternion;
+            let v = quarternion.v();
+            write!(
+                self.writer,
+                "{},{},{},{},{},{},{},{},{}",
+                self.time,
+                i,
+                position[0],
+                position[1],
+                position[2],
+                quarternion.s(),
+                v[0],
+                v[1],
+                v[2]
+            )?;
+            if self.momenta {
+                let momentum = &rigid_body.momentum;
+                let angular_momentum = &rigid_body.angular_momentum;
+                write!(
+                    self.writer,
+                    ",{},{},{},{},{},{}",
+                    momentum[0],
+                    momentum[1],
+                    momentum[2],
+                    angular_momentum[0],
+                    angular_momentum[1],
+                    angular_momentum[2]
+                )?;
+            }
+            writeln!(self.writer)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct RigidBodyState {
+    pub rigid_body: usize,
+    pub position: Vector3d,
+    pub quarternion: Quarternion,
+    pub momenta_opt: Option<(Vector3d, Vector3d)>,
+}
+
+#[derive(Clone)]
+pub struct RecordingFrame {
+    pub time: f64,
+    pub rigid_body_states: Vec<RigidBodyState>,
+}
+
+#[derive(Clone, Default)]
+pub struct Recording {
+    frames: Vec<RecordingFrame>,
+}
+
+impl Recording {
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|e| Error::from(e).path(path))?;
+        Self::from_reader(BufReader::new(file)).map_err(|e| e.path(path))
+    }
+
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<Self> {
+        let mut frames: Vec<RecordingFrame> = Vec::new();
+        for (line_index, line) in reader.lines().enumerate().skip(1) {
+            let line = line?;
+            if line.is_empty() {
+                continue;
+            }
+            let get_error = |message: &str| Error::Parse {
+                line: line_index + 1,
+                message: message.into(),
+            };
+            let fields = line.split(',').collect::<Vec<_>>();
+            if fields.len() != 9 && fields.len() != 15 {
+                return Err(get_error("Expected 9 or 15 values."));
+            }
+            let rigid_body = fields[1]
+                .parse::<usize>()
+                .map_err(|_| get_error("Invalid rigid body index."))?;
+            let values = fields
+                .iter()
+                .map(|value| value.parse::<f64>())
+                .collect::<std::result::Result<Vec<f64>, _>>()
+                .map_err(|_| get_error("Invalid number."))?;
+            let rigid_body_state = RigidBodyState {
+                rigid_body,
+                position: Vector3d::new(values[2], values[3], values[4]),
+                quarternion: Quarternion::new(
+                    values[5],
+                    &Vector3d::new(values[6], values[7], values[8]),
+                ),
+                momenta_opt: if values.len() == 15 {
+                    Some((
+                        Vector3d::new(values[9], values[10], values[11]),
+                        Vector3d::new(values[12], values[13], values[14]),
+                    ))
+                } else {
+                    None
+                },
+            };
+            let time = values[0];
+            match frames.last_mut() {
+                Some(frame) if frame.time == time => {
+                    frame.rigid_body_states.push(rigid_body_state)
+                }
+                Some(frame) if frame.time > time => {
+                    return Err(get_error("Time is not increasing."))
+                }
+                _ => frames.push(RecordingFrame {
+                    time,
+                    rigid_body_states: vec![rigid_body_state],
+                }),
+            }
+        }
+        Ok(Self { frames })
+    }
+
+    pub fn frames(&self) -> &[RecordingFrame] {
+        &self.frames
+    }
+
+    pub fn start_time(&self) -> f64 {
+        self.frames.first().map_or(0., |frame| frame.time)
+    }
+
+    pub fn end_time(&self) -> f64 {
+        self.frames.last().map_or(0., |frame| frame.time)
+    }
+}
+
+pub struct Playback {
+    recording: Recording,
+    time: f64,
+    paused: bool,
+    speed: f64,
+}
+
+impl Playback {
+    pub fn new(recording: Recording) -> Self {
+        Self {
+            time: recording.start_time(),
+            recording,
+            paused: false,
+            speed: 1.,
+        }
+    }
+
+    pub fn advance(&mut self, delta_t: f64) {
+        if !self.paused {
+            self.seek(self.time + delta_t * self.speed);
+        }
+    }
+
+    pub fn apply(&self, rigid_bodies: &mut [RigidBody]) {
+        let frame = match self.frame() {
+            Some(frame) => frame,
+            None => return,
+        };
+        for rigid_body_state in &frame.rigid_body_states {
+            let rigid_body =
+                match rigid_bodies.get_mut(rigid_body_state.rigid_body) {
+                    Some(rigid_body) => rigid_body,
+                    None => continue,
+                };
+            rigid_body.position = rigid_body_state.position;
+            rigid_body.quarternion = rigid_body_state.quarternion;
+            if let Some((momentum, angular_momentum)) =
+                &rigid_body_state.momenta_opt
+            {
+                rigid_body.momentum = *momentum;
+                rigid_body.angular_momentum = *angular_momentum;
+            }
+            rigid_body.update();
+        }
+    }
+
+    pub fn frame(&self) -> Option<&RecordingFrame> {
+        let frames = self.recording.frames();
+        match frames.partition_point(|frame| frame.time <= self.time) {
+            0 => frames.first(),
+            index => Some(&frames[index - 1]),
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn recording(&self) -> &Recording {
+        &self.recording
+    }
+
+    pub fn seek(&mut self, time: f64) {
+        self.time = time
+            .min(self.recording.end_time())
+            .max(self.recording.start_time());
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    pub fn set_speed(&mut self, speed: f64) {
+        self.speed = speed;
+    }
+
+    pub fn speed(&self) -> f64 {
+        self.speed
+    }
+
+    pub fn step(&mut self, frames: isize) {
+        let recording_frames = self.recording.frames();
+        if recording_frames.is_empty() {
+            return;
+        }
+        let index = recording_frames
+            .partition_point(|frame| frame.time <= self.time)
+            .max(1) as isize
+            - 1
+            + frames;
+        let index = index.max(0).min(recording_frames.len() as isize - 1);
+        self.time = recording_frames[index as usize].time;
+    }
+
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+}
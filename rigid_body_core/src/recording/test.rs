@@ -0,0 +1,71 @@
+use super::{Playback, Recorder, Recording, HEADER};
+use crate::{config, Error, RigidBodySimulationCore};
+use std::{env, fs, process};
+
+#[test]
+fn playback_reproduces_recorded_states() {
+    let path = env::temp_dir()
+        .join(format!("rigid_body_recording_{}.csv", process::id()));
+    let mut core = RigidBodySimulationCore::new((1, 1));
+    config::test::floor_debris(3, &mut core).unwrap();
+    let simulation = core.simulation_mut();
+    let mut recorder = Recorder::create(&path, true).unwrap();
+    recorder.record(simulation.rigid_bodies());
+    for _ in 0..30 {
+        simulation.tick(1. / 60.).unwrap();
+        recorder.advance(1. / 60.);
+        recorder.record(simulation.rigid_bodies());
+    }
+    recorder.finish().unwrap();
+    let recording = Recording::from_file(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(recording.frames().len(), 31);
+    assert_eq!(recording.frames()[0].rigid_body_states.len(), 9);
+
+    let mut playback_core = RigidBodySimulationCore::new((1, 1));
+    config::test::floor_debris(3, &mut playback_core).unwrap();
+    let mut playback = Playback::new(recording);
+    playback.seek(100.);
+    assert_eq!(playback.time(), playback.recording().end_time());
+    playback.apply(playback_core.simulation_mut().rigid_bodies_mut());
+    for (rigid_body, played) in core
+        .simulation()
+        .rigid_bodies()
+        .iter()
+        .zip(playback_core.simulation().rigid_bodies())
+    {
+        if rigid_body.is_immovable() {
+            continue;
+        }
+        for axis in 0..3 {
+            assert_eq!(rigid_body.position[axis], played.position[axis]);
+            assert_eq!(rigid_body.momentum[axis], played.momentum[axis]);
+        }
+    }
+
+    playback.step(-40);
+    assert_eq!(playback.time(), 0.);
+    playback.step(2);
+    assert_eq!(playback.time(), playback.recording().frames()[2].time);
+    playback.set_paused(true);
+    playback.advance(1.);
+    assert_eq!(playback.time(), playback.recording().frames()[2].time);
+}
+
+#[test]
+fn rigid_body_index_must_be_an_index() {
+    let parse = |index: &str| {
+        let csv = format!("{}\n0,{},0,0,0,1,0,0,0\n", HEADER, index);
+        Recording::from_reader(csv.as_bytes())
+    };
+    assert_eq!(
+        parse("2").unwrap().frames()[0].rigid_body_states[0].rigid_body,
+        2
+    );
+    for index in &["-1", "2.7", "1e3", "NaN"] {
+        match parse(index) {
+            Err(Error::Parse { line, .. }) => assert_eq!(line, 2),
+            _ => panic!("index {} was accepted", index),
+        }
+    }
+}
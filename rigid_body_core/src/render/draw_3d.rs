@@ -1,5 +1,6 @@
 use super::{
     camera::Camera,
+    render_config::RenderConfig,
     render_object_creator::RenderObjectCreator,
     screen_buffer::{
         Color, ScreenBuffer, ScreenBufferAccess, ScreenBufferTrait,
@@ -9,7 +10,6 @@ use crate::{
     math::{matrix::Matrix3x3, polyhedron::Polyhedron, vector::Vector3d},
     mesh::Mesh,
 };
-use std::f64::consts::PI;
 
 pub struct Draw3d {
     pub camera: Camera,
@@ -18,15 +18,15 @@ pub struct Draw3d {
 }
 
 impl Draw3d {
-    pub fn new(window_size: (u32, u32)) -> Self {
+    pub fn with_config(window_size: (u32, u32), config: &RenderConfig) -> Self {
         Self {
             camera: Camera::default(),
             screen_buffer: ScreenBuffer::new(window_size),
             render_object_creator: RenderObjectCreator::new(
                 window_size,
-                0.1,
-                20000.,
-                PI / 2.,
+                config.near,
+                config.far,
+                config.fov,
             ),
         }
     }
@@ -51,6 +51,22 @@ pub trait Draw3dTrait: Draw3dAccess {
         &mut self.draw_3d_access_mut().camera
     }
 
+    fn render_config(&self) -> RenderConfig {
+        let render_object_creator =
+            &self.draw_3d_access().render_object_creator;
+        RenderConfig {
+            near: render_object_creator.get_near(),
+            far: render_object_creator.get_far(),
+            fov: render_object_creator.get_fov(),
+        }
+    }
+
+    fn set_render_config(&mut self, config: &RenderConfig) {
+        self.draw_3d_access_mut()
+            .render_object_creator
+            .set_projection(config.near, config.far, config.fov);
+    }
+
     fn set_window_size(&mut self, window_size: (u32, u32)) {
         let draw_3d = self.draw_3d_access_mut();
         draw_3d.render_object_creator.set_window_size(window_size);
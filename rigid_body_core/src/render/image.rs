@@ -0,0 +1,265 @@
+use super::screen_buffer::PIXEL_FORMAT;
+use crate::error::{Error, Result};
+use std::{fs, path::Path};
+
+const PNG_SIGNATURE: [u8; 8] = [137, 80, 78, 71, 13, 10, 26, 10];
+const HASH_BITS: u32 = 15;
+const MAX_CHAIN: usize = 64;
+const MAX_DIST: usize = 32768;
+const MAX_MATCH: usize = 258;
+const MIN_MATCH: usize = 3;
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59,
+    67, 83, 99, 115, 131, 163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5,
+    5, 5, 5, 0,
+];
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513,
+    769, 1025, 1537, 2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10,
+    11, 11, 12, 12, 13, 13,
+];
+
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    bits: u32,
+    len: u32,
+}
+
+impl BitWriter {
+    fn finish(mut self) -> Vec<u8> {
+        if self.len > 0 {
+            self.bytes.push(self.bits as u8);
+        }
+        self.bytes
+    }
+
+    fn write(&mut self, value: u32, len: u32) {
+        self.bits |= value << self.len;
+        self.len += len;
+        while self.len >= 8 {
+            self.bytes.push(self.bits as u8);
+            self.bits >>= 8;
+            self.len -= 8;
+        }
+    }
+
+    fn write_code(&mut self, code: u32, len: u32) {
+        self.write(code.reverse_bits() >> (32 - len), len);
+    }
+
+    fn write_literal(&mut self, symbol: u32) {
+        match symbol {
+            0..=143 => self.write_code(0x30 + symbol, 8),
+            144..=255 => self.write_code(0x190 + symbol - 144, 9),
+            256..=279 => self.write_code(symbol - 256, 7),
+            _ => self.write_code(0xC0 + symbol - 280, 8),
+        }
+    }
+
+    fn write_match(&mut self, len: usize, dist: usize) {
+        let code = LENGTH_BASE
+            .iter()
+            .rposition(|base| usize::from(*base) <= len)
+            .unwrap_or(0);
+        self.write_literal(257 + code as u32);
+        self.write(
+            (len - usize::from(LENGTH_BASE[code])) as u32,
+            u32::from(LENGTH_EXTRA[code]),
+        );
+        let code = DIST_BASE
+            .iter()
+            .rposition(|base| usize::from(*base) <= dist)
+            .unwrap_or(0);
+        self.write_code(code as u32, 5);
+        self.write(
+            (dist - usize::from(DIST_BASE[code])) as u32,
+            u32::from(DIST_EXTRA[code]),
+        );
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ImageFormat {
+    #[default]
+    Png,
+    Ppm,
+}
+
+impl ImageFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::Ppm => "ppm",
+        }
+    }
+}
+
+pub fn encode(
+    format: ImageFormat,
+    window_size: (u32, u32),
+    pixel_buffer: &[u8],
+) -> Result<Vec<u8>> {
+    let (width, height) = window_size;
+    if width == 0 || height == 0 {
+        return Err(Error::InvalidValue(format!(
+            "Image size {}x{} is empty.",
+            width, height
+        )));
+    }
+    if pixel_buffer.len() != width as usize * height as usize * PIXEL_FORMAT {
+        return Err(Error::InvalidValue(format!(
+            "Pixel buffer of length {} does not match a {}x{} image.",
+            pixel_buffer.len(),
+            width,
+            height
+        )));
+    }
+    Ok(match format {
+        ImageFormat::Png => encode_png(window_size, pixel_buffer),
+        ImageFormat::Ppm => encode_ppm(window_size, pixel_buffer),
+    })
+}
+
+pub fn save<P: AsRef<Path>>(
+    path: P,
+    format: ImageFormat,
+    window_size: (u32, u32),
+    pixel_buffer: &[u8],
+) -> Result<()> {
+    let path = path.as_ref();
+    fs::write(path, encode(format, window_size, pixel_buffer)?)
+        .map_err(|e| Error::from(e).path(path))
+}
+
+fn encode_png(window_size: (u32, u32), pixel_buffer: &[u8]) -> Vec<u8> {
+    let mut raw = Vec::with_capacity(pixel_buffer.len());
+    for row in pixel_buffer.chunks(window_size.0 as usize * PIXEL_FORMAT) {
+        raw.push(0);
+        for pixel in row.chunks(PIXEL_FORMAT) {
+            raw.extend_from_slice(&pixel[..3]);
+        }
+    }
+    let mut zlib = vec![0x78, 0x01];
+    zlib.extend(deflate(&raw));
+    zlib.extend_from_slice(&adler32(&raw).to_be_bytes());
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&window_size.0.to_be_bytes());
+    header.extend_from_slice(&window_size.1.to_be_bytes());
+    header.extend_from_slice(&[8, 2, 0, 0, 0]);
+
+    let mut ret = PNG_SIGNATURE.to_vec();
+    write_png_chunk(&mut ret, b"IHDR", &header);
+    write_png_chunk(&mut ret, b"IDAT", &zlib);
+    write_png_chunk(&mut ret, b"IEND", &[]);
+    ret
+}
+
+fn deflate(data: &[u8]) -> Vec<u8> {
+    let hash = |i: usize| {
+        let key = u32::from(data[i]) << 16
+            | u32::from(data[i + 1]) << 8
+            | u32::from(data[i + 2]);
+        (key.wrapping_mul(0x9E37_79B1) >> (32 - HASH_BITS)) as usize
+    };
+    let mut head = vec![usize::MAX; 1 << HASH_BITS];
+    let mut prev = vec![usize::MAX; data.len()];
+    let insert = |i: usize, head: &mut [usize], prev: &mut [usize]| {
+        if i + MIN_MATCH <= data.len() {
+            let h = hash(i);
+            prev[i] = head[h];
+            head[h] = i;
+        }
+    };
+    let mut writer = BitWriter::default();
+    writer.write(1, 1);
+    writer.write(1, 2);
+    let mut i = 0;
+    while i < data.len() {
+        let mut best = (0, 0);
+        if i + MIN_MATCH <= data.len() {
+            let max_len = MAX_MATCH.min(data.len() - i);
+            let mut candidate = head[hash(i)];
+            for _ in 0..MAX_CHAIN {
+                if candidate == usize::MAX || i - candidate > MAX_DIST {
+                    break;
+                }
+                let len = data[candidate..]
+                    .iter()
+                    .zip(&data[i..i + max_len])
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                if len > best.0 {
+                    best = (len, i - candidate);
+                    if len == max_len {
+                        break;
+                    }
+                }
+                candidate = prev[candidate];
+            }
+        }
+        let (len, dist) = best;
+        if len >= MIN_MATCH {
+            writer.write_match(len, dist);
+            for k in i..i + len {
+                insert(k, &mut head, &mut prev);
+            }
+            i += len;
+        } else {
+            writer.write_literal(u32::from(data[i]));
+            insert(i, &mut head, &mut prev);
+            i += 1;
+        }
+    }
+    writer.write_literal(256);
+    writer.finish()
+}
+
+fn encode_ppm(window_size: (u32, u32), pixel_buffer: &[u8]) -> Vec<u8> {
+    let mut ret =
+        format!("P6\n{} {}\n255\n", window_size.0, window_size.1).into_bytes();
+    for pixel in pixel_buffer.chunks(PIXEL_FORMAT) {
+        ret.extend_from_slice(&pixel[..3]);
+    }
+    ret
+}
+
+fn write_png_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+pub(super) fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for byte in data {
+        a = (a + u32::from(*byte)) % 65521;
+        b = (b + a) % 65521;
+    }
+    b << 16 | a
+}
+
+pub(super) fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc ^= u32::from(*byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                crc >> 1 ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
@@ -1,16 +1,22 @@
 mod camera;
 mod draw_3d;
+pub mod image;
+mod render_config;
 mod render_object_creator;
 mod screen_buffer;
 
+#[cfg(test)]
+mod test;
+
 use crate::{
     math::{
-        matrix::Matrix3x3, matrix_vector, polyhedron::Polyhedron,
-        rotation_matrix, vector::Vector3d,
+        matrix_vector, polyhedron::Polyhedron, rotation_matrix,
+        vector::Vector3d,
     },
     mesh::Mesh,
     simulation::{
-        bounding_box::BoundingBox, rigid_body::RigidBody, Contact, Simulation,
+        bounding_box::BoundingBox, half_space::HalfSpace,
+        rigid_body::RigidBody, Contact, Simulation,
     },
     utility::int_hash::IntMap,
     SeparatingPlane, UID,
@@ -18,10 +24,15 @@ use crate::{
 pub use camera::Camera;
 pub use draw_3d::Draw3dTrait;
 use draw_3d::{Draw3d, Draw3dAccess};
+pub use image::ImageFormat;
+pub use render_config::RenderConfig;
 pub use screen_buffer::{Color, ScreenBufferTrait, PIXEL_FORMAT};
 use screen_buffer::{ScreenBuffer, ScreenBufferAccess};
 use std::f64::consts::PI;
 
+const HALF_SPACE_EXTENT: f64 = 50.;
+const HALF_SPACE_LINES: i32 = 10;
+
 type RenderMap = IntMap<UID, RenderOption>;
 
 pub enum RenderOption {
@@ -52,8 +63,12 @@ pub struct RendererCore {
 
 impl RendererCore {
     pub fn new(window_size: (u32, u32)) -> Self {
+        Self::with_config(window_size, &RenderConfig::default())
+    }
+
+    pub fn with_config(window_size: (u32, u32), config: &RenderConfig) -> Self {
         Self {
-            draw_3d: Draw3d::new(window_size),
+            draw_3d: Draw3d::with_config(window_size, config),
             render_map: RenderMap::default(),
             debug: false,
         }
@@ -93,6 +108,10 @@ impl RendererCore {
         }
     }
 
+    pub fn render_option(&self, uid: UID) -> Option<&RenderOption> {
+        self.render_map.get(&uid)
+    }
+
     pub fn set_debug(&mut self, set: bool) {
         self.debug = set;
     }
@@ -114,33 +133,60 @@ impl RendererCore {
         render_map: &RenderMap,
         draw_3d: &mut Draw3d,
     ) {
-        let dimensions = &bounding_box.inner_opt.as_ref().unwrap().dimensions;
+        let inner = bounding_box.inner_opt.as_ref().unwrap();
         match render_map.get(&bounding_box.uid) {
             Some(render_option) => match render_option {
                 RenderOption::Mesh { mesh, color } => draw_3d.draw_mesh(
                     mesh,
-                    &dimensions[0].add(&dimensions[1]).scale(0.5),
-                    &Matrix3x3::identity(),
+                    &inner.center(),
+                    &inner.rotation,
                     *color,
                 ),
-                RenderOption::None => Self::draw_bounding_box_default(
-                    &dimensions[0],
-                    &dimensions[1],
-                    draw_3d,
-                ),
-                RenderOption::PolyhedronEdges { color } => draw_3d
-                    .draw_aligned_cuboid(
-                        &dimensions[0],
-                        &dimensions[1],
-                        *color,
-                    ),
+                RenderOption::None => {
+                    if let Ok(polyhedron) = inner.polyhedron() {
+                        Self::draw_bounding_box_default(&polyhedron, draw_3d)
+                    }
+                }
+                RenderOption::PolyhedronEdges { color } => {
+                    if let Ok(polyhedron) = inner.polyhedron() {
+                        draw_3d.draw_polyhedron_edges(&polyhedron, *color)
+                    }
+                }
                 _ => (),
             },
-            None => Self::draw_bounding_box_default(
-                &dimensions[0],
-                &dimensions[1],
-                draw_3d,
-            ),
+            None => {
+                if let Ok(polyhedron) = inner.polyhedron() {
+                    Self::draw_bounding_box_default(&polyhedron, draw_3d)
+                }
+            }
+        }
+    }
+
+    fn draw_half_space(&mut self, half_space: &HalfSpace) {
+        let color = match self.render_map.get(&half_space.uid()) {
+            Some(RenderOption::Edges { color, .. })
+            | Some(RenderOption::FaceEdges { color, .. })
+            | Some(RenderOption::Mesh { color, .. })
+            | Some(RenderOption::PolyhedronEdges { color }) => *color,
+            Some(RenderOption::Invisible) => return,
+            Some(RenderOption::None) | None => Color::rgb(255, 0, 255),
+        };
+        let (tangent_1, tangent_2) = half_space.tangents();
+        let spacing = HALF_SPACE_EXTENT / f64::from(HALF_SPACE_LINES);
+        for (tangent_1, tangent_2) in
+            &[(&tangent_1, &tangent_2), (&tangent_2, &tangent_1)]
+        {
+            for i in -HALF_SPACE_LINES..=HALF_SPACE_LINES {
+                let offset = half_space
+                    .position()
+                    .add(&tangent_1.scale(f64::from(i) * spacing));
+                self.draw_line(
+                    &offset.sub(&tangent_2.scale(HALF_SPACE_EXTENT)),
+                    &offset.add(&tangent_2.scale(HALF_SPACE_EXTENT)),
+                    color,
+                    false,
+                );
+            }
         }
     }
 
@@ -192,11 +238,10 @@ impl RendererCore {
     }
 
     fn draw_bounding_box_default(
-        min: &Vector3d,
-        max: &Vector3d,
+        polyhedron: &Polyhedron,
         draw_3d: &mut Draw3d,
     ) {
-        draw_3d.draw_aligned_cuboid(min, max, Color::rgb(255, 0, 255));
+        draw_3d.draw_polyhedron_edges(polyhedron, Color::rgb(255, 0, 255));
     }
 
     fn draw_rigid_body_default(
@@ -328,23 +373,60 @@ impl RendererCore {
         }
     }
 
+    fn island_color(island_index: usize) -> Color {
+        let hue = (island_index as f64 * 0.618_033_988_749_895).fract() * 6.;
+        let fract = hue.fract();
+        let rising = (255. * fract) as u8;
+        let falling = (255. * (1. - fract)) as u8;
+        match hue as usize {
+            0 => Color::rgb(255, rising, 0),
+            1 => Color::rgb(falling, 255, 0),
+            2 => Color::rgb(0, 255, rising),
+            3 => Color::rgb(0, falling, 255),
+            4 => Color::rgb(rising, 0, 255),
+            _ => Color::rgb(255, 0, falling),
+        }
+    }
+
     fn render_simulation_impl(&mut self, simulation: &Simulation) {
         for rigid_body in simulation.rigid_bodies().iter() {
             self.draw_rigid_body(rigid_body, &None);
         }
+        for half_space in simulation.half_spaces() {
+            self.draw_half_space(half_space);
+        }
         let bounding_box = simulation.bounding_box();
         if bounding_box.inner_opt.is_some() {
             self.draw_bounding_box(bounding_box);
         }
+        if let Some(periodic_domain) = simulation.periodic_domain() {
+            self.draw_aligned_cuboid(
+                &periodic_domain.dimensions[0],
+                &periodic_domain.dimensions[1],
+                Color::rgb(0, 255, 255),
+            );
+        }
     }
 
     fn render_simulation_impl_debug(&mut self, simulation: &Simulation) {
         let rigid_bodies = simulation.rigid_bodies();
+        let islands = simulation.islands();
+        for half_space in simulation.half_spaces() {
+            self.draw_half_space(half_space);
+        }
+        for contact in simulation.collision_manager.half_space_contacts() {
+            self.draw_position(&contact.position, Color::rgb(255, 255, 0));
+        }
         for (i, rigid_body) in rigid_bodies.iter().enumerate() {
             self.draw_rigid_body(
                 rigid_body,
-                &Some(if simulation.collision_manager.is_colliding(i) {
-                    Color::rgb(255, 0, 0)
+                &Some(if rigid_body.is_sleeping() {
+                    Color::rgb(128, 128, 128)
+                } else if simulation.collision_manager.is_colliding(i) {
+                    match islands.island_index(i) {
+                        Some(island_index) => Self::island_color(island_index),
+                        None => Color::rgb(255, 0, 0),
+                    }
                 } else {
                     Color::rgb(0, 255, 0)
                 }),
@@ -436,7 +518,7 @@ impl RendererCore {
                                     true,
                                 );
                             }
-                            SeparatingPlane::None => unreachable!(),
+                            SeparatingPlane::None => (),
                         }
                     } else {
                         for contact in &collision_status.contacts {
@@ -459,6 +541,12 @@ impl RendererCore {
                                         Color::rgb(0, 255, 255),
                                     );
                                 }
+                                Contact::Point { point_indices } => {
+                                    self.draw_position(
+                                        &point_indices.contact_position,
+                                        Color::rgb(255, 0, 255),
+                                    );
+                                }
                             }
                         }
                     }
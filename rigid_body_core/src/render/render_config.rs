@@ -0,0 +1,23 @@
+use std::f64::consts::PI;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "scene",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(default)
+)]
+pub struct RenderConfig {
+    pub near: f64,
+    pub far: f64,
+    pub fov: f64,
+}
+
+impl Default for RenderConfig {
+    fn default() -> Self {
+        Self {
+            near: 0.1,
+            far: 20000.,
+            fov: PI / 2.,
+        }
+    }
+}
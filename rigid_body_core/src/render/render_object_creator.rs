@@ -74,6 +74,15 @@ impl RenderObjectCreator {
         );
     }
 
+    pub fn set_projection(&mut self, near: f64, far: f64, fov: f64) {
+        self.near = near;
+        self.far = far;
+        self.fov = fov;
+        self.near_plane.pos = Vector3d::new(0., 0., near);
+        self.projection_matrix =
+            Self::get_projection_matrix(&self.window_hsize, near, far, fov);
+    }
+
     pub fn get_fov(&self) -> f64 {
         self.fov
     }
@@ -0,0 +1,91 @@
+use super::{
+    image::{self, adler32, crc32},
+    ImageFormat,
+};
+use crate::{
+    config,
+    offscreen::{FrameOutput, RigidBodySimulationOffscreen},
+    Error,
+};
+use std::fs;
+
+#[test]
+fn checksums_match_reference_values() {
+    assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    assert_eq!(crc32(b"IEND"), 0xAE42_6082);
+    assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+}
+
+#[test]
+fn images_are_encoded_from_pixel_buffer() {
+    let window_size = (2, 1);
+    let pixel_buffer = [1, 2, 3, 255, 4, 5, 6, 255];
+    assert_eq!(
+        image::encode(ImageFormat::Ppm, window_size, &pixel_buffer).unwrap(),
+        b"P6\n2 1\n255\n\x01\x02\x03\x04\x05\x06".to_vec()
+    );
+
+    let png =
+        image::encode(ImageFormat::Png, window_size, &pixel_buffer).unwrap();
+    assert_eq!(&png[..8], &[137, 80, 78, 71, 13, 10, 26, 10]);
+    assert_eq!(&png[12..16], b"IHDR");
+    assert_eq!(&png[16..24], &[0, 0, 0, 2, 0, 0, 0, 1]);
+    let idat = &png[33..];
+    assert_eq!(&idat[4..8], b"IDAT");
+    let raw = [0, 1, 2, 3, 4, 5, 6];
+    assert_eq!(&idat[8..10], &[0x78, 0x01]);
+    assert_eq!(
+        &idat[10..19],
+        &[0x63, 0x60, 0x64, 0x62, 0x66, 0x61, 0x65, 0x03, 0x00]
+    );
+    assert_eq!(&idat[19..23], &adler32(&raw).to_be_bytes());
+    assert_eq!(&png[png.len() - 12..png.len() - 4], b"\0\0\0\0IEND");
+
+    let window_size = (64, 48);
+    let pixel_buffer = [32, 64, 96, 255].repeat(64 * 48);
+    let png =
+        image::encode(ImageFormat::Png, window_size, &pixel_buffer).unwrap();
+    assert!(png.len() < 200);
+
+    assert!(matches!(
+        image::encode(ImageFormat::Png, (3, 1), &pixel_buffer),
+        Err(Error::InvalidValue(_))
+    ));
+    assert_eq!(
+        image::encode(ImageFormat::Ppm, (0, 0), &[]).err(),
+        Some(Error::InvalidValue("Image size 0x0 is empty.".into()))
+    );
+}
+
+#[test]
+fn offscreen_writes_every_kth_frame() {
+    let directory = std::env::temp_dir()
+        .join(format!("rigid_body_offscreen_{}", std::process::id()));
+    fs::create_dir_all(&directory).unwrap();
+    let window_size = (32, 24);
+    let mut offscreen = RigidBodySimulationOffscreen::new(window_size);
+    config::test::floor_debris(2, &mut offscreen).unwrap();
+    offscreen.set_frame_output(Some(FrameOutput {
+        every: 2,
+        ..FrameOutput::new(&directory, ImageFormat::Ppm)
+    }));
+    assert!(offscreen.render_frame().unwrap().is_some());
+    for _ in 0..4 {
+        offscreen.tick(1. / 60.).unwrap();
+    }
+    let mut names = fs::read_dir(&directory)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(
+        names,
+        vec!["frame_00000.ppm", "frame_00002.ppm", "frame_00004.ppm"]
+    );
+    let ppm = fs::read(directory.join("frame_00004.ppm")).unwrap();
+    let header = b"P6\n32 24\n255\n";
+    assert_eq!(&ppm[..header.len()], header);
+    assert_eq!(ppm.len(), header.len() + 32 * 24 * 3);
+    assert!(ppm[header.len()..].iter().any(|value| *value != 0));
+    fs::remove_dir_all(&directory).unwrap();
+}
@@ -0,0 +1,690 @@
+#[cfg(test)]
+mod test;
+
+use crate::{
+    error::{Error, Result},
+    half_space::HalfSpace,
+    input::camera_mover::CameraMode,
+    math::{
+        matrix::Matrix3x3,
+        moment_of_inertia,
+        polyhedron::{Edge, Polyhedron},
+        vector::Vector3d,
+    },
+    mesh::{obj_loader, polyhedron_meshes, Mesh, MeshTriangle},
+    render::{Camera, Color, Draw3dTrait, RenderConfig, RenderOption},
+    rigid_body,
+    shape::Shape,
+    RigidBody, RigidBodySimulationTrait, SimulationConfig,
+};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+const MAX_ICOSPHERE_SUBDIVISIONS: u8 = 6;
+
+pub fn load<P: AsRef<Path>>(
+    path: P,
+    rigid_body_simulation: &mut impl RigidBodySimulationTrait,
+) -> Result<()> {
+    let path = path.as_ref();
+    Scene::from_file(path)?.build(
+        path.parent().unwrap_or_else(|| Path::new("")),
+        rigid_body_simulation,
+    )
+}
+
+pub fn save<P: AsRef<Path>>(
+    path: P,
+    rigid_body_simulation: &mut impl RigidBodySimulationTrait,
+) -> Result<()> {
+    Scene::capture(rigid_body_simulation).to_file(path)
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Scene {
+    #[serde(default)]
+    pub bounding_box: Option<SceneBoundingBox>,
+    #[serde(default)]
+    pub camera: Option<SceneCamera>,
+    #[serde(default)]
+    pub half_spaces: Vec<SceneHalfSpace>,
+    #[serde(default)]
+    pub render_config: Option<RenderConfig>,
+    #[serde(default)]
+    pub rigid_bodies: Vec<SceneRigidBody>,
+    #[serde(default)]
+    pub simulation_config: Option<SimulationConfig>,
+}
+
+impl Scene {
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let json =
+            fs::read_to_string(path).map_err(|e| Error::from(e).path(path))?;
+        Self::from_json(&json).map_err(|e| e.path(path))
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).map_err(|e| Error::Parse {
+            line: e.line(),
+            message: e.to_string(),
+        })
+    }
+
+    pub fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        fs::write(path, self.to_json()?).map_err(|e| Error::from(e).path(path))
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| Error::Format(e.to_string()))
+    }
+
+    pub fn build(
+        &self,
+        base_dir: &Path,
+        rigid_body_simulation: &mut impl RigidBodySimulationTrait,
+    ) -> Result<()> {
+        if let Some(simulation_config) = &self.simulation_config {
+            rigid_body_simulation.set_simulation_config(*simulation_config);
+        }
+        if let Some(render_config) = &self.render_config {
+            rigid_body_simulation.set_render_config(render_config);
+        }
+        if let Some(bounding_box) = &self.bounding_box {
+            rigid_body_simulation.set_bounding_box(Some((
+                &vector(&bounding_box.min),
+                &vector(&bounding_box.max),
+                bounding_box
+                    .render
+                    .render_option(None)
+                    .map_err(|e| e.context("bounding box"))?,
+            )));
+        }
+        if let Some(camera) = &self.camera {
+            *rigid_body_simulation.camera_mut() = Camera::new(
+                &vector(&camera.position),
+                camera.theta_x,
+                camera.theta_y,
+            );
+            let camera_mover = rigid_body_simulation.camera_mover_mut();
+            camera_mover.center = vector(&camera.center);
+            camera_mover.mode = match camera.mode {
+                SceneCameraMode::Fps => CameraMode::Fps,
+                SceneCameraMode::Rel => CameraMode::Rel,
+            };
+        }
+        for (i, half_space) in self.half_spaces.iter().enumerate() {
+            rigid_body_simulation.add_half_space(
+                HalfSpace::new(
+                    &vector(&half_space.position),
+                    &vector(&half_space.normal),
+                )
+                .map_err(|e| e.context(format!("half space {}", i)))?,
+                half_space
+                    .render
+                    .render_option(None)
+                    .map_err(|e| e.context(format!("half space {}", i)))?,
+            );
+        }
+        for (i, rigid_body) in self.rigid_bodies.iter().enumerate() {
+            let (rigid_body, render_option) = rigid_body
+                .build(base_dir)
+                .map_err(|e| e.context(format!("rigid body {}", i)))?;
+            rigid_body_simulation.add_rigid_body(rigid_body, render_option);
+        }
+        Ok(())
+    }
+
+    pub fn capture(
+        rigid_body_simulation: &mut impl RigidBodySimulationTrait,
+    ) -> Self {
+        let core = rigid_body_simulation.rigid_body_simulation_core_access();
+        let simulation_bounding_box = core.simulation.bounding_box();
+        let bounding_box =
+            simulation_bounding_box
+                .inner_opt
+                .as_ref()
+                .map(|bounding_box| SceneBoundingBox {
+                    min: array(&bounding_box.dimensions[0]),
+                    max: array(&bounding_box.dimensions[1]),
+                    render: SceneRender::capture(
+                        core.renderer
+                            .render_option(simulation_bounding_box.uid),
+                    ),
+                });
+        let half_spaces = core
+            .simulation
+            .half_spaces()
+            .iter()
+            .filter(|half_space| match &simulation_bounding_box.inner_opt {
+                Some(bounding_box) => {
+                    !bounding_box.half_space_uids.contains(&half_space.uid())
+                }
+                None => true,
+            })
+            .map(|half_space| SceneHalfSpace {
+                position: array(half_space.position()),
+                normal: array(half_space.normal()),
+                render: SceneRender::capture(
+                    core.renderer.render_option(half_space.uid()),
+                ),
+            })
+            .collect();
+        let rigid_bodies = core
+            .simulation
+            .rigid_bodies()
+            .iter()
+            .map(|rigid_body| SceneRigidBody {
+                shape: SceneShape::capture(rigid_body),
+                mass_inv: rigid_body.mass_inv(),
+                position: array(&rigid_body.position),
+                rotation: matrix_array(rigid_body.rotation()),
+                momentum: array(&rigid_body.momentum),
+                angular_momentum: array(&rigid_body.angular_momentum),
+                inertia_body_inv: Some(matrix_array(
+                    rigid_body.inertia_body_inv(),
+                )),
+                render: SceneRender::capture(
+                    core.renderer.render_option(rigid_body.uid()),
+                ),
+            })
+            .collect();
+        let camera = core.renderer.camera_mut();
+        let camera = SceneCamera {
+            position: array(&camera.position),
+            theta_x: camera.theta_x(),
+            theta_y: camera.theta_y(),
+            center: array(&core.camera_mover.center),
+            mode: match core.camera_mover.mode {
+                CameraMode::Fps => SceneCameraMode::Fps,
+                CameraMode::Rel => SceneCameraMode::Rel,
+            },
+        };
+        Self {
+            bounding_box,
+            camera: Some(camera),
+            half_spaces,
+            render_config: Some(core.renderer.render_config()),
+            rigid_bodies,
+            simulation_config: Some(*core.simulation.config()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SceneBoundingBox {
+    pub min: [f64; 3],
+    pub max: [f64; 3],
+    #[serde(default)]
+    pub render: SceneRender,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SceneHalfSpace {
+    pub position: [f64; 3],
+    pub normal: [f64; 3],
+    #[serde(default)]
+    pub render: SceneRender,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SceneCamera {
+    pub position: [f64; 3],
+    #[serde(default)]
+    pub theta_x: f64,
+    #[serde(default)]
+    pub theta_y: f64,
+    #[serde(default)]
+    pub center: [f64; 3],
+    #[serde(default)]
+    pub mode: SceneCameraMode,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize)]
+pub enum SceneCameraMode {
+    #[default]
+    Fps,
+    Rel,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SceneRigidBody {
+    pub shape: SceneShape,
+    pub mass_inv: f64,
+    pub position: [f64; 3],
+    #[serde(default = "identity")]
+    pub rotation: [[f64; 3]; 3],
+    #[serde(default)]
+    pub momentum: [f64; 3],
+    #[serde(default)]
+    pub angular_momentum: [f64; 3],
+    #[serde(default)]
+    pub inertia_body_inv: Option<[[f64; 3]; 3]>,
+    #[serde(default)]
+    pub render: SceneRender,
+}
+
+impl SceneRigidBody {
+    pub fn build(&self, base_dir: &Path) -> Result<(RigidBody, RenderOption)> {
+        let mesh_opt = self.shape.mesh(base_dir)?;
+        let inertia_body_inv = match &self.inertia_body_inv {
+            Some(inertia_body_inv) => Matrix3x3::new(inertia_body_inv),
+            None if self.mass_inv < f64::EPSILON => Matrix3x3::default(),
+            None => self
+                .shape
+                .inertia_body(1. / self.mass_inv, &mesh_opt)?
+                .inverse()
+                .ok_or_else(|| {
+                    Error::SingularInertia(
+                        "Scene rigid body inertia is not invertible.".into(),
+                    )
+                })?,
+        };
+        let position = vector(&self.position);
+        let rotation = Matrix3x3::new(&self.rotation);
+        let momentum = vector(&self.momentum);
+        let angular_momentum = vector(&self.angular_momentum);
+        let rigid_body = match (&self.shape, &mesh_opt) {
+            (shape, _) if shape.shape() != Shape::Polyhedron => {
+                RigidBody::from_shape(
+                    shape.shape(),
+                    self.mass_inv,
+                    &inertia_body_inv,
+                    &position,
+                    &rotation,
+                    &momentum,
+                    &angular_momentum,
+                )?
+            }
+            (_, Some(mesh)) => RigidBody::from_mesh(
+                mesh,
+                self.mass_inv,
+                &inertia_body_inv,
+                &position,
+                &rotation,
+                &momentum,
+                &angular_momentum,
+            )?,
+            (shape, None) => RigidBody::new(
+                self.mass_inv,
+                &inertia_body_inv,
+                shape.polyhedron()?,
+                &position,
+                &rotation,
+                &momentum,
+                &angular_momentum,
+            )?,
+        };
+        self.render.check_indices(rigid_body.polyhedron_body())?;
+        let render_option = self.render.render_option(mesh_opt.or_else(
+            || match &self.shape {
+                SceneShape::Cuboid { dimensions } => {
+                    Some(polyhedron_meshes::cuboid(&vector(dimensions)))
+                }
+                shape => shape.shape().mesh(),
+            },
+        ))?;
+        Ok((rigid_body, render_option))
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "type")]
+pub enum SceneShape {
+    Capsule {
+        radius: f64,
+        height: f64,
+    },
+    Cuboid {
+        dimensions: [f64; 3],
+    },
+    Cylinder {
+        radius: f64,
+        height: f64,
+    },
+    Icosahedron {
+        radius: f64,
+    },
+    Icosphere {
+        radius: f64,
+        subdivisions: u8,
+    },
+    Tetrahedron {
+        radius: f64,
+    },
+    Obj {
+        path: String,
+    },
+    Polyhedron {
+        vertices: Vec<[f64; 3]>,
+        faces: Vec<Vec<usize>>,
+        edges: Vec<[usize; 2]>,
+    },
+    Sphere {
+        radius: f64,
+    },
+}
+
+impl SceneShape {
+    fn capture(rigid_body: &RigidBody) -> Self {
+        let polyhedron = match rigid_body.shape() {
+            Shape::Polyhedron => rigid_body.polyhedron_body(),
+            Shape::Sphere { radius } => {
+                return Self::Sphere { radius: *radius }
+            }
+            Shape::Capsule { radius, height } => {
+                return Self::Capsule {
+                    radius: *radius,
+                    height: *height,
+                }
+            }
+            Shape::Cylinder { radius, height } => {
+                return Self::Cylinder {
+                    radius: *radius,
+                    height: *height,
+                }
+            }
+        };
+        Self::Polyhedron {
+            vertices: polyhedron.vertices().iter().map(array).collect(),
+            faces: polyhedron
+                .faces()
+                .iter()
+                .map(|face| face.vertex_indices().to_vec())
+                .collect(),
+            edges: polyhedron
+                .edges()
+                .iter()
+                .map(|edge| [edge.start_index(), edge.end_index()])
+                .collect(),
+        }
+    }
+
+    fn inertia_body(
+        &self,
+        mass: f64,
+        mesh_opt: &Option<Mesh>,
+    ) -> Result<Matrix3x3> {
+        Ok(match self {
+            Self::Cuboid { dimensions } => {
+                moment_of_inertia::aligned_cuboid(&vector(dimensions), mass)
+            }
+            Self::Icosahedron { radius } | Self::Icosphere { radius, .. } => {
+                moment_of_inertia::solid_sphere(*radius, mass)
+            }
+            Self::Tetrahedron { .. } => {
+                let vertices = &mesh_opt.as_ref().unwrap().vertices;
+                moment_of_inertia::regular_tetrahedron(
+                    vertices[0].dist(&vertices[1]),
+                    mass,
+                )
+            }
+            Self::Obj { .. } => moment_of_inertia::polyhedron(
+                &rigid_body::polyhedron_from_mesh(mesh_opt.as_ref().unwrap())?,
+                mass,
+            )?,
+            Self::Polyhedron { .. } => {
+                moment_of_inertia::polyhedron(&self.polyhedron()?, mass)?
+            }
+            Self::Capsule { .. }
+            | Self::Cylinder { .. }
+            | Self::Sphere { .. } => {
+                self.shape().inertia_body(mass).ok_or_else(|| {
+                    Error::InvalidValue(format!(
+                        "Scene shape {:?} has no analytic inertia.",
+                        self.shape()
+                    ))
+                })?
+            }
+        })
+    }
+
+    fn mesh(&self, base_dir: &Path) -> Result<Option<Mesh>> {
+        Ok(match self {
+            Self::Icosahedron { radius } => {
+                Some(polyhedron_meshes::regular_icosahedron(*radius))
+            }
+            Self::Icosphere {
+                radius,
+                subdivisions,
+            } => {
+                if *subdivisions > MAX_ICOSPHERE_SUBDIVISIONS {
+                    return Err(Error::InvalidValue(format!(
+                        "Scene icosphere subdivisions {} exceed {}.",
+                        subdivisions, MAX_ICOSPHERE_SUBDIVISIONS
+                    )));
+                }
+                Some(polyhedron_meshes::icosphere(*radius, *subdivisions))
+            }
+            Self::Tetrahedron { radius } => {
+                Some(polyhedron_meshes::regular_tetrahedron(*radius))
+            }
+            Self::Obj { path } => {
+                let path = base_dir.join(path);
+                Some(obj_loader::simple(&path)?)
+            }
+            Self::Capsule { .. }
+            | Self::Cuboid { .. }
+            | Self::Cylinder { .. }
+            | Self::Polyhedron { .. }
+            | Self::Sphere { .. } => None,
+        })
+    }
+
+    fn shape(&self) -> Shape {
+        match self {
+            Self::Capsule { radius, height } => Shape::Capsule {
+                radius: *radius,
+                height: *height,
+            },
+            Self::Cylinder { radius, height } => Shape::Cylinder {
+                radius: *radius,
+                height: *height,
+            },
+            Self::Sphere { radius } => Shape::Sphere { radius: *radius },
+            _ => Shape::Polyhedron,
+        }
+    }
+
+    fn polyhedron(&self) -> Result<Polyhedron> {
+        match self {
+            Self::Cuboid { dimensions } => {
+                Polyhedron::cuboid(&vector(dimensions))
+            }
+            Self::Polyhedron {
+                vertices,
+                faces,
+                edges,
+            } => {
+                let vertices = vertices.iter().map(vector).collect::<Vec<_>>();
+                for index in
+                    faces.iter().flatten().chain(edges.iter().flatten())
+                {
+                    if *index >= vertices.len() {
+                        return Err(Error::DegenerateGeometry(format!(
+                            "Scene polyhedron vertex index {} out of range.",
+                            index
+                        )));
+                    }
+                }
+                let edges = edges
+                    .iter()
+                    .map(|edge| Edge::new(edge[0], edge[1], &vertices))
+                    .collect();
+                Polyhedron::new(faces.clone(), edges, vertices)
+            }
+            _ => {
+                Err(Error::InvalidValue("Scene shape requires a mesh.".into()))
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(tag = "type")]
+pub enum SceneRender {
+    Invisible,
+    Edges {
+        edge_indices: Vec<usize>,
+        color: [u8; 3],
+    },
+    FaceEdges {
+        face_indices: Vec<usize>,
+        color: [u8; 3],
+    },
+    Mesh {
+        color: [u8; 3],
+        #[serde(default)]
+        mesh: Option<SceneMesh>,
+    },
+    PolyhedronEdges {
+        color: [u8; 3],
+    },
+    #[default]
+    None,
+}
+
+impl SceneRender {
+    fn capture(render_option_opt: Option<&RenderOption>) -> Self {
+        match render_option_opt {
+            Some(RenderOption::Invisible) => Self::Invisible,
+            Some(RenderOption::Edges {
+                edge_indices,
+                color,
+            }) => Self::Edges {
+                edge_indices: edge_indices.clone(),
+                color: color_array(color),
+            },
+            Some(RenderOption::FaceEdges {
+                face_indices,
+                color,
+            }) => Self::FaceEdges {
+                face_indices: face_indices.clone(),
+                color: color_array(color),
+            },
+            Some(RenderOption::Mesh { mesh, color }) => Self::Mesh {
+                color: color_array(color),
+                mesh: Some(SceneMesh {
+                    vertices: mesh.vertices.iter().map(array).collect(),
+                    triangles: mesh
+                        .mesh_triangles
+                        .iter()
+                        .map(|mesh_triangle| mesh_triangle.vertex_indices)
+                        .collect(),
+                }),
+            },
+            Some(RenderOption::PolyhedronEdges { color }) => {
+                Self::PolyhedronEdges {
+                    color: color_array(color),
+                }
+            }
+            Some(RenderOption::None) | None => Self::None,
+        }
+    }
+
+    fn check_indices(&self, polyhedron: &Polyhedron) -> Result<()> {
+        let (indices, count, name) = match self {
+            Self::Edges { edge_indices, .. } => {
+                (edge_indices, polyhedron.edges().len(), "edge")
+            }
+            Self::FaceEdges { face_indices, .. } => {
+                (face_indices, polyhedron.faces().len(), "face")
+            }
+            _ => return Ok(()),
+        };
+        match indices.iter().find(|index| **index >= count) {
+            Some(index) => Err(Error::InvalidValue(format!(
+                "Scene render {} index {} out of range for {} {}s.",
+                name, index, count, name
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    fn render_option(&self, mesh_opt: Option<Mesh>) -> Result<RenderOption> {
+        Ok(match self {
+            Self::Invisible => RenderOption::Invisible,
+            Self::Edges {
+                edge_indices,
+                color,
+            } => RenderOption::Edges {
+                edge_indices: edge_indices.clone(),
+                color: Color::rgb(color[0], color[1], color[2]),
+            },
+            Self::FaceEdges {
+                face_indices,
+                color,
+            } => RenderOption::FaceEdges {
+                face_indices: face_indices.clone(),
+                color: Color::rgb(color[0], color[1], color[2]),
+            },
+            Self::Mesh { color, mesh } => RenderOption::Mesh {
+                mesh: match (mesh, mesh_opt) {
+                    (Some(mesh), _) => mesh.mesh()?,
+                    (None, Some(mesh)) => mesh,
+                    (None, None) => {
+                        return Err(Error::InvalidValue(
+                            "Scene mesh render requires a mesh.".into(),
+                        ))
+                    }
+                },
+                color: Color::rgb(color[0], color[1], color[2]),
+            },
+            Self::PolyhedronEdges { color } => RenderOption::PolyhedronEdges {
+                color: Color::rgb(color[0], color[1], color[2]),
+            },
+            Self::None => RenderOption::None,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SceneMesh {
+    pub vertices: Vec<[f64; 3]>,
+    pub triangles: Vec<[usize; 3]>,
+}
+
+impl SceneMesh {
+    fn mesh(&self) -> Result<Mesh> {
+        let vertices = self.vertices.iter().map(vector).collect::<Vec<_>>();
+        let mut mesh_triangles = Vec::with_capacity(self.triangles.len());
+        for triangle in &self.triangles {
+            if triangle.iter().any(|index| *index >= vertices.len()) {
+                return Err(Error::DegenerateGeometry(
+                    "Scene mesh vertex index out of range.".into(),
+                ));
+            }
+            mesh_triangles.push(MeshTriangle::new(
+                triangle,
+                &MeshTriangle::normal(
+                    &vertices[triangle[0]],
+                    &vertices[triangle[1]],
+                    &vertices[triangle[2]],
+                ),
+            ));
+        }
+        Ok(Mesh::new(vertices, mesh_triangles))
+    }
+}
+
+fn array(vector: &Vector3d) -> [f64; 3] {
+    [vector[0], vector[1], vector[2]]
+}
+
+fn color_array(color: &Color) -> [u8; 3] {
+    [color.r, color.g, color.b]
+}
+
+fn identity() -> [[f64; 3]; 3] {
+    matrix_array(&Matrix3x3::identity())
+}
+
+fn matrix_array(matrix: &Matrix3x3) -> [[f64; 3]; 3] {
+    [matrix[0], matrix[1], matrix[2]]
+}
+
+fn vector(array: &[f64; 3]) -> Vector3d {
+    Vector3d::new(array[0], array[1], array[2])
+}
@@ -0,0 +1,231 @@
+use super::{Scene, SceneShape};
+use crate::{
+    config,
+    math::{matrix::Matrix3x3, moment_of_inertia, vector::Vector3d},
+    rigid_body::RigidBody,
+    Error, RigidBodySimulationCore, RigidBodySimulationTrait,
+};
+use std::path::Path;
+
+fn max_difference(
+    core_1: &RigidBodySimulationCore,
+    core_2: &RigidBodySimulationCore,
+) -> f64 {
+    let rigid_bodies_1 = core_1.simulation().rigid_bodies();
+    let rigid_bodies_2 = core_2.simulation().rigid_bodies();
+    assert_eq!(rigid_bodies_1.len(), rigid_bodies_2.len());
+    let mut ret = 0f64;
+    for (rigid_body_1, rigid_body_2) in
+        rigid_bodies_1.iter().zip(rigid_bodies_2)
+    {
+        ret = ret
+            .max(rigid_body_1.position.dist(&rigid_body_2.position))
+            .max(rigid_body_1.momentum.dist(&rigid_body_2.momentum))
+            .max(
+                rigid_body_1
+                    .angular_momentum
+                    .dist(&rigid_body_2.angular_momentum),
+            );
+        for i in 0..3 {
+            for j in 0..3 {
+                ret = ret.max(
+                    (rigid_body_1.rotation()[i][j]
+                        - rigid_body_2.rotation()[i][j])
+                        .abs(),
+                );
+            }
+        }
+    }
+    ret
+}
+
+#[test]
+fn capture_and_build_round_trip() {
+    let mut core_1 = RigidBodySimulationCore::new((1, 1));
+    config::test::floor_debris(4, &mut core_1).unwrap();
+    let json = Scene::capture(&mut core_1).to_json().unwrap();
+    let mut core_2 = RigidBodySimulationCore::new((1, 1));
+    Scene::from_json(&json)
+        .unwrap()
+        .build(Path::new(""), &mut core_2)
+        .unwrap();
+    assert!(max_difference(&core_1, &core_2) < 1e-12);
+    for _ in 0..30 {
+        core_1.simulation_mut().tick(1. / 60.).unwrap();
+        core_2.simulation_mut().tick(1. / 60.).unwrap();
+    }
+    assert!(max_difference(&core_1, &core_2) < 1e-6);
+}
+
+#[test]
+fn shapes_round_trip() {
+    let mut core_1 = RigidBodySimulationCore::new((1, 1));
+    config::test::shapes(&mut core_1).unwrap();
+    let json = Scene::capture(&mut core_1).to_json().unwrap();
+    let mut core_2 = RigidBodySimulationCore::new((1, 1));
+    Scene::from_json(&json)
+        .unwrap()
+        .build(Path::new(""), &mut core_2)
+        .unwrap();
+    for (rigid_body_1, rigid_body_2) in core_1
+        .simulation()
+        .rigid_bodies()
+        .iter()
+        .zip(core_2.simulation().rigid_bodies())
+    {
+        assert_eq!(rigid_body_1.shape(), rigid_body_2.shape());
+    }
+    for _ in 0..30 {
+        core_1.simulation_mut().tick(1. / 60.).unwrap();
+        core_2.simulation_mut().tick(1. / 60.).unwrap();
+    }
+    assert!(max_difference(&core_1, &core_2) < 1e-6);
+}
+
+#[test]
+fn build_from_description() {
+    let json = r#"{
+        "bounding_box": {
+            "min": [-10, -10, -10],
+            "max": [10, 10, 10],
+            "render": { "type": "PolyhedronEdges", "color": [255, 0, 0] }
+        },
+        "camera": { "position": [0, 0, -20], "mode": "Rel" },
+        "rigid_bodies": [
+            {
+                "shape": { "type": "Cuboid", "dimensions": [1, 2, 3] },
+                "mass_inv": 1,
+                "position": [0, 0, 0],
+                "momentum": [0, -1, 0],
+                "render": { "type": "Mesh", "color": [0, 255, 0] }
+            },
+            {
+                "shape": { "type": "Icosahedron", "radius": 1.5 },
+                "mass_inv": 0.5,
+                "position": [4, 0, 0]
+            },
+            {
+                "shape": { "type": "Tetrahedron", "radius": 1.5 },
+                "mass_inv": 0,
+                "position": [-4, 0, 0]
+            }
+        ]
+    }"#;
+    let mut core = RigidBodySimulationCore::new((1, 1));
+    Scene::from_json(json)
+        .unwrap()
+        .build(Path::new(""), &mut core)
+        .unwrap();
+    let rigid_bodies = core.simulation().rigid_bodies();
+    assert_eq!(rigid_bodies.len(), 3);
+    assert!(rigid_bodies[2].is_immovable());
+    assert!(!rigid_bodies[1].is_immovable());
+    assert_eq!(rigid_bodies[0].momentum[1], -1.);
+    assert_eq!(core.simulation().half_spaces().len(), 6);
+    assert!(Scene::from_json("{\"rigid_bodies\": [{}]}").is_err());
+    assert!(core.camera_mut().position[2] == -20.);
+}
+
+#[test]
+fn load_example_scene() {
+    let mut core = RigidBodySimulationCore::new((1, 1));
+    super::load("../scenes/tumbling.json", &mut core).unwrap();
+    assert_eq!(core.simulation().rigid_bodies().len(), 3);
+}
+
+#[test]
+fn polyhedron_inertia_from_geometry() {
+    let dimensions = Vector3d::new(1., 2., 3.);
+    let cuboid = RigidBody::cuboid(
+        &dimensions,
+        0.5,
+        &Vector3d::default(),
+        &Matrix3x3::identity(),
+        &Vector3d::default(),
+        &Vector3d::default(),
+    )
+    .unwrap();
+    let expected = moment_of_inertia::aligned_cuboid(&dimensions, 2.);
+    let mut shape = SceneShape::capture(&cuboid);
+    let inertia = shape.inertia_body(2., &None).unwrap();
+    for i in 0..3 {
+        for j in 0..3 {
+            assert!((inertia[i][j] - expected[i][j]).abs() < 1e-9);
+        }
+    }
+
+    if let SceneShape::Polyhedron { vertices, .. } = &mut shape {
+        for vertex in vertices.iter_mut() {
+            vertex[0] += 5.;
+        }
+    }
+    let inertia = shape.inertia_body(2., &None).unwrap();
+    for i in 0..3 {
+        for j in 0..3 {
+            assert!((inertia[i][j] - expected[i][j]).abs() < 1e-9);
+        }
+    }
+
+    let flat = SceneShape::Polyhedron {
+        vertices: vec![[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]],
+        faces: vec![vec![0, 1, 2], vec![0, 2, 1]],
+        edges: vec![[0, 1], [1, 2], [2, 0]],
+    };
+    assert!(matches!(
+        flat.inertia_body(1., &None),
+        Err(Error::DegenerateGeometry(_))
+    ));
+}
+
+#[test]
+fn invalid_render_indices_and_subdivisions_are_rejected() {
+    let build = |rigid_body: &str| {
+        let json = format!("{{\"rigid_bodies\": [{}]}}", rigid_body);
+        let mut core = RigidBodySimulationCore::new((1, 1));
+        let result = Scene::from_json(&json)
+            .unwrap()
+            .build(Path::new(""), &mut core);
+        (result, core.simulation().rigid_bodies().len())
+    };
+    let cuboid = r#""shape": { "type": "Cuboid", "dimensions": [1, 1, 1] },
+        "mass_inv": 1, "position": [0, 0, 0]"#;
+    let (result, count) = build(&format!(
+        r#"{{ {}, "render": {{ "type": "Edges", "edge_indices": [11],
+        "color": [0, 0, 0] }} }}"#,
+        cuboid
+    ));
+    assert!(result.is_ok());
+    assert_eq!(count, 1);
+    for render in &[
+        r#""type": "Edges", "edge_indices": [12]"#,
+        r#""type": "FaceEdges", "face_indices": [0, 6]"#,
+    ] {
+        let (result, count) = build(&format!(
+            r#"{{ {}, "render": {{ {}, "color": [0, 0, 0] }} }}"#,
+            cuboid, render
+        ));
+        let error = result.unwrap_err();
+        assert!(error.to_string().contains("rigid body 0"));
+        assert!(matches!(error.root(), Error::InvalidValue(_)));
+        assert_eq!(count, 0);
+    }
+    let (result, _) = build(
+        r#"{ "shape": { "type": "Icosphere", "radius": 1, "subdivisions": 7 },
+        "mass_inv": 1, "position": [0, 0, 0] }"#,
+    );
+    assert!(matches!(result.unwrap_err().root(), Error::InvalidValue(_)));
+}
+
+#[test]
+fn zero_half_space_normal_is_rejected() {
+    let json = r#"{
+        "half_spaces": [{ "position": [0, 0, 0], "normal": [0, 0, 0] }]
+    }"#;
+    let mut core = RigidBodySimulationCore::new((1, 1));
+    let error = Scene::from_json(json)
+        .unwrap()
+        .build(Path::new(""), &mut core)
+        .unwrap_err();
+    assert!(matches!(error.root(), Error::DegenerateGeometry(_)));
+    assert!(core.simulation().half_spaces().is_empty());
+}
@@ -0,0 +1,189 @@
+use crate::{math::vector::Vector3d, UID};
+
+const MAX_DEPTH: usize = 32;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Mass {
+    pub uid: UID,
+    pub position: Vector3d,
+    pub mass: f64,
+}
+
+#[derive(Clone, Debug)]
+struct Node {
+    center: Vector3d,
+    half_size: f64,
+    mass: f64,
+    mass_center: Vector3d,
+    children: Vec<usize>,
+    masses: Vec<usize>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Octree {
+    masses: Vec<Mass>,
+    nodes: Vec<Node>,
+}
+
+impl Octree {
+    pub fn new(masses: Vec<Mass>) -> Self {
+        let mut ret = Self {
+            masses,
+            nodes: Vec::new(),
+        };
+        if ret.masses.is_empty() {
+            return ret;
+        }
+        let mut min = ret.masses[0].position;
+        let mut max = min;
+        for mass in &ret.masses {
+            for axis in 0..3 {
+                min[axis] = min[axis].min(mass.position[axis]);
+                max[axis] = max[axis].max(mass.position[axis]);
+            }
+        }
+        let dimensions = max.sub(&min);
+        let half_size = dimensions[0].max(dimensions[1]).max(dimensions[2])
+            * 0.5
+            + f64::EPSILON;
+        let indices = (0..ret.masses.len()).collect::<Vec<_>>();
+        ret.build(indices, &min.add(&max).scale(0.5), half_size, 0);
+        ret
+    }
+
+    pub fn acceleration(
+        &self,
+        uid: UID,
+        position: &Vector3d,
+        theta: f64,
+        softening: f64,
+    ) -> Vector3d {
+        let mut ret = Vector3d::default();
+        if !self.nodes.is_empty() {
+            self.acceleration_impl(
+                0, uid, position, theta, softening, &mut ret,
+            );
+        }
+        ret
+    }
+
+    fn acceleration_impl(
+        &self,
+        node_index: usize,
+        uid: UID,
+        position: &Vector3d,
+        theta: f64,
+        softening: f64,
+        acceleration: &mut Vector3d,
+    ) {
+        let node = &self.nodes[node_index];
+        if node.children.is_empty() {
+            for mass in node.masses.iter().map(|index| &self.masses[*index]) {
+                if mass.uid != uid {
+                    acceleration.add_assign(&attraction(
+                        position,
+                        &mass.position,
+                        mass.mass,
+                        softening,
+                    ));
+                }
+            }
+            return;
+        }
+        let contains = (0..3).all(|axis| {
+            (position[axis] - node.center[axis]).abs() <= node.half_size
+        });
+        let dist = position.dist(&node.mass_center);
+        if !contains && node.half_size * 2. < theta * dist {
+            acceleration.add_assign(&attraction(
+                position,
+                &node.mass_center,
+                node.mass,
+                softening,
+            ));
+            return;
+        }
+        for child in &node.children {
+            self.acceleration_impl(
+                *child,
+                uid,
+                position,
+                theta,
+                softening,
+                acceleration,
+            );
+        }
+    }
+
+    fn build(
+        &mut self,
+        indices: Vec<usize>,
+        center: &Vector3d,
+        half_size: f64,
+        depth: usize,
+    ) -> usize {
+        let mut mass = 0.;
+        let mut mass_center = Vector3d::default();
+        for index in &indices {
+            let entry = &self.masses[*index];
+            mass += entry.mass;
+            mass_center.add_assign(&entry.position.scale(entry.mass));
+        }
+        if mass > 0. {
+            mass_center.scale_assign(1. / mass);
+        }
+        let node_index = self.nodes.len();
+        self.nodes.push(Node {
+            center: *center,
+            half_size,
+            mass,
+            mass_center,
+            children: Vec::new(),
+            masses: Vec::new(),
+        });
+        if indices.len() <= 1 || depth >= MAX_DEPTH {
+            self.nodes[node_index].masses = indices;
+            return node_index;
+        }
+        let mut octants = vec![Vec::new(); 8];
+        for index in indices {
+            let position = &self.masses[index].position;
+            let octant = (0..3)
+                .filter(|axis| position[*axis] > center[*axis])
+                .fold(0, |acc, axis| acc | 1 << axis);
+            octants[octant].push(index);
+        }
+        let half_size = half_size * 0.5;
+        for (octant, indices) in octants.into_iter().enumerate() {
+            if indices.is_empty() {
+                continue;
+            }
+            let mut child_center = *center;
+            for axis in 0..3 {
+                child_center[axis] += if octant & 1 << axis == 0 {
+                    -half_size
+                } else {
+                    half_size
+                };
+            }
+            let child =
+                self.build(indices, &child_center, half_size, depth + 1);
+            self.nodes[node_index].children.push(child);
+        }
+        node_index
+    }
+}
+
+pub fn attraction(
+    position: &Vector3d,
+    source: &Vector3d,
+    mass: f64,
+    softening: f64,
+) -> Vector3d {
+    let offset = source.sub(position);
+    let dist_sq = offset.mag_sq() + softening * softening;
+    if dist_sq == 0. {
+        return Vector3d::default();
+    }
+    offset.scale(mass / (dist_sq * dist_sq.sqrt()))
+}
@@ -1,82 +1,200 @@
-use super::rigid_body::{self, RigidBody};
+use super::{
+    half_space::HalfSpace,
+    rigid_body::{self, RigidBody},
+    simulation_config::SimulationConfig,
+};
 use crate::{
-    math::{matrix::Matrix3x3, vector::Vector3d},
+    error::{Error, Result},
+    math::{
+        matrix::Matrix3x3, matrix_vector, polyhedron::Polyhedron,
+        rotation_matrix, vector::Vector3d, Quarternion,
+    },
     UID,
 };
+use std::f64::consts::TAU;
+
+pub type BoundingBoxMotionFn = fn(f64) -> (Vector3d, Matrix3x3);
+
+pub trait BoundingBoxMotion: Send + Sync {
+    fn box_clone(&self) -> Box<dyn BoundingBoxMotion>;
+
+    fn pose(&self, time: f64) -> (Vector3d, Matrix3x3);
+}
+
+impl Clone for Box<dyn BoundingBoxMotion> {
+    fn clone(&self) -> Self {
+        self.box_clone()
+    }
+}
+
+impl BoundingBoxMotion for BoundingBoxMotionFn {
+    fn box_clone(&self) -> Box<dyn BoundingBoxMotion> {
+        Box::new(*self)
+    }
+
+    fn pose(&self, time: f64) -> (Vector3d, Matrix3x3) {
+        self(time)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Shake {
+    pub amplitude: Vector3d,
+    pub frequency: f64,
+}
+
+impl BoundingBoxMotion for Shake {
+    fn box_clone(&self) -> Box<dyn BoundingBoxMotion> {
+        Box::new(*self)
+    }
+
+    fn pose(&self, time: f64) -> (Vector3d, Matrix3x3) {
+        (
+            self.amplitude.scale((TAU * self.frequency * time).sin()),
+            Matrix3x3::identity(),
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Spin {
+    pub axis: Vector3d,
+    pub angular_speed: f64,
+}
+
+impl BoundingBoxMotion for Spin {
+    fn box_clone(&self) -> Box<dyn BoundingBoxMotion> {
+        Box::new(*self)
+    }
 
+    fn pose(&self, time: f64) -> (Vector3d, Matrix3x3) {
+        (
+            Vector3d::default(),
+            rotation_matrix::general(
+                &self.axis.normal(),
+                self.angular_speed * time,
+            ),
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Tilt {
+    pub axis: Vector3d,
+    pub angle: f64,
+    pub frequency: f64,
+}
+
+impl BoundingBoxMotion for Tilt {
+    fn box_clone(&self) -> Box<dyn BoundingBoxMotion> {
+        Box::new(*self)
+    }
+
+    fn pose(&self, time: f64) -> (Vector3d, Matrix3x3) {
+        (
+            Vector3d::default(),
+            rotation_matrix::general(
+                &self.axis.normal(),
+                self.angle * (TAU * self.frequency * time).sin(),
+            ),
+        )
+    }
+}
+
+#[derive(Clone)]
 pub struct BoundingBox {
     pub uid: UID,
     pub inner_opt: Option<BoundingBoxInner>,
+    motion_opt: Option<Box<dyn BoundingBoxMotion>>,
 }
 
 impl BoundingBox {
     pub fn new() -> Self {
         Self {
-            uid: crate::get_new_uid(),
+            uid: 0,
             inner_opt: None,
+            motion_opt: None,
+        }
+    }
+
+    pub fn advance(
+        &mut self,
+        time: f64,
+        delta_t: f64,
+        half_spaces: &mut [HalfSpace],
+    ) {
+        if let (Some(bounding_box), Some(motion)) =
+            (&mut self.inner_opt, &self.motion_opt)
+        {
+            let (position, rotation) = motion.pose(time);
+            bounding_box.set_pose(&position, &rotation, delta_t, half_spaces);
+        }
+    }
+
+    pub fn contain(
+        &self,
+        config: &SimulationConfig,
+        rigid_bodies: &mut [RigidBody],
+    ) {
+        if let Some(bounding_box) = &self.inner_opt {
+            bounding_box.contain(config, rigid_bodies);
+        }
+    }
+
+    pub fn reset(&mut self, half_spaces: &mut [HalfSpace]) {
+        if let Some(bounding_box) = &mut self.inner_opt {
+            let (position, rotation) = match &self.motion_opt {
+                Some(motion) => motion.pose(0.),
+                None => (Vector3d::default(), Matrix3x3::identity()),
+            };
+            bounding_box.set_pose(&position, &rotation, 0., half_spaces);
         }
     }
 
     pub fn set(
         &mut self,
         dimensions_opt: &Option<(&Vector3d, &Vector3d)>,
-        initial_rigid_bodies: &mut Vec<RigidBody>,
-        rigid_bodies: &mut Vec<RigidBody>,
+        half_spaces: &mut Vec<HalfSpace>,
+        next_uid: &mut UID,
     ) {
-        if let Some(bounding_box) = &mut self.inner_opt {
-            let mut i = 0;
-            while i < rigid_bodies.len()
-                && !bounding_box.rigid_body_uids.is_empty()
-            {
-                if let Some(index) = bounding_box
-                    .rigid_body_uids
-                    .iter()
-                    .position(|e| *e == rigid_bodies[i].uid())
-                {
-                    bounding_box.rigid_body_uids.swap_remove(index);
-                    initial_rigid_bodies.swap_remove(i);
-                    rigid_bodies.swap_remove(i);
-                } else {
-                    i += 1;
-                }
-            }
+        if let Some(bounding_box) = &self.inner_opt {
+            let half_space_uids = &bounding_box.half_space_uids;
+            half_spaces.retain(|e| !half_space_uids.contains(&e.uid()));
             self.inner_opt = None;
         }
         if let Some((min, max)) = &dimensions_opt {
-            let position = max.add(min).scale(0.5);
-            let x = position[0];
-            let y = position[1];
-            let z = position[2];
-            let dimensions = max.sub(min);
-            let xd = dimensions[0];
-            let yd = dimensions[1];
-            let zd = dimensions[2];
-            let mut rigid_body_uids = Vec::with_capacity(6);
-            let mut add_bounding_box_cuboid = |position: &Vector3d| {
-                let cuboid = RigidBody::cuboid(
-                    &dimensions,
-                    0.,
-                    position,
-                    &Matrix3x3::identity(),
-                    &Vector3d::default(),
-                    &Vector3d::default(),
-                );
-                rigid_body_uids.push(cuboid.uid());
-                initial_rigid_bodies.push(cuboid.clone());
-                rigid_bodies.push(cuboid);
-            };
-            add_bounding_box_cuboid(&Vector3d::new(x, y, z - zd));
-            add_bounding_box_cuboid(&Vector3d::new(x, y, z + zd));
-            add_bounding_box_cuboid(&Vector3d::new(x, y - yd, z));
-            add_bounding_box_cuboid(&Vector3d::new(x, y + yd, z));
-            add_bounding_box_cuboid(&Vector3d::new(x - xd, y, z));
-            add_bounding_box_cuboid(&Vector3d::new(x + xd, y, z));
+            let mut half_space_uids = Vec::with_capacity(6);
+            for axis in 0..3 {
+                for (position, sign) in &[(min, 1.), (max, -1.)] {
+                    let mut normal = Vector3d::default();
+                    normal[axis] = *sign;
+                    let mut half_space =
+                        HalfSpace::new(position, &normal).expect("axis normal");
+                    half_space.uid = super::new_uid(next_uid);
+                    half_space_uids.push(half_space.uid());
+                    half_spaces.push(half_space);
+                }
+            }
             self.inner_opt = Some(BoundingBoxInner {
                 dimensions: [**min, **max],
-                rigid_body_uids,
+                half_space_uids,
+                position: Vector3d::default(),
+                rotation: Matrix3x3::identity(),
+                velocity: Vector3d::default(),
+                angular_velocity: Vector3d::default(),
             });
+            self.reset(half_spaces);
         }
     }
+
+    pub fn set_motion(
+        &mut self,
+        motion_opt: Option<Box<dyn BoundingBoxMotion>>,
+        half_spaces: &mut [HalfSpace],
+    ) {
+        self.motion_opt = motion_opt;
+        self.reset(half_spaces);
+    }
 }
 
 impl Default for BoundingBox {
@@ -85,32 +203,229 @@ impl Default for BoundingBox {
     }
 }
 
+#[derive(Clone)]
 pub struct BoundingBoxInner {
     pub dimensions: rigid_body::BoundingBox,
-    pub rigid_body_uids: Vec<usize>,
+    pub half_space_uids: Vec<UID>,
+    pub position: Vector3d,
+    pub rotation: Matrix3x3,
+    pub velocity: Vector3d,
+    pub angular_velocity: Vector3d,
 }
 
 impl BoundingBoxInner {
-    pub fn contain(&self, rigid_bodies: &mut [RigidBody]) {
+    pub fn center(&self) -> Vector3d {
+        self.rest_center().add(&self.position)
+    }
+
+    pub fn contain(
+        &self,
+        config: &SimulationConfig,
+        rigid_bodies: &mut [RigidBody],
+    ) {
+        let center = self.center();
+        let half_dimensions =
+            self.dimensions[1].sub(&self.dimensions[0]).scale(0.5);
         for rigid_body in rigid_bodies {
             if rigid_body.is_immovable() {
                 continue;
             }
+            let mut contained = false;
             for axis in 0..3 {
-                let min_dist = rigid_body.bounding_box()[0][axis]
-                    - self.dimensions[0][axis];
-                if min_dist < 0. {
-                    rigid_body.position[axis] -= min_dist;
-                    rigid_body.update_geometry();
+                let mut local_normal = Vector3d::default();
+                local_normal[axis] = 1.;
+                let normal =
+                    matrix_vector::mult_3(&self.rotation, &local_normal);
+                let center_dist = center.dot(&normal);
+                let max_dist = rigid_body.support(&normal).dot(&normal)
+                    - center_dist
+                    - half_dimensions[axis];
+                let min_dist =
+                    rigid_body.support(&normal.scale(-1.)).dot(&normal)
+                        - center_dist
+                        + half_dimensions[axis];
+                let epsilon = config.collision_epsilon;
+                let offset = if max_dist > epsilon && min_dist < -epsilon {
+                    -(max_dist + min_dist) / 2.
+                } else if max_dist > epsilon {
+                    -max_dist
+                } else if min_dist < -epsilon {
+                    -min_dist
+                } else {
                     continue;
+                };
+                let inward = normal.scale(offset.signum());
+                rigid_body.position.add_assign(&normal.scale(offset));
+                rigid_body.update_geometry();
+                let wall_velocity = self
+                    .angular_velocity
+                    .cross(&rigid_body.position.sub(&center))
+                    .add(&self.velocity);
+                let rel_vel =
+                    rigid_body.velocity().sub(&wall_velocity).dot(&inward);
+                if rel_vel < 0. {
+                    rigid_body.momentum.sub_assign(&inward.scale(
+                        (1. + config.coefficient_of_restitution) * rel_vel
+                            / rigid_body.mass_inv(),
+                    ));
+                    rigid_body.update_velocity();
                 }
-                let max_dist = rigid_body.bounding_box()[1][axis]
-                    - self.dimensions[1][axis];
-                if max_dist > 0. {
-                    rigid_body.position[axis] -= max_dist;
-                    rigid_body.update_geometry();
+                contained = true;
+            }
+            if contained {
+                rigid_body.wake();
+            }
+        }
+    }
+
+    pub fn polyhedron(&self) -> Result<Polyhedron> {
+        let center = self.center();
+        let mut ret =
+            Polyhedron::cuboid(&self.dimensions[1].sub(&self.dimensions[0]))?;
+        for vertex in ret.vertices_mut() {
+            *vertex =
+                matrix_vector::mult_3(&self.rotation, vertex).add(&center);
+        }
+        ret.update();
+        Ok(ret)
+    }
+
+    pub fn set_pose(
+        &mut self,
+        position: &Vector3d,
+        rotation: &Matrix3x3,
+        delta_t: f64,
+        half_spaces: &mut [HalfSpace],
+    ) {
+        if delta_t > 0. {
+            self.velocity = position.sub(&self.position).scale(1. / delta_t);
+            let delta =
+                Quarternion::from_matrix(&rotation.mult_t(&self.rotation));
+            let sin = delta.v().mag();
+            self.angular_velocity = if sin > 0. {
+                delta.v().scale(2. * sin.atan2(delta.s()) / (sin * delta_t))
+            } else {
+                Vector3d::default()
+            };
+        } else {
+            self.velocity = Vector3d::default();
+            self.angular_velocity = Vector3d::default();
+        }
+        self.position = *position;
+        self.rotation = *rotation;
+
+        let rest_center = self.rest_center();
+        let center = self.center();
+        for half_space in half_spaces {
+            let index = match self
+                .half_space_uids
+                .iter()
+                .position(|uid| *uid == half_space.uid())
+            {
+                Some(index) => index,
+                None => continue,
+            };
+            let axis = index / 2;
+            let (local_position, sign) = if index % 2 == 0 {
+                (&self.dimensions[0], 1.)
+            } else {
+                (&self.dimensions[1], -1.)
+            };
+            let mut normal = Vector3d::default();
+            normal[axis] = sign;
+            half_space.set_pose(
+                &matrix_vector::mult_3(
+                    &self.rotation,
+                    &local_position.sub(&rest_center),
+                )
+                .add(&center),
+                &matrix_vector::mult_3(&self.rotation, &normal),
+            );
+            half_space.set_motion(
+                &self.velocity,
+                &self.angular_velocity,
+                &center,
+            );
+        }
+    }
+
+    fn rest_center(&self) -> Vector3d {
+        self.dimensions[0].add(&self.dimensions[1]).scale(0.5)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct PeriodicDomain {
+    pub dimensions: rigid_body::BoundingBox,
+    pub periodic: [bool; 3],
+}
+
+impl PeriodicDomain {
+    pub fn new(
+        min: &Vector3d,
+        max: &Vector3d,
+        periodic: [bool; 3],
+    ) -> Result<Self> {
+        for axis in 0..3 {
+            if !min[axis].is_finite()
+                || !max[axis].is_finite()
+                || periodic[axis] && min[axis] >= max[axis]
+            {
+                return Err(Error::InvalidValue(format!(
+                    "Invalid periodic domain bounds {} and {} on axis {}.",
+                    min[axis], max[axis], axis
+                )));
+            }
+        }
+        Ok(Self {
+            dimensions: [*min, *max],
+            periodic,
+        })
+    }
+
+    pub fn image_shift(
+        &self,
+        position: &Vector3d,
+        other: &Vector3d,
+    ) -> Vector3d {
+        let mut ret = Vector3d::default();
+        for axis in 0..3 {
+            if !self.periodic[axis] {
+                continue;
+            }
+            let length = self.length(axis);
+            ret[axis] =
+                -length * ((other[axis] - position[axis]) / length).round();
+        }
+        ret
+    }
+
+    pub fn length(&self, axis: usize) -> f64 {
+        self.dimensions[1][axis] - self.dimensions[0][axis]
+    }
+
+    pub fn wrap(&self, rigid_bodies: &mut [RigidBody]) {
+        for rigid_body in rigid_bodies {
+            if rigid_body.is_immovable() {
+                continue;
+            }
+            let mut wrapped = false;
+            for axis in 0..3 {
+                if !self.periodic[axis] {
+                    continue;
+                }
+                let length = self.length(axis);
+                let offset =
+                    rigid_body.position[axis] - self.dimensions[0][axis];
+                if offset < 0. || offset >= length {
+                    rigid_body.position[axis] -=
+                        length * (offset / length).floor();
+                    wrapped = true;
                 }
             }
+            if wrapped {
+                rigid_body.update_geometry();
+            }
         }
     }
 }
@@ -0,0 +1,383 @@
+use super::{
+    super::{
+        collision_table::CollisionTable,
+        rigid_body::{BoundingBox, RigidBody},
+    },
+    overlap, Broadphase, Overlaps,
+};
+use crate::{math::vector::Vector3d, utility::int_hash::IntSet};
+
+#[derive(Clone)]
+pub struct AabbTree {
+    collision_epsilon: f64,
+    margin: f64,
+    nodes: Vec<Node>,
+    free: Vec<usize>,
+    root: Option<usize>,
+    leaves: Vec<usize>,
+    overlaps: Overlaps,
+}
+
+impl AabbTree {
+    pub fn new(collision_epsilon: f64, margin: f64) -> Self {
+        Self {
+            collision_epsilon,
+            margin,
+            nodes: Vec::new(),
+            free: Vec::new(),
+            root: None,
+            leaves: Vec::new(),
+            overlaps: Overlaps::default(),
+        }
+    }
+
+    fn allocate(&mut self, node: Node) -> usize {
+        if let Some(index) = self.free.pop() {
+            self.nodes[index] = node;
+            index
+        } else {
+            self.nodes.push(node);
+            self.nodes.len() - 1
+        }
+    }
+
+    fn fat_bounding_box(&self, bounding_box: &BoundingBox) -> BoundingBox {
+        let margin = Vector3d::new(self.margin, self.margin, self.margin);
+        [bounding_box[0].sub(&margin), bounding_box[1].add(&margin)]
+    }
+
+    fn insert(&mut self, rigid_body_index: usize, bounding_box: BoundingBox) {
+        let leaf = self.allocate(Node {
+            bounding_box,
+            parent: None,
+            children: None,
+            rigid_body_index,
+        });
+        self.leaves[rigid_body_index] = leaf;
+        let root = match self.root {
+            Some(root) => root,
+            None => {
+                self.root = Some(leaf);
+                return;
+            }
+        };
+
+        let mut sibling = root;
+        while let Some([child_1, child_2]) = self.nodes[sibling].children {
+            let combined_area =
+                area(&union(&self.nodes[sibling].bounding_box, &bounding_box));
+            let inheritance_cost =
+                combined_area - area(&self.nodes[sibling].bounding_box);
+            let cost = |child: usize| {
+                let child_bounding_box = &self.nodes[child].bounding_box;
+                let child_union_area =
+                    area(&union(child_bounding_box, &bounding_box));
+                if self.nodes[child].children.is_none() {
+                    child_union_area + inheritance_cost
+                } else {
+                    child_union_area - area(child_bounding_box)
+                        + inheritance_cost
+                }
+            };
+            let cost_1 = cost(child_1);
+            let cost_2 = cost(child_2);
+            if combined_area < cost_1 && combined_area < cost_2 {
+                break;
+            }
+            sibling = if cost_1 < cost_2 { child_1 } else { child_2 };
+        }
+
+        let old_parent = self.nodes[sibling].parent;
+        let new_parent = self.allocate(Node {
+            bounding_box: union(
+                &self.nodes[sibling].bounding_box,
+                &bounding_box,
+            ),
+            parent: old_parent,
+            children: Some([sibling, leaf]),
+            rigid_body_index: 0,
+        });
+        self.nodes[sibling].parent = Some(new_parent);
+        self.nodes[leaf].parent = Some(new_parent);
+        match old_parent {
+            Some(old_parent) => {
+                self.replace_child(old_parent, sibling, new_parent);
+                self.refit(old_parent);
+            }
+            None => self.root = Some(new_parent),
+        }
+    }
+
+    fn query(
+        &self,
+        rigid_body_index: usize,
+        rigid_bodies: &[RigidBody],
+    ) -> IntSet<usize> {
+        let mut ret = IntSet::default();
+        let root = match self.root {
+            Some(root) => root,
+            None => return ret,
+        };
+        let rigid_body = &rigid_bodies[rigid_body_index];
+        let fat_bounding_box =
+            &self.nodes[self.leaves[rigid_body_index]].bounding_box;
+        let mut stack = vec![root];
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if !overlap(
+                &node.bounding_box,
+                fat_bounding_box,
+                self.collision_epsilon,
+            ) {
+                continue;
+            }
+            match node.children {
+                Some([child_1, child_2]) => {
+                    stack.push(child_1);
+                    stack.push(child_2);
+                }
+                None => {
+                    let other_index = node.rigid_body_index;
+                    if other_index != rigid_body_index
+                        && !(rigid_body.is_immovable()
+                            && rigid_bodies[other_index].is_immovable())
+                    {
+                        ret.insert(other_index);
+                    }
+                }
+            }
+        }
+        ret
+    }
+
+    fn refit(&mut self, mut index: usize) {
+        loop {
+            if let Some([child_1, child_2]) = self.nodes[index].children {
+                self.nodes[index].bounding_box = union(
+                    &self.nodes[child_1].bounding_box,
+                    &self.nodes[child_2].bounding_box,
+                );
+            }
+            match self.nodes[index].parent {
+                Some(parent) => index = parent,
+                None => return,
+            }
+        }
+    }
+
+    fn reinsert(
+        &mut self,
+        rigid_body_index: usize,
+        rigid_body: &RigidBody,
+    ) -> bool {
+        let leaf = self.leaves[rigid_body_index];
+        if contains(&self.nodes[leaf].bounding_box, rigid_body.bounding_box()) {
+            return false;
+        }
+        self.remove(leaf);
+        self.insert(
+            rigid_body_index,
+            self.fat_bounding_box(rigid_body.bounding_box()),
+        );
+        true
+    }
+
+    fn remove(&mut self, leaf: usize) {
+        self.free.push(leaf);
+        let parent = match self.nodes[leaf].parent {
+            Some(parent) => parent,
+            None => {
+                self.root = None;
+                return;
+            }
+        };
+        let sibling = match self.nodes[parent].children {
+            Some([child_1, child_2]) => {
+                if child_1 == leaf {
+                    child_2
+                } else {
+                    child_1
+                }
+            }
+            None => unreachable!(),
+        };
+        self.free.push(parent);
+        let grandparent = self.nodes[parent].parent;
+        self.nodes[sibling].parent = grandparent;
+        match grandparent {
+            Some(grandparent) => {
+                self.replace_child(grandparent, parent, sibling);
+                self.refit(grandparent);
+            }
+            None => self.root = Some(sibling),
+        }
+    }
+
+    fn replace_child(&mut self, parent: usize, old: usize, new: usize) {
+        if let Some(children) = &mut self.nodes[parent].children {
+            for child in children.iter_mut() {
+                if *child == old {
+                    *child = new;
+                }
+            }
+        }
+    }
+
+    fn update_overlaps(
+        &mut self,
+        indices: &[usize],
+        rigid_bodies: &[RigidBody],
+        collision_table: &mut CollisionTable,
+    ) {
+        for i in indices {
+            let neighbors = self.query(*i, rigid_bodies);
+            self.overlaps.set(*i, neighbors, collision_table);
+        }
+    }
+}
+
+impl Broadphase for AabbTree {
+    fn box_clone(&self) -> Box<dyn Broadphase> {
+        Box::new(self.clone())
+    }
+
+    fn candidate_pairs(
+        &self,
+        _collision_table: &CollisionTable,
+    ) -> Vec<(usize, usize)> {
+        self.overlaps.candidate_pairs()
+    }
+
+    fn generate(
+        &mut self,
+        rigid_bodies: &[RigidBody],
+        collision_table: &mut CollisionTable,
+    ) {
+        self.nodes.clear();
+        self.free.clear();
+        self.root = None;
+        self.leaves = vec![0; rigid_bodies.len()];
+        self.overlaps.generate(rigid_bodies.len());
+        for (i, rigid_body) in rigid_bodies.iter().enumerate() {
+            self.insert(i, self.fat_bounding_box(rigid_body.bounding_box()));
+        }
+        let indices = (0..rigid_bodies.len()).collect::<Vec<_>>();
+        self.update_overlaps(&indices, rigid_bodies, collision_table);
+        self.overlaps.refresh(
+            &indices,
+            rigid_bodies,
+            self.collision_epsilon,
+            collision_table,
+        );
+    }
+
+    fn neighbors(
+        &self,
+        rigid_body_index: usize,
+        _collision_table: &CollisionTable,
+    ) -> Vec<usize> {
+        self.overlaps.neighbors(rigid_body_index)
+    }
+
+    fn query_bounding_box(
+        &self,
+        bounding_box: &BoundingBox,
+        rigid_bodies: &[RigidBody],
+    ) -> Vec<usize> {
+        let mut ret = Vec::new();
+        let root = match self.root {
+            Some(root) if self.leaves.len() == rigid_bodies.len() => root,
+            _ => return ret,
+        };
+        let mut stack = vec![root];
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if !overlap(&node.bounding_box, bounding_box, 0.) {
+                continue;
+            }
+            match node.children {
+                Some([child_1, child_2]) => {
+                    stack.push(child_1);
+                    stack.push(child_2);
+                }
+                None => {
+                    let rigid_body_index = node.rigid_body_index;
+                    if overlap(
+                        rigid_bodies[rigid_body_index].bounding_box(),
+                        bounding_box,
+                        0.,
+                    ) {
+                        ret.push(rigid_body_index);
+                    }
+                }
+            }
+        }
+        ret.sort_unstable();
+        ret
+    }
+
+    fn update(
+        &mut self,
+        rigid_bodies: &[RigidBody],
+        collision_table: &mut CollisionTable,
+    ) {
+        let indices = (0..rigid_bodies.len()).collect::<Vec<_>>();
+        self.update_bodies(&indices, rigid_bodies, collision_table);
+    }
+
+    fn update_bodies(
+        &mut self,
+        indices: &[usize],
+        rigid_bodies: &[RigidBody],
+        collision_table: &mut CollisionTable,
+    ) {
+        let reinserted = indices
+            .iter()
+            .copied()
+            .filter(|i| self.reinsert(*i, &rigid_bodies[*i]))
+            .collect::<Vec<_>>();
+        self.update_overlaps(&reinserted, rigid_bodies, collision_table);
+        self.overlaps.refresh(
+            indices,
+            rigid_bodies,
+            self.collision_epsilon,
+            collision_table,
+        );
+    }
+}
+
+#[derive(Clone)]
+struct Node {
+    bounding_box: BoundingBox,
+    parent: Option<usize>,
+    children: Option<[usize; 2]>,
+    rigid_body_index: usize,
+}
+
+fn area(bounding_box: &BoundingBox) -> f64 {
+    let dimensions = bounding_box[1].sub(&bounding_box[0]);
+    2. * (dimensions[0] * dimensions[1]
+        + dimensions[1] * dimensions[2]
+        + dimensions[2] * dimensions[0])
+}
+
+fn contains(outer: &BoundingBox, inner: &BoundingBox) -> bool {
+    for axis in 0..3 {
+        if inner[0][axis] < outer[0][axis] || inner[1][axis] > outer[1][axis] {
+            return false;
+        }
+    }
+    true
+}
+
+fn union(
+    bounding_box_1: &BoundingBox,
+    bounding_box_2: &BoundingBox,
+) -> BoundingBox {
+    let mut ret = *bounding_box_1;
+    for axis in 0..3 {
+        ret[0][axis] = ret[0][axis].min(bounding_box_2[0][axis]);
+        ret[1][axis] = ret[1][axis].max(bounding_box_2[1][axis]);
+    }
+    ret
+}
@@ -0,0 +1,193 @@
+mod aabb_tree;
+mod spatial_hash_grid;
+mod sweep_and_prune;
+
+use super::{
+    collision_table::CollisionTable,
+    rigid_body::{BoundingBox, RigidBody},
+};
+use crate::utility::int_hash::IntSet;
+use aabb_tree::AabbTree;
+use spatial_hash_grid::SpatialHashGrid;
+use std::mem;
+use sweep_and_prune::SweepAndPrune;
+
+pub trait Broadphase {
+    fn box_clone(&self) -> Box<dyn Broadphase>;
+
+    fn candidate_pairs(
+        &self,
+        collision_table: &CollisionTable,
+    ) -> Vec<(usize, usize)> {
+        let mut ret = Vec::new();
+        for j in 0..collision_table.len() {
+            for i in j + 1..collision_table.len() {
+                if collision_table.get(i, j).bounding_box_collision() {
+                    ret.push((i, j));
+                }
+            }
+        }
+        ret
+    }
+
+    fn generate(
+        &mut self,
+        rigid_bodies: &[RigidBody],
+        collision_table: &mut CollisionTable,
+    );
+
+    fn neighbors(
+        &self,
+        rigid_body_index: usize,
+        collision_table: &CollisionTable,
+    ) -> Vec<usize> {
+        (0..collision_table.len())
+            .filter(|j| {
+                *j != rigid_body_index
+                    && collision_table
+                        .get(rigid_body_index, *j)
+                        .bounding_box_collision()
+            })
+            .collect()
+    }
+
+    fn query_bounding_box(
+        &self,
+        bounding_box: &BoundingBox,
+        rigid_bodies: &[RigidBody],
+    ) -> Vec<usize> {
+        (0..rigid_bodies.len())
+            .filter(|i| {
+                overlap(rigid_bodies[*i].bounding_box(), bounding_box, 0.)
+            })
+            .collect()
+    }
+
+    fn update(
+        &mut self,
+        rigid_bodies: &[RigidBody],
+        collision_table: &mut CollisionTable,
+    );
+
+    fn update_bodies(
+        &mut self,
+        _indices: &[usize],
+        rigid_bodies: &[RigidBody],
+        collision_table: &mut CollisionTable,
+    ) {
+        self.update(rigid_bodies, collision_table);
+    }
+}
+
+impl Clone for Box<dyn Broadphase> {
+    fn clone(&self) -> Self {
+        self.box_clone()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub enum BroadphaseKind {
+    #[default]
+    SweepAndPrune,
+    AabbTree {
+        margin: f64,
+    },
+    SpatialHashGrid {
+        cell_size: f64,
+    },
+}
+
+impl BroadphaseKind {
+    pub fn create(&self, collision_epsilon: f64) -> Box<dyn Broadphase> {
+        match *self {
+            Self::SweepAndPrune => {
+                Box::new(SweepAndPrune::new(collision_epsilon))
+            }
+            Self::AabbTree { margin } => {
+                Box::new(AabbTree::new(collision_epsilon, margin))
+            }
+            Self::SpatialHashGrid { cell_size } => {
+                Box::new(SpatialHashGrid::new(collision_epsilon, cell_size))
+            }
+        }
+    }
+}
+
+fn overlap(
+    bounding_box_1: &BoundingBox,
+    bounding_box_2: &BoundingBox,
+    collision_epsilon: f64,
+) -> bool {
+    for axis in 0..3 {
+        if bounding_box_1[1][axis] + collision_epsilon
+            < bounding_box_2[0][axis] - collision_epsilon
+            || bounding_box_1[0][axis] - collision_epsilon
+                > bounding_box_2[1][axis] + collision_epsilon
+        {
+            return false;
+        }
+    }
+    true
+}
+
+#[derive(Clone, Default)]
+struct Overlaps {
+    neighbors: Vec<IntSet<usize>>,
+}
+
+impl Overlaps {
+    fn candidate_pairs(&self) -> Vec<(usize, usize)> {
+        let mut ret = Vec::new();
+        for (j, neighbors) in self.neighbors.iter().enumerate() {
+            let start = ret.len();
+            ret.extend(neighbors.iter().filter(|i| **i > j).map(|i| (*i, j)));
+            ret[start..].sort_unstable();
+        }
+        ret
+    }
+
+    fn generate(&mut self, len: usize) {
+        self.neighbors = vec![IntSet::default(); len];
+    }
+
+    fn neighbors(&self, rigid_body_index: usize) -> Vec<usize> {
+        self.neighbors[rigid_body_index].iter().copied().collect()
+    }
+
+    fn refresh(
+        &self,
+        indices: &[usize],
+        rigid_bodies: &[RigidBody],
+        collision_epsilon: f64,
+        collision_table: &mut CollisionTable,
+    ) {
+        for i in indices {
+            for j in &self.neighbors[*i] {
+                let colliding = overlap(
+                    rigid_bodies[*i].bounding_box(),
+                    rigid_bodies[*j].bounding_box(),
+                    collision_epsilon,
+                );
+                collision_table.get_mut(*i, *j).bounding_box = [colliding; 3];
+            }
+        }
+    }
+
+    fn set(
+        &mut self,
+        i: usize,
+        neighbors: IntSet<usize>,
+        collision_table: &mut CollisionTable,
+    ) {
+        for j in mem::take(&mut self.neighbors[i]) {
+            if !neighbors.contains(&j) {
+                collision_table.get_mut(i, j).bounding_box = [false; 3];
+                self.neighbors[j].remove(&i);
+            }
+        }
+        for j in &neighbors {
+            self.neighbors[*j].insert(i);
+        }
+        self.neighbors[i] = neighbors;
+    }
+}
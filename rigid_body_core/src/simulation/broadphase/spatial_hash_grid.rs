@@ -0,0 +1,214 @@
+use super::{
+    super::{collision_table::CollisionTable, rigid_body::RigidBody},
+    Broadphase, Overlaps,
+};
+use crate::utility::int_hash::{IntMap, IntSet};
+
+const MAX_CELLS_PER_BODY: i64 = 512;
+
+type Cell = (i64, i64, i64);
+
+#[derive(Clone)]
+pub struct SpatialHashGrid {
+    collision_epsilon: f64,
+    cell_size: f64,
+    cells: IntMap<Cell, Vec<usize>>,
+    ranges: Vec<Option<(Cell, Cell)>>,
+    oversized: IntSet<usize>,
+    overlaps: Overlaps,
+}
+
+impl SpatialHashGrid {
+    pub fn new(collision_epsilon: f64, cell_size: f64) -> Self {
+        Self {
+            collision_epsilon,
+            cell_size,
+            cells: IntMap::default(),
+            ranges: Vec::new(),
+            oversized: IntSet::default(),
+            overlaps: Overlaps::default(),
+        }
+    }
+
+    fn cell_range(&self, rigid_body: &RigidBody) -> (Cell, Cell) {
+        let bounding_box = rigid_body.bounding_box();
+        let get_cell = |bound: usize, sign: f64| {
+            let get_index = |axis: usize| {
+                ((bounding_box[bound][axis] + sign * self.collision_epsilon)
+                    / self.cell_size)
+                    .floor() as i64
+            };
+            (get_index(0), get_index(1), get_index(2))
+        };
+        (get_cell(0, -1.), get_cell(1, 1.))
+    }
+
+    fn insert(&mut self, rigid_body_index: usize, rigid_body: &RigidBody) {
+        let (min, max) = self.cell_range(rigid_body);
+        let count_opt = [(min.0, max.0), (min.1, max.1), (min.2, max.2)]
+            .iter()
+            .try_fold(1i64, |count, (min, max)| {
+                max.checked_sub(*min)?.checked_add(1)?.checked_mul(count)
+            });
+        if count_opt.is_none_or(|count| count > MAX_CELLS_PER_BODY) {
+            self.oversized.insert(rigid_body_index);
+            self.ranges[rigid_body_index] = None;
+            return;
+        }
+        for x in min.0..=max.0 {
+            for y in min.1..=max.1 {
+                for z in min.2..=max.2 {
+                    self.cells
+                        .entry((x, y, z))
+                        .or_default()
+                        .push(rigid_body_index);
+                }
+            }
+        }
+        self.ranges[rigid_body_index] = Some((min, max));
+    }
+
+    fn query(
+        &self,
+        rigid_body_index: usize,
+        rigid_bodies: &[RigidBody],
+    ) -> IntSet<usize> {
+        let mut candidates = self.oversized.clone();
+        match self.ranges[rigid_body_index] {
+            Some((min, max)) => {
+                for x in min.0..=max.0 {
+                    for y in min.1..=max.1 {
+                        for z in min.2..=max.2 {
+                            if let Some(cell) = self.cells.get(&(x, y, z)) {
+                                candidates.extend(cell);
+                            }
+                        }
+                    }
+                }
+            }
+            None => candidates.extend(0..rigid_bodies.len()),
+        }
+        let rigid_body = &rigid_bodies[rigid_body_index];
+        candidates
+            .into_iter()
+            .filter(|j| {
+                *j != rigid_body_index
+                    && !(rigid_body.is_immovable()
+                        && rigid_bodies[*j].is_immovable())
+            })
+            .collect()
+    }
+
+    fn remove(&mut self, rigid_body_index: usize) {
+        let (min, max) = match self.ranges[rigid_body_index].take() {
+            Some(range) => range,
+            None => {
+                self.oversized.remove(&rigid_body_index);
+                return;
+            }
+        };
+        for x in min.0..=max.0 {
+            for y in min.1..=max.1 {
+                for z in min.2..=max.2 {
+                    let cell = (x, y, z);
+                    if let Some(indices) = self.cells.get_mut(&cell) {
+                        indices.retain(|i| *i != rigid_body_index);
+                        if indices.is_empty() {
+                            self.cells.remove(&cell);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn update_overlaps(
+        &mut self,
+        indices: &[usize],
+        rigid_bodies: &[RigidBody],
+        collision_table: &mut CollisionTable,
+    ) {
+        for i in indices {
+            let neighbors = self.query(*i, rigid_bodies);
+            self.overlaps.set(*i, neighbors, collision_table);
+        }
+    }
+}
+
+impl Broadphase for SpatialHashGrid {
+    fn box_clone(&self) -> Box<dyn Broadphase> {
+        Box::new(self.clone())
+    }
+
+    fn candidate_pairs(
+        &self,
+        _collision_table: &CollisionTable,
+    ) -> Vec<(usize, usize)> {
+        self.overlaps.candidate_pairs()
+    }
+
+    fn generate(
+        &mut self,
+        rigid_bodies: &[RigidBody],
+        collision_table: &mut CollisionTable,
+    ) {
+        self.cells.clear();
+        self.oversized.clear();
+        self.ranges = vec![None; rigid_bodies.len()];
+        self.overlaps.generate(rigid_bodies.len());
+        for (i, rigid_body) in rigid_bodies.iter().enumerate() {
+            self.insert(i, rigid_body);
+        }
+        let indices = (0..rigid_bodies.len()).collect::<Vec<_>>();
+        self.update_overlaps(&indices, rigid_bodies, collision_table);
+        self.overlaps.refresh(
+            &indices,
+            rigid_bodies,
+            self.collision_epsilon,
+            collision_table,
+        );
+    }
+
+    fn neighbors(
+        &self,
+        rigid_body_index: usize,
+        _collision_table: &CollisionTable,
+    ) -> Vec<usize> {
+        self.overlaps.neighbors(rigid_body_index)
+    }
+
+    fn update(
+        &mut self,
+        rigid_bodies: &[RigidBody],
+        collision_table: &mut CollisionTable,
+    ) {
+        let indices = (0..rigid_bodies.len()).collect::<Vec<_>>();
+        self.update_bodies(&indices, rigid_bodies, collision_table);
+    }
+
+    fn update_bodies(
+        &mut self,
+        indices: &[usize],
+        rigid_bodies: &[RigidBody],
+        collision_table: &mut CollisionTable,
+    ) {
+        let moved = indices
+            .iter()
+            .copied()
+            .filter(|i| {
+                self.ranges[*i] != Some(self.cell_range(&rigid_bodies[*i]))
+            })
+            .collect::<Vec<_>>();
+        for i in &moved {
+            self.remove(*i);
+            self.insert(*i, &rigid_bodies[*i]);
+        }
+        self.update_overlaps(&moved, rigid_bodies, collision_table);
+        self.overlaps.refresh(
+            indices,
+            rigid_bodies,
+            self.collision_epsilon,
+            collision_table,
+        );
+    }
+}
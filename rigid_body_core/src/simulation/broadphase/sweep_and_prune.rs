@@ -1,15 +1,19 @@
 use super::{
-    collision_table::CollisionTable,
-    rigid_body::{BoundingBox, RigidBody},
+    super::{
+        collision_table::CollisionTable,
+        rigid_body::{BoundingBox, RigidBody},
+    },
+    Broadphase,
 };
 use crate::utility::int_hash::IntSet;
 use std::cmp::Ordering;
 
-pub struct BoundingBoxCollisionManager {
+#[derive(Clone)]
+pub struct SweepAndPrune {
     axes: [BoundingBoxAxisIntervals; 3],
 }
 
-impl BoundingBoxCollisionManager {
+impl SweepAndPrune {
     pub fn new(collision_epsilon: f64) -> Self {
         Self {
             axes: [
@@ -19,8 +23,14 @@ impl BoundingBoxCollisionManager {
             ],
         }
     }
+}
+
+impl Broadphase for SweepAndPrune {
+    fn box_clone(&self) -> Box<dyn Broadphase> {
+        Box::new(self.clone())
+    }
 
-    pub fn generate(
+    fn generate(
         &mut self,
         rigid_bodies: &[RigidBody],
         collision_table: &mut CollisionTable,
@@ -30,7 +40,7 @@ impl BoundingBoxCollisionManager {
         }
     }
 
-    pub fn update(
+    fn update(
         &mut self,
         rigid_bodies: &[RigidBody],
         collision_table: &mut CollisionTable,
@@ -41,6 +51,7 @@ impl BoundingBoxCollisionManager {
     }
 }
 
+#[derive(Clone)]
 struct BoundingBoxAxisIntervals {
     axis: usize,
     sorted: Vec<(usize, usize)>,
@@ -101,7 +112,7 @@ impl BoundingBoxAxisIntervals {
                     _ => unreachable!(),
                 }
         };
-        let mut order = get_bound(a).partial_cmp(&get_bound(b)).unwrap();
+        let mut order = get_bound(a).total_cmp(&get_bound(b));
         if let Ordering::Equal = order {
             if a.1 == 0 && b.1 == 1 {
                 order = Ordering::Greater;
@@ -1,36 +1,84 @@
 use super::{
-    bounding_box_collision_manager::BoundingBoxCollisionManager,
+    bounding_box::PeriodicDomain,
+    broadphase::{Broadphase, BroadphaseKind},
     collision_table::{
         CollisionTable, Contact, Contacts, EdgeEdgeIndices, EdgeIndices,
-        FaceIndices, VertexFaceIndices,
+        FaceIndices, PointIndices, VertexFaceIndices,
     },
-    rigid_body::RigidBody,
+    event_log::{EventLog, FailureKind, Severity},
+    half_space::{HalfSpace, HalfSpaceContact},
+    island::Islands,
+    profiler::{self, ProfileCounter, ProfileStage, Profiler},
+    rigid_body::{BoundingBox, RigidBody},
+    simulation_config::SimulationConfig,
+};
+use crate::math::{
+    geometry::{self, FiniteLine3d},
+    gjk, matrix_vector,
+    polyhedron::Polyhedron,
+    vector::Vector3d,
+};
+use std::{
+    collections::BTreeSet,
+    f64::{EPSILON, MAX},
+    mem,
 };
-use crate::math::{geometry, matrix_vector, vector::Vector3d};
-use std::f64::{EPSILON, MAX};
 
 pub use super::collision_table::SeparatingPlane;
 
-const COLLISION_EPSILON: f64 = 1e-3;
-const COR: f64 = 1.;
-
+#[derive(Clone)]
 pub struct CollisionManager {
-    pub debug: bool,
-    bounding_box_collision_manager: BoundingBoxCollisionManager,
+    pub(super) profiler_opt: Option<Profiler>,
+    broadphase: Box<dyn Broadphase>,
+    broadphase_kind: BroadphaseKind,
+    config: SimulationConfig,
     collision_table: CollisionTable,
+    periodic_table: CollisionTable,
+    event_log: EventLog,
+    half_space_contacts: Vec<HalfSpaceContact>,
 }
 
 impl CollisionManager {
     pub fn new() -> Self {
+        Self::with_config(SimulationConfig::default())
+    }
+
+    pub fn with_config(config: SimulationConfig) -> Self {
+        let broadphase_kind = BroadphaseKind::default();
         Self {
-            debug: false,
-            bounding_box_collision_manager: BoundingBoxCollisionManager::new(
-                COLLISION_EPSILON,
-            ),
+            profiler_opt: None,
+            broadphase: broadphase_kind.create(config.collision_epsilon),
+            broadphase_kind,
+            config,
             collision_table: CollisionTable::new(),
+            periodic_table: CollisionTable::new(),
+            event_log: EventLog::default(),
+            half_space_contacts: Vec::new(),
         }
     }
 
+    pub fn config(&self) -> &SimulationConfig {
+        &self.config
+    }
+
+    pub fn event_log(&self) -> &EventLog {
+        &self.event_log
+    }
+
+    pub fn event_log_mut(&mut self) -> &mut EventLog {
+        &mut self.event_log
+    }
+
+    pub fn set_broadphase(&mut self, broadphase_kind: BroadphaseKind) {
+        self.broadphase_kind = broadphase_kind;
+        self.broadphase = broadphase_kind.create(self.config.collision_epsilon);
+    }
+
+    pub fn set_config(&mut self, config: SimulationConfig) {
+        self.config = config;
+        self.set_broadphase(self.broadphase_kind);
+    }
+
     #[allow(dead_code)]
     pub fn is_bounding_box_colliding(&self, i: usize) -> bool {
         for j in 0..self.collision_table.len() {
@@ -51,30 +99,238 @@ impl CollisionManager {
         false
     }
 
-    #[allow(dead_code)]
     pub fn collision_table(&self) -> &CollisionTable {
         &self.collision_table
     }
 
     pub fn collide_simple(&mut self, rigid_bodies: &mut [RigidBody]) {
-        self.bounding_box_collision_manager
-            .update(rigid_bodies, &mut self.collision_table);
+        self.update_broadphase(rigid_bodies);
         self.collision_table.reset_colliding();
-        for j in 0..rigid_bodies.len() {
-            for i in j + 1..rigid_bodies.len() {
-                if rigid_bodies[i].is_immovable()
-                    && rigid_bodies[j].is_immovable()
+        profiler::begin(&mut self.profiler_opt, ProfileStage::Narrowphase);
+        #[cfg(feature = "parallel")]
+        self.collide_pairs_parallel(rigid_bodies);
+        #[cfg(not(feature = "parallel"))]
+        self.collide_pairs(rigid_bodies);
+        profiler::end(&mut self.profiler_opt);
+    }
+
+    pub fn collide_half_spaces(
+        &mut self,
+        half_spaces: &[HalfSpace],
+        rigid_bodies: &mut [RigidBody],
+    ) {
+        let collision_epsilon = self.config.collision_epsilon;
+        let coefficient_of_restitution = self.config.coefficient_of_restitution;
+        self.half_space_contacts.clear();
+        for (half_space_index, half_space) in half_spaces.iter().enumerate() {
+            for rigid_body_index in 0..rigid_bodies.len() {
+                let rigid_body = &mut rigid_bodies[rigid_body_index];
+                if !rigid_body.is_active()
+                    || half_space.bounding_box_dist(rigid_body.bounding_box())
+                        > collision_epsilon
                 {
                     continue;
                 }
-                if !self.collision_table.get(i, j).bounding_box_collision() {
+                let normal = half_space.normal();
+                let mut support_positions = match (
+                    rigid_body.rounded_core(),
+                    rigid_body.cylinder_core(),
+                ) {
+                    (Some((core, radius)), _) => {
+                        let offset = normal.scale(radius);
+                        let mut ret = vec![core.start.sub(&offset)];
+                        if core.start.dist_sq(&core.end) > 0. {
+                            ret.push(core.end.sub(&offset));
+                        }
+                        ret
+                    }
+                    (None, Some((core, radius))) => {
+                        Self::cylinder_rim(&core, radius, &normal.scale(-1.))
+                    }
+                    (None, None) => {
+                        rigid_body.polyhedron_world().vertices().to_vec()
+                    }
+                };
+                let penetration = support_positions
+                    .iter()
+                    .map(|position| half_space.signed_dist(position))
+                    .fold(f64::MAX, f64::min);
+                if penetration < 0. {
+                    let offset = normal.scale(-penetration);
+                    rigid_body.position.add_assign(&offset);
+                    rigid_body.update_geometry();
+                    for position in &mut support_positions {
+                        position.add_assign(&offset);
+                    }
+                }
+                let start = self.half_space_contacts.len();
+                for position in support_positions {
+                    if half_space.signed_dist(&position) <= collision_epsilon {
+                        self.half_space_contacts.push(HalfSpaceContact {
+                            rigid_body: rigid_body_index,
+                            position,
+                            half_space: half_space_index,
+                        });
+                    }
+                }
+                for contact in &self.half_space_contacts[start..] {
+                    Self::half_space_contact_force(
+                        contact,
+                        half_spaces,
+                        coefficient_of_restitution,
+                        rigid_bodies,
+                    );
+                }
+            }
+        }
+    }
+
+    pub fn collide_periodic(
+        &mut self,
+        periodic_domain: &PeriodicDomain,
+        rigid_bodies: &mut [RigidBody],
+    ) {
+        let mut reach = Vector3d::default();
+        for rigid_body in rigid_bodies.iter() {
+            let bounding_box = rigid_body.bounding_box();
+            for axis in 0..3 {
+                reach[axis] = reach[axis]
+                    .max(bounding_box[1][axis] - bounding_box[0][axis]);
+            }
+        }
+        let collision_epsilon = self.config.collision_epsilon;
+        let [min, max] = &periodic_domain.dimensions;
+        let mut moved = Vec::new();
+        let boundary = (0..rigid_bodies.len())
+            .filter(|i| {
+                let bounding_box = rigid_bodies[*i].bounding_box();
+                (0..3).any(|axis| {
+                    periodic_domain.periodic[axis]
+                        && (bounding_box[0][axis]
+                            < min[axis] + reach[axis] + collision_epsilon
+                            || bounding_box[1][axis]
+                                > max[axis] - reach[axis] - collision_epsilon)
+                })
+            })
+            .collect::<Vec<_>>();
+        for (n, j) in boundary.iter().enumerate() {
+            for i in &boundary[n + 1..] {
+                let (i, j) = (*i, *j);
+                if !rigid_bodies[i].is_active() && !rigid_bodies[j].is_active()
+                    || rigid_bodies[i].is_immovable()
+                        && rigid_bodies[j].is_immovable()
+                {
                     continue;
                 }
-                if !self.check_for_separating_plane(i, j, rigid_bodies) {
-                    self.collision_table.get_mut(i, j).colliding = true;
-                    self.handle_collision_simple(i, j, rigid_bodies);
-                    self.bounding_box_collision_manager
-                        .update(rigid_bodies, &mut self.collision_table);
+                let shift = periodic_domain.image_shift(
+                    &rigid_bodies[j].position,
+                    &rigid_bodies[i].position,
+                );
+                let bounding_box_1 = rigid_bodies[j].bounding_box();
+                let bounding_box_2 = rigid_bodies[i].bounding_box();
+                if shift.is_zero()
+                    || (0..3).any(|axis| {
+                        bounding_box_2[0][axis] + shift[axis]
+                            > bounding_box_1[1][axis] + collision_epsilon
+                            || bounding_box_1[0][axis]
+                                > bounding_box_2[1][axis]
+                                    + shift[axis]
+                                    + collision_epsilon
+                    })
+                {
+                    continue;
+                }
+                Self::translate(&mut rigid_bodies[i], &shift);
+                mem::swap(&mut self.collision_table, &mut self.periodic_table);
+                if self.collide_pair(i, j, rigid_bodies) {
+                    moved.extend(&[i, j]);
+                }
+                mem::swap(&mut self.collision_table, &mut self.periodic_table);
+                Self::translate(&mut rigid_bodies[i], &shift.scale(-1.));
+            }
+        }
+        moved.sort_unstable();
+        moved.dedup();
+        self.update_broadphase_bodies(&moved, rigid_bodies);
+    }
+
+    pub fn update_broadphase(&mut self, rigid_bodies: &[RigidBody]) {
+        profiler::begin(&mut self.profiler_opt, ProfileStage::Broadphase);
+        self.broadphase
+            .update(rigid_bodies, &mut self.collision_table);
+        profiler::end(&mut self.profiler_opt);
+    }
+
+    fn update_broadphase_bodies(
+        &mut self,
+        indices: &[usize],
+        rigid_bodies: &[RigidBody],
+    ) {
+        if indices.is_empty() {
+            return;
+        }
+        profiler::begin(&mut self.profiler_opt, ProfileStage::Broadphase);
+        self.broadphase.update_bodies(
+            indices,
+            rigid_bodies,
+            &mut self.collision_table,
+        );
+        profiler::end(&mut self.profiler_opt);
+    }
+
+    pub fn query_bounding_box(
+        &self,
+        bounding_box: &BoundingBox,
+        rigid_bodies: &[RigidBody],
+    ) -> Vec<usize> {
+        self.broadphase
+            .query_bounding_box(bounding_box, rigid_bodies)
+    }
+
+    pub fn half_space_contacts(&self) -> &[HalfSpaceContact] {
+        &self.half_space_contacts
+    }
+
+    pub fn solve_islands(
+        &self,
+        islands: &Islands,
+        iterations: usize,
+        half_spaces: &[HalfSpace],
+        rigid_bodies: &mut [RigidBody],
+    ) {
+        if iterations <= 1 {
+            return;
+        }
+        let mut island_half_space_contacts =
+            vec![Vec::new(); islands.islands().len()];
+        for contact in &self.half_space_contacts {
+            if let Some(island_index) = islands.island_index(contact.rigid_body)
+            {
+                island_half_space_contacts[island_index].push(*contact);
+            }
+        }
+        for (island, half_space_contacts) in
+            islands.islands().iter().zip(&island_half_space_contacts)
+        {
+            for _ in 1..iterations {
+                let mut applied = false;
+                for (i, j) in island.pairs() {
+                    applied |= Self::contact_forces_simple(
+                        &self.collision_table.get(*i, *j).contacts,
+                        self.config.coefficient_of_restitution,
+                        rigid_bodies,
+                    );
+                }
+                for contact in half_space_contacts {
+                    applied |= Self::half_space_contact_force(
+                        contact,
+                        half_spaces,
+                        self.config.coefficient_of_restitution,
+                        rigid_bodies,
+                    );
+                }
+                if !applied {
+                    break;
                 }
             }
         }
@@ -82,12 +338,14 @@ impl CollisionManager {
 
     pub fn generate(&mut self, rigid_bodies: &[RigidBody]) {
         self.collision_table.generate(rigid_bodies.len());
-        self.bounding_box_collision_manager
+        self.periodic_table.generate(rigid_bodies.len());
+        self.broadphase
             .generate(rigid_bodies, &mut self.collision_table);
         for j in 0..rigid_bodies.len() {
             for i in j + 1..rigid_bodies.len() {
                 if rigid_bodies[i].is_immovable()
                     && rigid_bodies[j].is_immovable()
+                    || Self::is_point_pair(i, j, rigid_bodies)
                 {
                     continue;
                 }
@@ -102,49 +360,708 @@ impl CollisionManager {
         rigid_body_2_index: usize,
         rigid_bodies: &[RigidBody],
     ) -> bool {
-        let separating_plane = &mut self
-            .collision_table
-            .get_mut(rigid_body_1_index, rigid_body_2_index)
-            .separating_plane;
-        match &separating_plane {
-            SeparatingPlane::Face { face_indices } => {
-                if Self::face_is_separating_plane(face_indices, rigid_bodies) {
-                    return true;
+        Self::find_separating_plane(
+            &mut self
+                .collision_table
+                .get_mut(rigid_body_1_index, rigid_body_2_index)
+                .separating_plane,
+            rigid_body_1_index,
+            rigid_body_2_index,
+            rigid_bodies,
+        )
+    }
+
+    pub(super) fn collide_pairs(
+        &mut self,
+        rigid_bodies: &mut [RigidBody],
+    ) -> Vec<usize> {
+        let pending = self
+            .broadphase
+            .candidate_pairs(&self.collision_table)
+            .into_iter()
+            .map(|(i, j)| (j, i))
+            .collect::<BTreeSet<_>>();
+        self.collide_pending(pending, rigid_bodies)
+    }
+
+    #[cfg(feature = "parallel")]
+    pub(super) fn collide_pairs_parallel(
+        &mut self,
+        rigid_bodies: &mut [RigidBody],
+    ) {
+        let pairs = self
+            .broadphase
+            .candidate_pairs(&self.collision_table)
+            .into_iter()
+            .filter(|(i, j)| {
+                rigid_bodies[*i].is_active() || rigid_bodies[*j].is_active()
+            })
+            .collect::<Vec<_>>();
+        let islands = Islands::from_pairs(&pairs, rigid_bodies);
+        let mut islands = islands
+            .islands()
+            .iter()
+            .filter(|island| !island.pairs().is_empty())
+            .collect::<Vec<_>>();
+        islands.sort_by_key(|island| {
+            island
+                .rigid_body_indices()
+                .iter()
+                .map(|i| rigid_bodies[*i].uid())
+                .min()
+        });
+        let mut pending = BTreeSet::new();
+        let largest_opt = islands
+            .iter()
+            .enumerate()
+            .rev()
+            .max_by_key(|(_, island)| island.pairs().len())
+            .map(|(k, _)| k);
+        if let Some(largest) = largest_opt {
+            if islands[largest].pairs().len() * 2 >= pairs.len() {
+                let island = islands.remove(largest);
+                pending.extend(island.pairs().iter().map(|(i, j)| (*j, *i)));
+            }
+        }
+        let mut jobs = islands
+            .into_iter()
+            .map(|island| self.island_job(island, rigid_bodies))
+            .collect::<Vec<_>>();
+        let config = self.config;
+        let broadphase_kind = self.broadphase_kind;
+        let profiling = self.profiler_opt.is_some();
+        crate::utility::parallel::for_each_mut(&mut jobs, |job| {
+            job.run(config, broadphase_kind, profiling)
+        });
+        let mut job_indices = vec![None; rigid_bodies.len()];
+        let mut moved = Vec::new();
+        for (k, job) in jobs.iter_mut().enumerate() {
+            moved.extend(self.merge_island_job(job, rigid_bodies));
+            for i in &job.rigid_body_indices {
+                if !rigid_bodies[*i].is_immovable() {
+                    job_indices[*i] = Some(k);
                 }
-                *separating_plane = SeparatingPlane::None;
             }
-            SeparatingPlane::Edge { edge_indices } => {
-                if Self::edges_make_separating_plane(edge_indices, rigid_bodies)
-                {
-                    return true;
+        }
+        moved.sort_unstable();
+        self.update_broadphase_bodies(&moved, rigid_bodies);
+        let is_resolved =
+            |i: usize, k: usize| match (job_indices[i], job_indices[k]) {
+                (Some(l), Some(m)) => l == m,
+                (Some(l), None) => jobs[l]
+                    .rigid_body_indices
+                    .binary_search_by_key(&rigid_bodies[k].uid(), |j| {
+                        rigid_bodies[*j].uid()
+                    })
+                    .is_ok(),
+                _ => false,
+            };
+        for i in &moved {
+            for k in self.broadphase.neighbors(*i, &self.collision_table) {
+                if !is_resolved(*i, k) {
+                    pending.insert((k.min(*i), k.max(*i)));
                 }
-                *separating_plane = SeparatingPlane::None;
             }
-            SeparatingPlane::None => (),
         }
-        if Self::separating_plane_face_search(
-            rigid_body_1_index,
-            rigid_body_2_index,
+        self.collide_pending(pending, rigid_bodies);
+    }
+
+    fn collide_pending(
+        &mut self,
+        mut pending: BTreeSet<(usize, usize)>,
+        rigid_bodies: &mut [RigidBody],
+    ) -> Vec<usize> {
+        let mut moved = Vec::new();
+        while let Some((j, i)) = pending.pop_first() {
+            if !rigid_bodies[i].is_active() && !rigid_bodies[j].is_active() {
+                continue;
+            }
+            if !self.collision_table.get(i, j).bounding_box_collision() {
+                continue;
+            }
+            profiler::count(
+                &mut self.profiler_opt,
+                ProfileCounter::PairsTested,
+                1,
+            );
+            if self.collide_pair(i, j, rigid_bodies) {
+                self.update_broadphase_bodies(&[i, j], rigid_bodies);
+                self.queue_neighbors(&[i, j], (j, i), &mut pending);
+                moved.extend(&[i, j]);
+            }
+        }
+        moved.sort_unstable();
+        moved.dedup();
+        moved
+    }
+
+    #[cfg(feature = "parallel")]
+    fn island_job(
+        &mut self,
+        island: &super::island::Island,
+        rigid_bodies: &mut [RigidBody],
+    ) -> IslandJob {
+        let mut rigid_body_indices = island.rigid_body_indices().to_vec();
+        for (i, j) in island.pairs() {
+            rigid_body_indices.extend(&[*i, *j]);
+        }
+        rigid_body_indices.sort_unstable_by_key(|i| rigid_bodies[*i].uid());
+        rigid_body_indices.dedup();
+        let mut collision_table = CollisionTable::new();
+        collision_table.generate(rigid_body_indices.len());
+        self.swap_island_statuses(
+            &rigid_body_indices,
+            &mut collision_table,
             rigid_bodies,
-            separating_plane,
-        ) || Self::separating_plane_face_search(
-            rigid_body_2_index,
-            rigid_body_1_index,
+        );
+        let mut event_log = EventLog::default();
+        event_log.set_min_severity(self.event_log.min_severity());
+        IslandJob {
+            rigid_bodies: rigid_body_indices
+                .iter()
+                .map(|i| {
+                    if rigid_bodies[*i].is_immovable() {
+                        rigid_bodies[*i].clone()
+                    } else {
+                        mem::take(&mut rigid_bodies[*i])
+                    }
+                })
+                .collect(),
+            rigid_body_indices,
+            collision_table,
+            event_log,
+            profile_frame: Default::default(),
+            moved: Vec::new(),
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    fn merge_island_job(
+        &mut self,
+        job: &mut IslandJob,
+        rigid_bodies: &mut [RigidBody],
+    ) -> Vec<usize> {
+        for (i, rigid_body) in job
+            .rigid_body_indices
+            .iter()
+            .zip(job.rigid_bodies.drain(..))
+        {
+            if !rigid_body.is_immovable() {
+                rigid_bodies[*i] = rigid_body;
+            }
+        }
+        self.swap_island_statuses(
+            &job.rigid_body_indices,
+            &mut job.collision_table,
             rigid_bodies,
-            separating_plane,
-        ) {
-            return true;
+        );
+        for entry in job.event_log.drain() {
+            self.event_log.push(entry.severity, entry.uids, entry.kind);
         }
-        Self::separating_plane_edge_search(
-            rigid_body_1_index,
-            rigid_body_2_index,
+        for counter in &ProfileCounter::ALL {
+            profiler::count(
+                &mut self.profiler_opt,
+                *counter,
+                job.profile_frame.count(*counter) as usize,
+            );
+        }
+        job.moved
+            .iter()
+            .map(|k| job.rigid_body_indices[*k])
+            .filter(|i| !rigid_bodies[*i].is_immovable())
+            .collect()
+    }
+
+    #[cfg(feature = "parallel")]
+    fn swap_island_statuses(
+        &mut self,
+        rigid_body_indices: &[usize],
+        collision_table: &mut CollisionTable,
+        rigid_bodies: &[RigidBody],
+    ) {
+        for (k, i) in rigid_body_indices.iter().enumerate() {
+            for (l, j) in rigid_body_indices[..k].iter().enumerate() {
+                if rigid_bodies[*i].is_immovable()
+                    && rigid_bodies[*j].is_immovable()
+                {
+                    continue;
+                }
+                let collision_status = self.collision_table.get_mut(*i, *j);
+                let island_status = collision_table.get_mut(k, l);
+                if collision_status.is_clear() && island_status.is_clear() {
+                    continue;
+                }
+                mem::swap(collision_status, island_status);
+                mem::swap(
+                    &mut collision_status.bounding_box,
+                    &mut island_status.bounding_box,
+                );
+                collision_status
+                    .map_rigid_bodies(|m| if m == k { *i } else { *j });
+                island_status.map_rigid_bodies(|m| if m == *i { k } else { l });
+            }
+        }
+    }
+
+    fn queue_neighbors(
+        &self,
+        indices: &[usize],
+        current: (usize, usize),
+        pending: &mut BTreeSet<(usize, usize)>,
+    ) {
+        for i in indices {
+            for k in self.broadphase.neighbors(*i, &self.collision_table) {
+                let pair = (k.min(*i), k.max(*i));
+                if pair > current {
+                    pending.insert(pair);
+                }
+            }
+        }
+    }
+
+    fn collide_pair(
+        &mut self,
+        i: usize,
+        j: usize,
+        rigid_bodies: &mut [RigidBody],
+    ) -> bool {
+        if Self::is_point_pair(i, j, rigid_bodies) {
+            return self.collide_point_pair(i, j, rigid_bodies);
+        }
+        !self.check_for_separating_plane(i, j, rigid_bodies)
+            && self.resolve_pair(i, j, rigid_bodies)
+    }
+
+    fn resolve_pair(
+        &mut self,
+        i: usize,
+        j: usize,
+        rigid_bodies: &mut [RigidBody],
+    ) -> bool {
+        self.collision_table.get_mut(i, j).colliding = true;
+        self.handle_collision_simple(i, j, rigid_bodies);
+        true
+    }
+
+    fn collide_point_pair(
+        &mut self,
+        i: usize,
+        j: usize,
+        rigid_bodies: &mut [RigidBody],
+    ) -> bool {
+        let mut candidates = Self::point_candidates(i, j, rigid_bodies);
+        let mut moved = false;
+        profiler::begin(&mut self.profiler_opt, ProfileStage::DePenetration);
+        for _ in 0..self.config.de_penetrate_iterations {
+            let (gap, point_indices) =
+                match candidates.iter().min_by(|a, b| a.0.total_cmp(&b.0)) {
+                    Some((gap, point_indices)) if *gap < 0. => {
+                        (*gap, *point_indices)
+                    }
+                    _ => break,
+                };
+            let position_index = point_indices.position_rigid_body;
+            let normal_index = point_indices.normal_rigid_body;
+            let mass_inv_position = rigid_bodies[position_index].mass_inv();
+            let mass_inv_normal = rigid_bodies[normal_index].mass_inv();
+            let separation = point_indices
+                .normal
+                .scale(-gap / (mass_inv_position + mass_inv_normal));
+            let rigid_body = &mut rigid_bodies[position_index];
+            rigid_body
+                .position
+                .add_assign(&separation.scale(mass_inv_position));
+            rigid_body.update_geometry();
+            let rigid_body = &mut rigid_bodies[normal_index];
+            rigid_body
+                .position
+                .sub_assign(&separation.scale(mass_inv_normal));
+            rigid_body.update_geometry();
+            candidates = Self::point_candidates(i, j, rigid_bodies);
+            moved = true;
+        }
+        profiler::end(&mut self.profiler_opt);
+        let touching = candidates
+            .iter()
+            .filter(|(gap, _)| *gap <= self.config.collision_epsilon)
+            .map(|(_, point_indices)| *point_indices)
+            .collect::<Vec<_>>();
+        let mut extremes = (0, 0, 0.);
+        for k in 1..touching.len() {
+            for l in 0..k {
+                let dist = touching[k]
+                    .contact_position
+                    .dist(&touching[l].contact_position);
+                if dist > extremes.2 {
+                    extremes = (l, k, dist);
+                }
+            }
+        }
+        let collision_status = self.collision_table.get_mut(i, j);
+        collision_status.contacts.clear();
+        if !touching.is_empty() {
+            collision_status.contacts.push(Contact::Point {
+                point_indices: touching[extremes.0],
+            });
+        }
+        if extremes.2 > self.config.collision_epsilon {
+            collision_status.contacts.push(Contact::Point {
+                point_indices: touching[extremes.1],
+            });
+        }
+        if collision_status.contacts.is_empty() {
+            return moved;
+        }
+        collision_status.colliding = true;
+        profiler::count(
+            &mut self.profiler_opt,
+            ProfileCounter::ContactsFound,
+            collision_status.contacts.len(),
+        );
+        profiler::begin(
+            &mut self.profiler_opt,
+            ProfileStage::ContactResolution,
+        );
+        Self::contact_forces_simple(
+            &collision_status.contacts,
+            self.config.coefficient_of_restitution,
             rigid_bodies,
-            separating_plane,
-        )
+        );
+        profiler::end(&mut self.profiler_opt);
+        true
+    }
+
+    fn is_point_pair(i: usize, j: usize, rigid_bodies: &[RigidBody]) -> bool {
+        [i, j].iter().any(|k| {
+            rigid_bodies[*k].rounded_core().is_some()
+                || rigid_bodies[*k].cylinder_core().is_some()
+        })
+    }
+
+    fn cylinder_candidates(
+        i: usize,
+        j: usize,
+        rigid_bodies: &[RigidBody],
+    ) -> Vec<(f64, PointIndices)> {
+        let (cylinder_index, other_index) =
+            if rigid_bodies[i].cylinder_core().is_some() {
+                (i, j)
+            } else {
+                (j, i)
+            };
+        let cylinder = &rigid_bodies[cylinder_index];
+        let other_rigid_body = &rigid_bodies[other_index];
+        let (core, radius) = cylinder.cylinder_core().unwrap();
+        let (gap, position, other_position, normal) = gjk::signed_dist(
+            |direction| cylinder.support(direction),
+            |direction| other_rigid_body.support(direction),
+            &cylinder.position.sub(&other_rigid_body.position),
+        );
+        let mut ret = vec![(
+            gap,
+            PointIndices {
+                position_rigid_body: cylinder_index,
+                normal_rigid_body: other_index,
+                contact_position: position.add(&other_position).scale(0.5),
+                normal,
+            },
+        )];
+        for position in &Self::cylinder_rim(&core, radius, &normal.scale(-1.)) {
+            let (dist, contact_position, normal) =
+                Self::point_closest(position, other_rigid_body);
+            ret.push((
+                dist,
+                PointIndices {
+                    position_rigid_body: cylinder_index,
+                    normal_rigid_body: other_index,
+                    contact_position,
+                    normal,
+                },
+            ));
+        }
+        let other_positions = if let Some((other_core, other_radius)) =
+            other_rigid_body.rounded_core()
+        {
+            let offset = normal.scale(other_radius);
+            vec![other_core.start.add(&offset), other_core.end.add(&offset)]
+        } else if let Some((other_core, other_radius)) =
+            other_rigid_body.cylinder_core()
+        {
+            Self::cylinder_rim(&other_core, other_radius, &normal)
+        } else {
+            other_rigid_body.polyhedron_world().vertices().to_vec()
+        };
+        for position in &other_positions {
+            let (dist, contact_position, normal) =
+                Self::point_closest(position, cylinder);
+            ret.push((
+                dist,
+                PointIndices {
+                    position_rigid_body: other_index,
+                    normal_rigid_body: cylinder_index,
+                    contact_position,
+                    normal,
+                },
+            ));
+        }
+        ret
+    }
+
+    fn cylinder_rim(
+        core: &FiniteLine3d,
+        radius: f64,
+        direction: &Vector3d,
+    ) -> Vec<Vector3d> {
+        let axis = core.end.sub(&core.start).normal();
+        let mut radial = direction.sub(&axis.scale(axis.dot(direction)));
+        if radial.mag_sq() <= f64::EPSILON {
+            let other = if axis[0].abs() < 0.9 {
+                Vector3d::new(1., 0., 0.)
+            } else {
+                Vector3d::new(0., 1., 0.)
+            };
+            radial = axis.cross(&other);
+        }
+        let radial = radial.normal().scale(radius);
+        let tangent = axis.cross(&radial);
+        let mut ret = Vec::with_capacity(8);
+        for cap in &[core.start, core.end] {
+            for offset in &[radial, tangent] {
+                ret.push(cap.add(offset));
+                ret.push(cap.sub(offset));
+            }
+        }
+        ret
+    }
+
+    pub(super) fn point_polyhedron_closest(
+        position: &Vector3d,
+        polyhedron: &Polyhedron,
+    ) -> (f64, Vector3d, Vector3d) {
+        let vertices = polyhedron.vertices();
+        let mut max_face_dist = f64::MIN;
+        let mut max_face_direction = Vector3d::default();
+        for face in polyhedron.faces() {
+            let dist = geometry::pos_raw_plane_signed_dist(
+                position,
+                &vertices[face.vertex_indices()[0]],
+                face.direction(),
+            );
+            if dist > max_face_dist {
+                max_face_dist = dist;
+                max_face_direction = *face.direction();
+            }
+        }
+        if max_face_dist <= 0. {
+            return (
+                max_face_dist,
+                position.sub(&max_face_direction.scale(max_face_dist)),
+                max_face_direction,
+            );
+        }
+        let mut ret = (f64::MAX, Vector3d::default(), Vector3d::default());
+        'faces: for face in polyhedron.faces() {
+            let dist = geometry::pos_raw_plane_signed_dist(
+                position,
+                &vertices[face.vertex_indices()[0]],
+                face.direction(),
+            );
+            if dist <= 0. || dist >= ret.0 {
+                continue;
+            }
+            for enclosing_plane in
+                &face.enclosing_planes(vertices, polyhedron.edges())
+            {
+                if geometry::pos_raw_plane_signed_dist(
+                    position,
+                    &vertices[enclosing_plane.vertex_index()],
+                    enclosing_plane.direction(),
+                ) >= 0.
+                {
+                    continue 'faces;
+                }
+            }
+            ret = (
+                dist,
+                position.sub(&face.direction().scale(dist)),
+                *face.direction(),
+            );
+        }
+        for edge in polyhedron.edges() {
+            let (_, edge_position, dist_sq) =
+                geometry::raw_finite_line_closest_dist_sq(
+                    position,
+                    position,
+                    &vertices[edge.start_index()],
+                    &vertices[edge.end_index()],
+                );
+            let dist = dist_sq.sqrt();
+            if dist < ret.0 && dist > 0. {
+                ret = (
+                    dist,
+                    edge_position,
+                    position.sub(&edge_position).scale(1. / dist),
+                );
+            }
+        }
+        ret
+    }
+
+    fn point_candidates(
+        i: usize,
+        j: usize,
+        rigid_bodies: &[RigidBody],
+    ) -> Vec<(f64, PointIndices)> {
+        if rigid_bodies[i].cylinder_core().is_some()
+            || rigid_bodies[j].cylinder_core().is_some()
+        {
+            Self::cylinder_candidates(i, j, rigid_bodies)
+        } else {
+            Self::rounded_candidates(i, j, rigid_bodies)
+        }
+    }
+
+    fn point_closest(
+        position: &Vector3d,
+        rigid_body: &RigidBody,
+    ) -> (f64, Vector3d, Vector3d) {
+        if let Some((core, radius)) = rigid_body.rounded_core() {
+            let (_, closest, dist_sq) =
+                geometry::raw_finite_line_closest_dist_sq(
+                    position,
+                    position,
+                    &core.start,
+                    &core.end,
+                );
+            let dist = dist_sq.sqrt();
+            let normal = if dist > f64::EPSILON {
+                position.sub(&closest).scale(1. / dist)
+            } else {
+                Vector3d::new(1., 0., 0.)
+            };
+            return (dist - radius, closest.add(&normal.scale(radius)), normal);
+        }
+        if let Some((core, radius)) = rigid_body.cylinder_core() {
+            return geometry::pos_cylinder_closest(position, &core, radius);
+        }
+        Self::point_polyhedron_closest(position, rigid_body.polyhedron_world())
+    }
+
+    fn rounded_candidates(
+        i: usize,
+        j: usize,
+        rigid_bodies: &[RigidBody],
+    ) -> Vec<(f64, PointIndices)> {
+        let (rounded_index, other_index) =
+            if rigid_bodies[i].rounded_core().is_some() {
+                (i, j)
+            } else {
+                (j, i)
+            };
+        let (core, radius) =
+            rigid_bodies[rounded_index].rounded_core().unwrap();
+        let other_rigid_body = &rigid_bodies[other_index];
+        let mut ret = Vec::new();
+        match other_rigid_body.rounded_core() {
+            Some((other_core, other_radius)) => {
+                let mut push =
+                    |position: &Vector3d, other_position: &Vector3d| {
+                        let dir = position.sub(other_position);
+                        let dist = dir.mag();
+                        let normal = if dist > f64::EPSILON {
+                            dir.scale(1. / dist)
+                        } else {
+                            Vector3d::new(1., 0., 0.)
+                        };
+                        let gap = dist - radius - other_radius;
+                        ret.push((
+                            gap,
+                            PointIndices {
+                                position_rigid_body: rounded_index,
+                                normal_rigid_body: other_index,
+                                contact_position: other_position.add(
+                                    &normal.scale(other_radius + gap / 2.),
+                                ),
+                                normal,
+                            },
+                        ));
+                    };
+                let (position, other_position, _) =
+                    geometry::raw_finite_line_closest_dist_sq(
+                        &core.start,
+                        &core.end,
+                        &other_core.start,
+                        &other_core.end,
+                    );
+                push(&position, &other_position);
+                for (line, other_line, flip) in
+                    &[(&core, &other_core, false), (&other_core, &core, true)]
+                {
+                    if line.start.dist_sq(&line.end) <= 0. {
+                        continue;
+                    }
+                    for end in &[line.start, line.end] {
+                        let (_, closest, _) =
+                            geometry::raw_finite_line_closest_dist_sq(
+                                end,
+                                end,
+                                &other_line.start,
+                                &other_line.end,
+                            );
+                        if *flip {
+                            push(&closest, end);
+                        } else {
+                            push(end, &closest);
+                        }
+                    }
+                }
+            }
+            None => {
+                let polyhedron = other_rigid_body.polyhedron_world();
+                let vertices = polyhedron.vertices();
+                let mut positions = vec![core.start];
+                if core.start.dist_sq(&core.end) > 0. {
+                    positions.push(core.end);
+                    let (position, _, _) =
+                        geometry::raw_finite_line_closest_dist_sq(
+                            &core.start,
+                            &core.end,
+                            &other_rigid_body.position,
+                            &other_rigid_body.position,
+                        );
+                    positions.push(position);
+                    for edge in polyhedron.edges() {
+                        let (position, _, _) =
+                            geometry::raw_finite_line_closest_dist_sq(
+                                &core.start,
+                                &core.end,
+                                &vertices[edge.start_index()],
+                                &vertices[edge.end_index()],
+                            );
+                        positions.push(position);
+                    }
+                }
+                for position in &positions {
+                    let (dist, contact_position, normal) =
+                        Self::point_polyhedron_closest(position, polyhedron);
+                    ret.push((
+                        dist - radius,
+                        PointIndices {
+                            position_rigid_body: rounded_index,
+                            normal_rigid_body: other_index,
+                            contact_position,
+                            normal,
+                        },
+                    ));
+                }
+            }
+        }
+        ret
     }
 
     fn contact_force(
         contact: &Contact,
+        coefficient_of_restitution: f64,
         rigid_bodies: &mut [RigidBody],
     ) -> bool {
         let position_rigid_body_index;
@@ -172,6 +1089,12 @@ impl CollisionManager {
                 normal_rigid_body_index = edge_edge_indices.plane_rigid_body;
                 normal = &edge_edge_indices.plane_direction;
             }
+            Contact::Point { point_indices } => {
+                position_rigid_body_index = point_indices.position_rigid_body;
+                position = &point_indices.contact_position;
+                normal_rigid_body_index = point_indices.normal_rigid_body;
+                normal = &point_indices.normal;
+            }
         }
         let position_rigid_body = &rigid_bodies[position_rigid_body_index];
         let normal_rigid_body = &rigid_bodies[normal_rigid_body_index];
@@ -193,18 +1116,16 @@ impl CollisionManager {
             return false;
         }
 
-        let get_den_term = |rigid_body: &RigidBody, rel_com: &Vector3d| {
-            rigid_body.mass_inv()
-                + matrix_vector::mult_3(
-                    &rigid_body.inertia_inv(),
-                    &rel_com.cross(normal),
-                )
-                .cross(rel_com)
-                .dot(normal)
-        };
-        let impulse_mag = (-(1. + COR) * rel_vel)
-            / (get_den_term(position_rigid_body, &rel_com_position)
-                + get_den_term(normal_rigid_body, &rel_com_normal));
+        let impulse_mag = (-(1. + coefficient_of_restitution) * rel_vel)
+            / (Self::impulse_den_term(
+                position_rigid_body,
+                &rel_com_position,
+                normal,
+            ) + Self::impulse_den_term(
+                normal_rigid_body,
+                &rel_com_normal,
+                normal,
+            ));
         let impulse = normal.scale(impulse_mag);
 
         let position_rigid_body = &mut rigid_bodies[position_rigid_body_index];
@@ -226,8 +1147,56 @@ impl CollisionManager {
         true
     }
 
+    fn half_space_contact_force(
+        contact: &HalfSpaceContact,
+        half_spaces: &[HalfSpace],
+        coefficient_of_restitution: f64,
+        rigid_bodies: &mut [RigidBody],
+    ) -> bool {
+        let half_space = &half_spaces[contact.half_space];
+        let normal = half_space.normal();
+        let rigid_body = &rigid_bodies[contact.rigid_body];
+        let rel_com = contact.position.sub(&rigid_body.position);
+        let rel_vel = rigid_body
+            .angular_velocity()
+            .cross(&rel_com)
+            .add(rigid_body.velocity())
+            .sub(&half_space.velocity_at(&contact.position))
+            .dot(normal);
+        if rel_vel >= 0. {
+            return false;
+        }
+        let impulse = normal.scale(
+            (-(1. + coefficient_of_restitution) * rel_vel)
+                / Self::impulse_den_term(rigid_body, &rel_com, normal),
+        );
+        let rigid_body = &mut rigid_bodies[contact.rigid_body];
+        rigid_body.momentum.add_assign(&impulse);
+        rigid_body
+            .angular_momentum
+            .add_assign(&rel_com.cross(&impulse));
+        rigid_body.update_velocity();
+        rigid_body.update_angular_velocity();
+        true
+    }
+
+    fn impulse_den_term(
+        rigid_body: &RigidBody,
+        rel_com: &Vector3d,
+        normal: &Vector3d,
+    ) -> f64 {
+        rigid_body.mass_inv()
+            + matrix_vector::mult_3(
+                &rigid_body.inertia_inv(),
+                &rel_com.cross(normal),
+            )
+            .cross(rel_com)
+            .dot(normal)
+    }
+
     fn contact_search(
         separating_plane: &SeparatingPlane,
+        collision_epsilon: f64,
         rigid_bodies: &[RigidBody],
         mode: &mut Mode,
     ) {
@@ -269,6 +1238,7 @@ impl CollisionManager {
                             face_rigid_body: separating_rigid_body_index,
                             face: face_indices.face,
                         },
+                        collision_epsilon,
                         rigid_bodies,
                         mode,
                     );
@@ -297,8 +1267,10 @@ impl CollisionManager {
                 separating_vertices,
                 separating_plane_pos,
                 separating_plane_dir,
+                collision_epsilon,
             ),
             other_rigid_body_index,
+            collision_epsilon,
             rigid_bodies,
             mode,
         );
@@ -307,6 +1279,7 @@ impl CollisionManager {
             separating_plane_pos,
             separating_plane_dir,
             other_rigid_body_index,
+            collision_epsilon,
             rigid_bodies,
             mode,
         );
@@ -317,6 +1290,7 @@ impl CollisionManager {
         plane_pos: &Vector3d,
         plane_dir: &Vector3d,
         other_rigid_body_index: usize,
+        collision_epsilon: f64,
         rigid_bodies: &[RigidBody],
         mode: &mut Mode,
     ) {
@@ -331,12 +1305,12 @@ impl CollisionManager {
                 &vertices[edge.start_index()],
                 plane_pos,
                 plane_dir,
-            ) <= COLLISION_EPSILON
+            ) <= collision_epsilon
                 || geometry::pos_raw_plane_dist(
                     &vertices[edge.end_index()],
                     plane_pos,
                     plane_dir,
-                ) <= COLLISION_EPSILON
+                ) <= collision_epsilon
             {
                 plane_coincident_edges.push(edge_index);
             }
@@ -362,7 +1336,7 @@ impl CollisionManager {
                 match mode {
                     Mode::ClosestDist { dist } => **dist = dist.min(ip),
                     Mode::Contacts { contacts } => {
-                        if ip <= COLLISION_EPSILON {
+                        if ip <= collision_epsilon {
                             let mut plane_direction =
                                 edge.direction().cross(other_edge.direction());
                             if plane_direction.is_zero() {
@@ -440,6 +1414,49 @@ impl CollisionManager {
         }
     }
 
+    fn find_separating_plane(
+        separating_plane: &mut SeparatingPlane,
+        rigid_body_1_index: usize,
+        rigid_body_2_index: usize,
+        rigid_bodies: &[RigidBody],
+    ) -> bool {
+        match &separating_plane {
+            SeparatingPlane::Face { face_indices } => {
+                if Self::face_is_separating_plane(face_indices, rigid_bodies) {
+                    return true;
+                }
+                *separating_plane = SeparatingPlane::None;
+            }
+            SeparatingPlane::Edge { edge_indices } => {
+                if Self::edges_make_separating_plane(edge_indices, rigid_bodies)
+                {
+                    return true;
+                }
+                *separating_plane = SeparatingPlane::None;
+            }
+            SeparatingPlane::None => (),
+        }
+        if Self::separating_plane_face_search(
+            rigid_body_1_index,
+            rigid_body_2_index,
+            rigid_bodies,
+            separating_plane,
+        ) || Self::separating_plane_face_search(
+            rigid_body_2_index,
+            rigid_body_1_index,
+            rigid_bodies,
+            separating_plane,
+        ) {
+            return true;
+        }
+        Self::separating_plane_edge_search(
+            rigid_body_1_index,
+            rigid_body_2_index,
+            rigid_bodies,
+            separating_plane,
+        )
+    }
+
     fn face_is_separating_plane(
         face_indices: &FaceIndices,
         rigid_bodies: &[RigidBody],
@@ -466,11 +1483,12 @@ impl CollisionManager {
         vertices: &[Vector3d],
         plane_pos: &Vector3d,
         plane_dir: &Vector3d,
+        collision_epsilon: f64,
     ) -> Vec<usize> {
         let mut ret = Vec::with_capacity(vertices.len());
         for (vertex_index, vertex) in vertices.iter().enumerate() {
             if geometry::pos_raw_plane_dist(vertex, plane_pos, plane_dir)
-                <= COLLISION_EPSILON
+                <= collision_epsilon
             {
                 ret.push(vertex_index);
             }
@@ -554,6 +1572,7 @@ impl CollisionManager {
 
     fn vertex_face_dist_check(
         vertex_face_indices: &VertexFaceIndices,
+        collision_epsilon: f64,
         rigid_bodies: &[RigidBody],
         mode: &mut Mode,
     ) -> bool {
@@ -586,7 +1605,7 @@ impl CollisionManager {
             match mode {
                 Mode::ClosestDist { dist } => **dist = dist.min(signed_dist),
                 Mode::Contacts { contacts } => {
-                    if signed_dist <= COLLISION_EPSILON {
+                    if signed_dist <= collision_epsilon {
                         contacts.push(Contact::VertexFace {
                             vertex_face_indices: *vertex_face_indices,
                         });
@@ -603,6 +1622,7 @@ impl CollisionManager {
         vertex_rigid_body: usize,
         vertex_indices: &[usize],
         face_rigid_body: usize,
+        collision_epsilon: f64,
         rigid_bodies: &[RigidBody],
         mode: &mut Mode,
     ) {
@@ -616,6 +1636,7 @@ impl CollisionManager {
                         face_rigid_body,
                         face,
                     },
+                    collision_epsilon,
                     rigid_bodies,
                     mode,
                 ) {
@@ -631,12 +1652,12 @@ impl CollisionManager {
         rigid_body_2_index: usize,
         rigid_bodies: &mut [RigidBody],
     ) {
-        fn get_h_extent(rigid_body: &RigidBody) -> f64 {
+        fn get_h_extent(rigid_body: &RigidBody, collision_epsilon: f64) -> f64 {
             let bounding_box = rigid_body.bounding_box();
             let mut ret = 0.;
             for i in 0..3 {
                 let dist_axis = bounding_box[1][i] - bounding_box[0][i]
-                    + COLLISION_EPSILON * 2.;
+                    + collision_epsilon * 2.;
                 ret += dist_axis * dist_axis;
             }
             ret.sqrt() / 2.
@@ -644,8 +1665,11 @@ impl CollisionManager {
         let rigid_body_1 = &rigid_bodies[rigid_body_1_index];
         let rigid_body_2 = &rigid_bodies[rigid_body_2_index];
         let separating = rigid_body_2.position.sub(&rigid_body_1.position);
-        let extent = get_h_extent(rigid_body_1) + get_h_extent(rigid_body_2);
+        let collision_epsilon = self.config.collision_epsilon;
+        let extent = get_h_extent(rigid_body_1, collision_epsilon)
+            + get_h_extent(rigid_body_2, collision_epsilon);
 
+        profiler::begin(&mut self.profiler_opt, ProfileStage::DePenetration);
         if separating.is_zero() {
             self.de_penetrate_dir(
                 &Vector3d::new(1., 0., 0.),
@@ -663,20 +1687,43 @@ impl CollisionManager {
                 rigid_bodies,
             );
         }
+        profiler::end(&mut self.profiler_opt);
         let collision_status = self
             .collision_table
             .get_mut(rigid_body_1_index, rigid_body_2_index);
         Self::contact_search(
             &collision_status.separating_plane,
+            collision_epsilon,
             rigid_bodies,
             &mut Mode::Contacts {
                 contacts: &mut collision_status.contacts,
             },
         );
-        if self.debug && collision_status.contacts.is_empty() {
-            println!("closest_distance - Contacts",);
+        if collision_status.contacts.is_empty() {
+            self.event_log.push(
+                Severity::Debug,
+                (
+                    rigid_bodies[rigid_body_1_index].uid(),
+                    rigid_bodies[rigid_body_2_index].uid(),
+                ),
+                FailureKind::MissingContacts,
+            );
         }
-        Self::contact_forces_simple(&collision_status.contacts, rigid_bodies);
+        profiler::count(
+            &mut self.profiler_opt,
+            ProfileCounter::ContactsFound,
+            collision_status.contacts.len(),
+        );
+        profiler::begin(
+            &mut self.profiler_opt,
+            ProfileStage::ContactResolution,
+        );
+        Self::contact_forces_simple(
+            &collision_status.contacts,
+            self.config.coefficient_of_restitution,
+            rigid_bodies,
+        );
+        profiler::end(&mut self.profiler_opt);
     }
 
     fn de_penetrate_dir(
@@ -692,16 +1739,23 @@ impl CollisionManager {
         let mass_inv_tot = mass_inv_1 + mass_inv_2;
         let mass_ratio_1 = mass_inv_1 / mass_inv_tot;
         let mass_ratio_2 = mass_inv_2 / mass_inv_tot;
+        let uids = (
+            rigid_bodies[rigid_body_1_index].uid(),
+            rigid_bodies[rigid_body_2_index].uid(),
+        );
         let mut dist = MAX;
-        while dist >= COLLISION_EPSILON {
+        while dist >= self.config.collision_epsilon {
+            profiler::count(
+                &mut self.profiler_opt,
+                ProfileCounter::BisectionIterations,
+                1,
+            );
             if bisect.abs() < EPSILON {
-                if self.debug {
-                    println!(
-                        "de_penetrate_dir failiure - bisect, uids: ({} {})",
-                        rigid_bodies[rigid_body_1_index].uid(),
-                        rigid_bodies[rigid_body_2_index].uid(),
-                    );
-                }
+                self.event_log.push(
+                    Severity::Warning,
+                    uids,
+                    FailureKind::DePenetrateBisect,
+                );
                 return;
             }
             let separation = separating_dir.scale(bisect);
@@ -726,29 +1780,26 @@ impl CollisionManager {
                         .collision_table
                         .get(rigid_body_1_index, rigid_body_2_index)
                         .separating_plane,
+                    self.config.collision_epsilon,
                     rigid_bodies,
                     &mut Mode::ClosestDist { dist: &mut dist },
                 );
                 if dist == MAX {
-                    if self.debug {
-                        println!(
-                            "de_penetrate_dir failiure - plane, uids: ({} {})",
-                            rigid_bodies[rigid_body_1_index].uid(),
-                            rigid_bodies[rigid_body_2_index].uid(),
-                        );
-                    }
+                    self.event_log.push(
+                        Severity::Warning,
+                        uids,
+                        FailureKind::DePenetratePlane,
+                    );
                     return;
                 }
                 bisect = bisect.copysign(-1.);
             } else {
                 if dist == MAX {
-                    if self.debug {
-                        println!(
-			    "de_penetrate_dir failiure - no plane, uids: ({} {})",
-			    rigid_bodies[rigid_body_1_index].uid(),
-			    rigid_bodies[rigid_body_2_index].uid(),
-			);
-                    }
+                    self.event_log.push(
+                        Severity::Warning,
+                        uids,
+                        FailureKind::DePenetrateNoPlane,
+                    );
                     return;
                 }
                 bisect = bisect.copysign(1.);
@@ -759,10 +1810,59 @@ impl CollisionManager {
 
     fn contact_forces_simple(
         contacts: &[Contact],
+        coefficient_of_restitution: f64,
         rigid_bodies: &mut [RigidBody],
-    ) {
+    ) -> bool {
+        let mut ret = false;
         for contact in contacts {
-            Self::contact_force(contact, rigid_bodies);
+            ret |= Self::contact_force(
+                contact,
+                coefficient_of_restitution,
+                rigid_bodies,
+            );
+        }
+        ret
+    }
+
+    fn translate(rigid_body: &mut RigidBody, offset: &Vector3d) {
+        rigid_body.position = rigid_body.position.add(offset);
+        rigid_body.update_geometry();
+    }
+}
+
+#[cfg(feature = "parallel")]
+struct IslandJob {
+    rigid_body_indices: Vec<usize>,
+    rigid_bodies: Vec<RigidBody>,
+    collision_table: CollisionTable,
+    event_log: EventLog,
+    profile_frame: super::profiler::ProfileFrame,
+    moved: Vec<usize>,
+}
+
+#[cfg(feature = "parallel")]
+impl IslandJob {
+    fn run(
+        &mut self,
+        config: SimulationConfig,
+        broadphase_kind: BroadphaseKind,
+        profiling: bool,
+    ) {
+        let mut collision_manager = CollisionManager::with_config(config);
+        collision_manager.set_broadphase(broadphase_kind);
+        collision_manager.profiler_opt = profiling.then(|| Profiler::new(1));
+        collision_manager.event_log = mem::take(&mut self.event_log);
+        collision_manager.collision_table =
+            mem::take(&mut self.collision_table);
+        collision_manager.broadphase.generate(
+            &self.rigid_bodies,
+            &mut collision_manager.collision_table,
+        );
+        self.moved = collision_manager.collide_pairs(&mut self.rigid_bodies);
+        self.collision_table = collision_manager.collision_table;
+        self.event_log = collision_manager.event_log;
+        if let Some(profiler) = collision_manager.profiler_opt {
+            self.profile_frame = *profiler.current_frame();
         }
     }
 }
@@ -2,7 +2,7 @@ use super::rigid_body::RigidBody;
 use crate::math::{geometry, vector::Vector3d};
 use std::mem;
 
-#[derive(Default)]
+#[derive(Clone, Default)]
 pub struct CollisionTable {
     data: Vec<Vec<CollisionStatus>>,
 }
@@ -71,6 +71,55 @@ impl CollisionStatus {
     pub fn bounding_box_collision(&self) -> bool {
         self.bounding_box[0] && self.bounding_box[1] && self.bounding_box[2]
     }
+
+    #[cfg(feature = "parallel")]
+    pub(super) fn is_clear(&self) -> bool {
+        !self.colliding
+            && self.contacts.is_empty()
+            && matches!(self.separating_plane, SeparatingPlane::None)
+    }
+
+    #[cfg(feature = "parallel")]
+    pub(super) fn map_rigid_bodies(&mut self, f: impl Fn(usize) -> usize) {
+        match &mut self.separating_plane {
+            SeparatingPlane::Face { face_indices } => {
+                face_indices.face_rigid_body = f(face_indices.face_rigid_body);
+                face_indices.other_rigid_body =
+                    f(face_indices.other_rigid_body);
+            }
+            SeparatingPlane::Edge { edge_indices } => {
+                edge_indices.plane_rigid_body =
+                    f(edge_indices.plane_rigid_body);
+                edge_indices.other_rigid_body =
+                    f(edge_indices.other_rigid_body);
+            }
+            SeparatingPlane::None => {}
+        }
+        for contact in &mut self.contacts {
+            match contact {
+                Contact::VertexFace {
+                    vertex_face_indices,
+                } => {
+                    vertex_face_indices.vertex_rigid_body =
+                        f(vertex_face_indices.vertex_rigid_body);
+                    vertex_face_indices.face_rigid_body =
+                        f(vertex_face_indices.face_rigid_body);
+                }
+                Contact::EdgeEdge { edge_edge_indices } => {
+                    edge_edge_indices.plane_rigid_body =
+                        f(edge_edge_indices.plane_rigid_body);
+                    edge_edge_indices.other_rigid_body =
+                        f(edge_edge_indices.other_rigid_body);
+                }
+                Contact::Point { point_indices } => {
+                    point_indices.position_rigid_body =
+                        f(point_indices.position_rigid_body);
+                    point_indices.normal_rigid_body =
+                        f(point_indices.normal_rigid_body);
+                }
+            }
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -137,6 +186,9 @@ pub enum Contact {
     EdgeEdge {
         edge_edge_indices: EdgeEdgeIndices,
     },
+    Point {
+        point_indices: PointIndices,
+    },
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -156,3 +208,11 @@ pub struct EdgeEdgeIndices {
     pub contact_position: Vector3d,
     pub plane_direction: Vector3d,
 }
+
+#[derive(Clone, Copy, Debug)]
+pub struct PointIndices {
+    pub position_rigid_body: usize,
+    pub normal_rigid_body: usize,
+    pub contact_position: Vector3d,
+    pub normal: Vector3d,
+}
@@ -0,0 +1,227 @@
+use super::rigid_body::{self, RigidBody};
+use crate::math::vector::Vector3d;
+use std::collections::VecDeque;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DiagnosticsSample {
+    pub time: f64,
+    pub kinetic_energy: f64,
+    pub potential_energy: f64,
+    pub linear_momentum: Vector3d,
+    pub angular_momentum: Vector3d,
+}
+
+impl DiagnosticsSample {
+    pub fn new(
+        time: f64,
+        potential_energy: f64,
+        rigid_bodies: &[RigidBody],
+    ) -> Self {
+        let mut linear_momentum = Vector3d::default();
+        let mut angular_momentum = Vector3d::default();
+        for rigid_body in rigid_bodies {
+            if rigid_body.is_immovable() {
+                continue;
+            }
+            linear_momentum.add_assign(&rigid_body.momentum);
+            angular_momentum
+                .add_assign(&rigid_body.position.cross(&rigid_body.momentum));
+            angular_momentum.add_assign(&rigid_body.angular_momentum);
+        }
+        Self {
+            time,
+            kinetic_energy: rigid_body::ki_total(rigid_bodies),
+            potential_energy,
+            linear_momentum,
+            angular_momentum,
+        }
+    }
+
+    pub fn total_energy(&self) -> f64 {
+        self.kinetic_energy + self.potential_energy
+    }
+
+    pub fn drift(&self, initial: &Self, quantity: DiagnosticsQuantity) -> f64 {
+        match quantity {
+            DiagnosticsQuantity::Energy => {
+                (self.total_energy() - initial.total_energy()).abs()
+            }
+            DiagnosticsQuantity::LinearMomentum => {
+                self.linear_momentum.dist(&initial.linear_momentum)
+            }
+            DiagnosticsQuantity::AngularMomentum => {
+                self.angular_momentum.dist(&initial.angular_momentum)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DiagnosticsQuantity {
+    Energy,
+    LinearMomentum,
+    AngularMomentum,
+}
+
+impl DiagnosticsQuantity {
+    pub const ALL: [Self; 3] =
+        [Self::Energy, Self::LinearMomentum, Self::AngularMomentum];
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct DiagnosticsTolerance {
+    pub energy: f64,
+    pub linear_momentum: f64,
+    pub angular_momentum: f64,
+}
+
+impl DiagnosticsTolerance {
+    pub fn get(&self, quantity: DiagnosticsQuantity) -> f64 {
+        match quantity {
+            DiagnosticsQuantity::Energy => self.energy,
+            DiagnosticsQuantity::LinearMomentum => self.linear_momentum,
+            DiagnosticsQuantity::AngularMomentum => self.angular_momentum,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct DiagnosticsWarning {
+    pub time: f64,
+    pub quantity: DiagnosticsQuantity,
+    pub drift: f64,
+    pub tolerance: f64,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DriftStatistics {
+    pub current: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub rms: f64,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Diagnostics {
+    initial_opt: Option<DiagnosticsSample>,
+    latest_opt: Option<DiagnosticsSample>,
+    samples: VecDeque<DiagnosticsSample>,
+    history: usize,
+    sample_count: usize,
+    drift_max: [f64; 3],
+    drift_sum: [f64; 3],
+    drift_sum_sq: [f64; 3],
+    tolerance_opt: Option<DiagnosticsTolerance>,
+    exceeded: [bool; 3],
+    warnings: Vec<DiagnosticsWarning>,
+}
+
+impl Diagnostics {
+    pub fn new(tolerance_opt: Option<DiagnosticsTolerance>) -> Self {
+        Self {
+            tolerance_opt,
+            ..Self::default()
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.initial_opt = None;
+        self.latest_opt = None;
+        self.samples.clear();
+        self.sample_count = 0;
+        self.drift_max = [0.; 3];
+        self.drift_sum = [0.; 3];
+        self.drift_sum_sq = [0.; 3];
+        self.exceeded = [false; 3];
+        self.warnings.clear();
+    }
+
+    pub fn history(&self) -> usize {
+        self.history
+    }
+
+    pub fn initial(&self) -> Option<&DiagnosticsSample> {
+        self.initial_opt.as_ref()
+    }
+
+    pub fn latest(&self) -> Option<&DiagnosticsSample> {
+        self.latest_opt.as_ref()
+    }
+
+    pub fn record(
+        &mut self,
+        sample: DiagnosticsSample,
+    ) -> &[DiagnosticsWarning] {
+        let warnings_len = self.warnings.len();
+        let initial = *self.initial_opt.get_or_insert(sample);
+        for (i, quantity) in DiagnosticsQuantity::ALL.iter().enumerate() {
+            let drift = sample.drift(&initial, *quantity);
+            self.drift_max[i] = self.drift_max[i].max(drift);
+            self.drift_sum[i] += drift;
+            self.drift_sum_sq[i] += drift * drift;
+            if let Some(tolerance) = &self.tolerance_opt {
+                let exceeded = drift > tolerance.get(*quantity);
+                if exceeded && !self.exceeded[i] {
+                    self.warnings.push(DiagnosticsWarning {
+                        time: sample.time,
+                        quantity: *quantity,
+                        drift,
+                        tolerance: tolerance.get(*quantity),
+                    });
+                }
+                self.exceeded[i] = exceeded;
+            }
+        }
+        self.latest_opt = Some(sample);
+        self.sample_count += 1;
+        if self.history > 0 {
+            if self.samples.len() == self.history {
+                self.samples.pop_front();
+            }
+            self.samples.push_back(sample);
+        }
+        &self.warnings[warnings_len..]
+    }
+
+    pub fn sample_count(&self) -> usize {
+        self.sample_count
+    }
+
+    pub fn samples(&self) -> impl Iterator<Item = &DiagnosticsSample> {
+        self.samples.iter()
+    }
+
+    pub fn set_history(&mut self, history: usize) {
+        self.history = history;
+        while self.samples.len() > history {
+            self.samples.pop_front();
+        }
+    }
+
+    pub fn set_tolerance(
+        &mut self,
+        tolerance_opt: Option<DiagnosticsTolerance>,
+    ) {
+        self.tolerance_opt = tolerance_opt;
+        self.exceeded = [false; 3];
+    }
+
+    pub fn statistics(&self, quantity: DiagnosticsQuantity) -> DriftStatistics {
+        let (initial, latest) = match (&self.initial_opt, &self.latest_opt) {
+            (Some(initial), Some(latest)) => (initial, latest),
+            _ => return DriftStatistics::default(),
+        };
+        let i = quantity as usize;
+        let n = self.sample_count as f64;
+        DriftStatistics {
+            current: latest.drift(initial, quantity),
+            max: self.drift_max[i],
+            mean: self.drift_sum[i] / n,
+            rms: (self.drift_sum_sq[i] / n).sqrt(),
+        }
+    }
+
+    pub fn warnings(&self) -> &[DiagnosticsWarning] {
+        &self.warnings
+    }
+}
@@ -0,0 +1,115 @@
+use crate::UID;
+use std::{
+    collections::VecDeque,
+    fmt::{self, Display, Formatter},
+};
+
+const DEFAULT_CAPACITY: usize = 1024;
+
+#[derive(Clone, Copy, Debug, Default, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Severity {
+    Debug,
+    #[default]
+    Warning,
+    Error,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FailureKind {
+    DePenetrateBisect,
+    DePenetratePlane,
+    DePenetrateNoPlane,
+    MissingContacts,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct LogEntry {
+    pub severity: Severity,
+    pub tick: u64,
+    pub uids: (UID, UID),
+    pub kind: FailureKind,
+}
+
+impl Display for LogEntry {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:?} tick {}: {:?}, uids: ({} {})",
+            self.severity, self.tick, self.kind, self.uids.0, self.uids.1
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct EventLog {
+    entries: VecDeque<LogEntry>,
+    capacity: usize,
+    min_severity: Severity,
+    tick: u64,
+}
+
+impl EventLog {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::new(),
+            capacity,
+            min_severity: Severity::default(),
+            tick: 0,
+        }
+    }
+
+    pub fn push(
+        &mut self,
+        severity: Severity,
+        uids: (UID, UID),
+        kind: FailureKind,
+    ) {
+        if severity < self.min_severity || self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(LogEntry {
+            severity,
+            tick: self.tick,
+            uids,
+            kind,
+        });
+    }
+
+    pub fn advance(&mut self) {
+        self.tick += 1;
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.tick = 0;
+    }
+
+    pub fn drain(&mut self) -> Vec<LogEntry> {
+        self.entries.drain(..).collect()
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &LogEntry> {
+        self.entries.iter()
+    }
+
+    pub fn min_severity(&self) -> Severity {
+        self.min_severity
+    }
+
+    pub fn set_min_severity(&mut self, min_severity: Severity) {
+        self.min_severity = min_severity;
+    }
+
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+}
+
+impl Default for EventLog {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
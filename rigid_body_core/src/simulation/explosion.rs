@@ -0,0 +1,96 @@
+use super::{collision_manager::CollisionManager, rigid_body::RigidBody};
+use crate::math::{geometry, vector::Vector3d};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Falloff {
+    Constant,
+    #[default]
+    Linear,
+    Quadratic,
+}
+
+impl Falloff {
+    pub fn factor(&self, dist: f64, radius: f64) -> f64 {
+        let linear = (1. - dist / radius).max(0.);
+        match self {
+            Self::Constant => 1.,
+            Self::Linear => linear,
+            Self::Quadratic => linear * linear,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Explosion {
+    pub radius: f64,
+    pub strength: f64,
+    pub falloff: Falloff,
+}
+
+impl Default for Explosion {
+    fn default() -> Self {
+        Self {
+            radius: 25.,
+            strength: 20.,
+            falloff: Falloff::Linear,
+        }
+    }
+}
+
+pub fn closest_surface_point(
+    rigid_body: &RigidBody,
+    position: &Vector3d,
+) -> (f64, Vector3d) {
+    if let Some((core, radius)) = rigid_body.rounded_core() {
+        let (_, core_position, dist_sq) =
+            geometry::raw_finite_line_closest_dist_sq(
+                position,
+                position,
+                &core.start,
+                &core.end,
+            );
+        let dist = dist_sq.sqrt();
+        let direction = if dist > 0. {
+            position.sub(&core_position).scale(1. / dist)
+        } else {
+            Vector3d::default()
+        };
+        return (dist - radius, core_position.add(&direction.scale(radius)));
+    }
+    let (dist, surface_position, _) =
+        CollisionManager::point_polyhedron_closest(
+            position,
+            rigid_body.polyhedron_world(),
+        );
+    (dist, surface_position)
+}
+
+pub fn impulse(
+    rigid_body: &RigidBody,
+    center: &Vector3d,
+    radius: f64,
+    strength: f64,
+    falloff: Falloff,
+) -> Option<(Vector3d, Vector3d)> {
+    if rigid_body.is_immovable() {
+        return None;
+    }
+    let (dist, surface_position) = closest_surface_point(rigid_body, center);
+    let dist = dist.max(0.);
+    if dist > radius {
+        return None;
+    }
+    let mut direction = surface_position.sub(center);
+    if direction.is_zero() {
+        direction = rigid_body.position.sub(center);
+    }
+    if direction.is_zero() {
+        return None;
+    }
+    Some((
+        direction
+            .normal()
+            .scale(strength * falloff.factor(dist, radius)),
+        surface_position,
+    ))
+}
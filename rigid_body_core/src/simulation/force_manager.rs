@@ -1,40 +1,471 @@
 #![allow(dead_code)]
-use super::rigid_body::RigidBody;
-use crate::math::vector::Vector3d;
+use super::{
+    barnes_hut::{self, Mass, Octree},
+    rigid_body::RigidBody,
+};
+use crate::{math::vector::Vector3d, UID};
+use std::f64::consts::PI;
 
 pub type ForceFn = fn(&RigidBody, &Vector3d) -> (Vector3d, Vector3d);
 
+pub trait ForceGenerator: Send + Sync {
+    fn box_clone(&self) -> Box<dyn ForceGenerator>;
+
+    fn force(&self, rigid_body: &RigidBody) -> (Vector3d, Vector3d);
+
+    fn potential_energy(&self, _rigid_body: &RigidBody) -> f64 {
+        0.
+    }
+
+    fn prepare(&mut self, _rigid_bodies: &[RigidBody]) {}
+
+    fn total_potential_energy(&self, rigid_bodies: &[RigidBody]) -> f64 {
+        rigid_bodies
+            .iter()
+            .filter(|rigid_body| !rigid_body.is_immovable())
+            .map(|rigid_body| self.potential_energy(rigid_body))
+            .sum()
+    }
+}
+
+impl Clone for Box<dyn ForceGenerator> {
+    fn clone(&self) -> Self {
+        self.box_clone()
+    }
+}
+
+impl ForceGenerator for ForceFn {
+    fn box_clone(&self) -> Box<dyn ForceGenerator> {
+        Box::new(*self)
+    }
+
+    fn force(&self, rigid_body: &RigidBody) -> (Vector3d, Vector3d) {
+        self(rigid_body, rigid_body.velocity())
+    }
+}
+
+#[derive(Clone, Default)]
 pub struct ForceManager {
-    force_functions: Vec<ForceFn>,
+    force_generators: Vec<Box<dyn ForceGenerator>>,
 }
 
 impl ForceManager {
-    pub fn new(force_functions: Vec<ForceFn>) -> Self {
-        Self { force_functions }
+    pub fn new(force_generators: Vec<Box<dyn ForceGenerator>>) -> Self {
+        Self { force_generators }
     }
 
-    pub fn resultant(&self, rigid_bodies: &mut [RigidBody]) {
+    pub fn add(&mut self, force_generator: Box<dyn ForceGenerator>) {
+        self.force_generators.push(force_generator);
+    }
+
+    pub fn potential_energy(&self, rigid_bodies: &[RigidBody]) -> f64 {
+        self.force_generators
+            .iter()
+            .map(|force_generator| {
+                force_generator.total_potential_energy(rigid_bodies)
+            })
+            .sum()
+    }
+
+    pub fn resultant(&mut self, rigid_bodies: &mut [RigidBody]) {
+        for force_generator in &mut self.force_generators {
+            force_generator.prepare(rigid_bodies);
+        }
+        let this = &*self;
+        #[cfg(feature = "parallel")]
+        crate::utility::parallel::for_each_mut(rigid_bodies, |rigid_body| {
+            if rigid_body.is_active() {
+                this.resultant_impl(rigid_body);
+            }
+        });
+        #[cfg(not(feature = "parallel"))]
         for rigid_body in rigid_bodies {
-            if rigid_body.is_immovable() {
+            if !rigid_body.is_active() {
                 continue;
             }
-            self.resultant_impl(rigid_body);
+            this.resultant_impl(rigid_body);
         }
     }
 
     fn resultant_impl(&self, rigid_body: &mut RigidBody) {
-        for force_function in &self.force_functions {
-            let (force, torque) =
-                force_function(rigid_body, rigid_body.velocity());
+        for force_generator in &self.force_generators {
+            let (force, torque) = force_generator.force(rigid_body);
             rigid_body.force.add_assign(&force);
             rigid_body.torque.add_assign(&torque);
         }
     }
 }
 
-impl Default for ForceManager {
-    fn default() -> Self {
-        Self::new(Vec::new())
+#[derive(Clone, Copy, Debug)]
+pub struct Gravity {
+    pub acceleration: Vector3d,
+}
+
+impl Gravity {
+    pub fn earth() -> Self {
+        Self {
+            acceleration: Vector3d::new(0., -9.81, 0.),
+        }
+    }
+}
+
+impl ForceGenerator for Gravity {
+    fn box_clone(&self) -> Box<dyn ForceGenerator> {
+        Box::new(*self)
+    }
+
+    fn force(&self, rigid_body: &RigidBody) -> (Vector3d, Vector3d) {
+        (
+            self.acceleration.scale(1. / rigid_body.mass_inv()),
+            Vector3d::default(),
+        )
+    }
+
+    fn potential_energy(&self, rigid_body: &RigidBody) -> f64 {
+        -self.acceleration.dot(&rigid_body.position) / rigid_body.mass_inv()
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub enum Targets {
+    #[default]
+    All,
+    Uids(Vec<UID>),
+}
+
+impl Targets {
+    pub fn contains(&self, uid: UID) -> bool {
+        match self {
+            Self::All => true,
+            Self::Uids(uids) => uids.contains(&uid),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum AttractorKind {
+    Point(Vector3d),
+    Line {
+        point: Vector3d,
+        direction: Vector3d,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub struct Attractor {
+    pub kind: AttractorKind,
+    pub strength: f64,
+    pub exponent: f64,
+    pub softening: f64,
+    pub targets: Targets,
+}
+
+impl Attractor {
+    pub fn line(point: &Vector3d, direction: &Vector3d, strength: f64) -> Self {
+        Self::new(
+            AttractorKind::Line {
+                point: *point,
+                direction: direction.normal(),
+            },
+            strength,
+        )
+    }
+
+    pub fn point(position: &Vector3d, strength: f64) -> Self {
+        Self::new(AttractorKind::Point(*position), strength)
+    }
+
+    fn new(kind: AttractorKind, strength: f64) -> Self {
+        Self {
+            kind,
+            strength,
+            exponent: 2.,
+            softening: 0.,
+            targets: Targets::All,
+        }
+    }
+
+    fn offset(&self, position: &Vector3d) -> Vector3d {
+        match &self.kind {
+            AttractorKind::Point(point) => point.sub(position),
+            AttractorKind::Line { point, direction } => {
+                let offset = point.sub(position);
+                offset.sub(&direction.scale(offset.dot(direction)))
+            }
+        }
+    }
+
+    fn dist(&self, offset: &Vector3d) -> f64 {
+        (offset.mag_sq() + self.softening * self.softening).sqrt()
+    }
+}
+
+impl ForceGenerator for Attractor {
+    fn box_clone(&self) -> Box<dyn ForceGenerator> {
+        Box::new(self.clone())
+    }
+
+    fn force(&self, rigid_body: &RigidBody) -> (Vector3d, Vector3d) {
+        let offset = self.offset(&rigid_body.position);
+        let dist = self.dist(&offset);
+        if !self.targets.contains(rigid_body.uid()) || dist == 0. {
+            return (Vector3d::default(), Vector3d::default());
+        }
+        (
+            offset.scale(
+                self.strength
+                    / (rigid_body.mass_inv() * dist.powf(self.exponent + 1.)),
+            ),
+            Vector3d::default(),
+        )
+    }
+
+    fn potential_energy(&self, rigid_body: &RigidBody) -> f64 {
+        let dist = self.dist(&self.offset(&rigid_body.position));
+        if !self.targets.contains(rigid_body.uid()) || dist == 0. {
+            return 0.;
+        }
+        let potential = if (self.exponent - 1.).abs() < f64::EPSILON {
+            dist.ln()
+        } else {
+            dist.powf(1. - self.exponent) / (1. - self.exponent)
+        };
+        self.strength * potential / rigid_body.mass_inv()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AerodynamicDrag {
+    pub density: f64,
+    pub drag_coefficient: f64,
+    pub targets: Targets,
+}
+
+impl AerodynamicDrag {
+    pub fn new(density: f64, drag_coefficient: f64) -> Self {
+        Self {
+            density,
+            drag_coefficient,
+            targets: Targets::All,
+        }
+    }
+}
+
+impl ForceGenerator for AerodynamicDrag {
+    fn box_clone(&self) -> Box<dyn ForceGenerator> {
+        Box::new(self.clone())
+    }
+
+    fn force(&self, rigid_body: &RigidBody) -> (Vector3d, Vector3d) {
+        let mut force = Vector3d::default();
+        let mut torque = Vector3d::default();
+        if !self.targets.contains(rigid_body.uid()) {
+            return (force, torque);
+        }
+        let (vertices, edges, faces) = rigid_body.polyhedron_world().get_refs();
+        for face in faces {
+            let arm = face.center(vertices).sub(&rigid_body.position);
+            let velocity = rigid_body
+                .velocity()
+                .add(&rigid_body.angular_velocity().cross(&arm));
+            let projection = face.direction().dot(&velocity);
+            if projection <= 0. {
+                continue;
+            }
+            let face_force = velocity.scale(
+                -0.5 * self.density
+                    * self.drag_coefficient
+                    * face.area(vertices, edges)
+                    * projection,
+            );
+            force.add_assign(&face_force);
+            torque.add_assign(&arm.cross(&face_force));
+        }
+        (force, torque)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Damping {
+    pub linear: f64,
+    pub quadratic: f64,
+    pub angular: f64,
+    pub targets: Targets,
+}
+
+impl ForceGenerator for Damping {
+    fn box_clone(&self) -> Box<dyn ForceGenerator> {
+        Box::new(self.clone())
+    }
+
+    fn force(&self, rigid_body: &RigidBody) -> (Vector3d, Vector3d) {
+        if !self.targets.contains(rigid_body.uid()) {
+            return (Vector3d::default(), Vector3d::default());
+        }
+        let velocity = rigid_body.velocity();
+        (
+            velocity.scale(-self.linear - self.quadratic * velocity.mag()),
+            rigid_body.angular_velocity().scale(-self.angular),
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct FluidVolume {
+    pub position: Vector3d,
+    pub normal: Vector3d,
+    pub density: f64,
+    pub viscosity: f64,
+    pub gravity: Vector3d,
+    pub targets: Targets,
+}
+
+impl FluidVolume {
+    pub fn new(level: f64, density: f64, viscosity: f64) -> Self {
+        Self {
+            position: Vector3d::new(0., level, 0.),
+            normal: Vector3d::new(0., 1., 0.),
+            density,
+            viscosity,
+            gravity: Gravity::earth().acceleration,
+            targets: Targets::All,
+        }
+    }
+
+    pub fn submerged(&self, rigid_body: &RigidBody) -> (f64, Vector3d) {
+        rigid_body
+            .polyhedron_world()
+            .clipped_volume(&self.position, &self.normal.normal())
+    }
+}
+
+impl ForceGenerator for FluidVolume {
+    fn box_clone(&self) -> Box<dyn ForceGenerator> {
+        Box::new(self.clone())
+    }
+
+    fn force(&self, rigid_body: &RigidBody) -> (Vector3d, Vector3d) {
+        if !self.targets.contains(rigid_body.uid()) {
+            return (Vector3d::default(), Vector3d::default());
+        }
+        let (volume, centroid) = self.submerged(rigid_body);
+        if volume <= 0. {
+            return (Vector3d::default(), Vector3d::default());
+        }
+        let radius = (volume * 3. / (4. * PI)).cbrt();
+        let arm = centroid.sub(&rigid_body.position);
+        let angular_velocity = rigid_body.angular_velocity();
+        let velocity = rigid_body.velocity().add(&angular_velocity.cross(&arm));
+        let force = self
+            .gravity
+            .scale(-self.density * volume)
+            .add(&velocity.scale(-6. * PI * self.viscosity * radius));
+        let torque = arm.cross(&force).sub(
+            &angular_velocity.scale(8. * PI * self.viscosity * radius.powi(3)),
+        );
+        (force, torque)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct MutualGravity {
+    pub constant: f64,
+    pub softening: f64,
+    pub targets: Targets,
+    pub theta_opt: Option<f64>,
+    masses: Vec<Mass>,
+    octree_opt: Option<Octree>,
+}
+
+impl MutualGravity {
+    pub fn new(constant: f64, theta_opt: Option<f64>) -> Self {
+        Self {
+            constant,
+            softening: 0.,
+            targets: Targets::All,
+            theta_opt,
+            masses: Vec::new(),
+            octree_opt: None,
+        }
+    }
+
+    fn masses(&self, rigid_bodies: &[RigidBody]) -> Vec<Mass> {
+        rigid_bodies
+            .iter()
+            .filter(|rigid_body| {
+                !rigid_body.is_immovable()
+                    && self.targets.contains(rigid_body.uid())
+            })
+            .map(|rigid_body| Mass {
+                uid: rigid_body.uid(),
+                position: rigid_body.position,
+                mass: 1. / rigid_body.mass_inv(),
+            })
+            .collect()
+    }
+}
+
+impl ForceGenerator for MutualGravity {
+    fn box_clone(&self) -> Box<dyn ForceGenerator> {
+        Box::new(self.clone())
+    }
+
+    fn force(&self, rigid_body: &RigidBody) -> (Vector3d, Vector3d) {
+        let uid = rigid_body.uid();
+        if rigid_body.is_immovable() || !self.targets.contains(uid) {
+            return (Vector3d::default(), Vector3d::default());
+        }
+        let acceleration = match (&self.octree_opt, self.theta_opt) {
+            (Some(octree), Some(theta)) => octree.acceleration(
+                uid,
+                &rigid_body.position,
+                theta,
+                self.softening,
+            ),
+            _ => {
+                let mut ret = Vector3d::default();
+                for mass in self.masses.iter().filter(|mass| mass.uid != uid) {
+                    ret.add_assign(&barnes_hut::attraction(
+                        &rigid_body.position,
+                        &mass.position,
+                        mass.mass,
+                        self.softening,
+                    ));
+                }
+                ret
+            }
+        };
+        (
+            acceleration.scale(self.constant / rigid_body.mass_inv()),
+            Vector3d::default(),
+        )
+    }
+
+    fn prepare(&mut self, rigid_bodies: &[RigidBody]) {
+        let masses = self.masses(rigid_bodies);
+        if self.theta_opt.is_some() {
+            self.masses.clear();
+            self.octree_opt = Some(Octree::new(masses));
+        } else {
+            self.masses = masses;
+            self.octree_opt = None;
+        }
+    }
+
+    fn total_potential_energy(&self, rigid_bodies: &[RigidBody]) -> f64 {
+        let masses = self.masses(rigid_bodies);
+        let mut ret = 0.;
+        for (i, mass_1) in masses.iter().enumerate() {
+            for mass_2 in &masses[i + 1..] {
+                let dist = (mass_1.position.dist_sq(&mass_2.position)
+                    + self.softening * self.softening)
+                    .sqrt();
+                if dist > 0. {
+                    ret -= self.constant * mass_1.mass * mass_2.mass / dist;
+                }
+            }
+        }
+        ret
     }
 }
 
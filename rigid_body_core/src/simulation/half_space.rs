@@ -0,0 +1,104 @@
+use super::rigid_body::BoundingBox;
+use crate::{
+    error::{Error, Result},
+    math::vector::Vector3d,
+    UID,
+};
+
+#[derive(Clone, Copy, Debug)]
+pub struct HalfSpace {
+    pub(super) uid: UID,
+    position: Vector3d,
+    normal: Vector3d,
+    velocity: Vector3d,
+    angular_velocity: Vector3d,
+    pivot: Vector3d,
+}
+
+impl HalfSpace {
+    pub fn new(position: &Vector3d, normal: &Vector3d) -> Result<Self> {
+        let mag = normal.mag();
+        if !mag.is_finite() || mag < f64::EPSILON {
+            return Err(Error::DegenerateGeometry(format!(
+                "Half space normal must be non-zero and finite ({}, {}, {}).",
+                normal[0], normal[1], normal[2]
+            )));
+        }
+        Ok(Self {
+            uid: 0,
+            position: *position,
+            normal: normal.scale(1. / mag),
+            velocity: Vector3d::default(),
+            angular_velocity: Vector3d::default(),
+            pivot: *position,
+        })
+    }
+
+    pub fn bounding_box_dist(&self, bounding_box: &BoundingBox) -> f64 {
+        let mut support = Vector3d::default();
+        for axis in 0..3 {
+            support[axis] = if self.normal[axis] > 0. {
+                bounding_box[0][axis]
+            } else {
+                bounding_box[1][axis]
+            };
+        }
+        self.signed_dist(&support)
+    }
+
+    pub fn normal(&self) -> &Vector3d {
+        &self.normal
+    }
+
+    pub fn position(&self) -> &Vector3d {
+        &self.position
+    }
+
+    pub fn set_motion(
+        &mut self,
+        velocity: &Vector3d,
+        angular_velocity: &Vector3d,
+        pivot: &Vector3d,
+    ) {
+        self.velocity = *velocity;
+        self.angular_velocity = *angular_velocity;
+        self.pivot = *pivot;
+    }
+
+    pub fn set_pose(&mut self, position: &Vector3d, normal: &Vector3d) {
+        self.position = *position;
+        self.normal = normal.normal();
+    }
+
+    pub fn signed_dist(&self, position: &Vector3d) -> f64 {
+        position.sub(&self.position).dot(&self.normal)
+    }
+
+    pub fn tangents(&self) -> (Vector3d, Vector3d) {
+        let axis = if self.normal[0].abs() < 0.9 {
+            Vector3d::new(1., 0., 0.)
+        } else {
+            Vector3d::new(0., 1., 0.)
+        };
+        let tangent_1 = self.normal.cross(&axis).normal();
+        let tangent_2 = self.normal.cross(&tangent_1);
+        (tangent_1, tangent_2)
+    }
+
+    pub fn uid(&self) -> UID {
+        self.uid
+    }
+
+    pub fn velocity_at(&self, position: &Vector3d) -> Vector3d {
+        self.angular_velocity
+            .cross(&position.sub(&self.pivot))
+            .add(&self.velocity)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct HalfSpaceContact {
+    pub rigid_body: usize,
+    pub position: Vector3d,
+    pub half_space: usize,
+}
@@ -0,0 +1,87 @@
+use super::rigid_body::RigidBody;
+use crate::{
+    math::{matrix::Matrix3x3, vector::Vector3d},
+    UID,
+};
+use std::fmt::{self, Display, Formatter};
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum HealthPolicy {
+    Panic,
+    Freeze,
+    Reset,
+    #[default]
+    Error,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum HealthStage {
+    External,
+    Forces,
+    Integration,
+    Narrowphase,
+    Periodic,
+    HalfSpace,
+    Solver,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum HealthQuantity {
+    Position,
+    Quarternion,
+    Momentum,
+    AngularMomentum,
+    Inertia,
+    Force,
+    Torque,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HealthIssue {
+    pub uid: UID,
+    pub time: f64,
+    pub stage: HealthStage,
+    pub quantity: HealthQuantity,
+}
+
+impl Display for HealthIssue {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "rigid body {} has a non-finite {:?} after stage {:?} at time {}",
+            self.uid, self.quantity, self.stage, self.time
+        )
+    }
+}
+
+pub fn check(rigid_body: &RigidBody) -> Option<HealthQuantity> {
+    let quarternion = &rigid_body.quarternion;
+    if !is_finite_vector(&rigid_body.position) {
+        Some(HealthQuantity::Position)
+    } else if !quarternion.s().is_finite() || !is_finite_vector(quarternion.v())
+    {
+        Some(HealthQuantity::Quarternion)
+    } else if !is_finite_vector(&rigid_body.momentum) {
+        Some(HealthQuantity::Momentum)
+    } else if !is_finite_vector(&rigid_body.angular_momentum) {
+        Some(HealthQuantity::AngularMomentum)
+    } else if !is_finite_matrix(rigid_body.inertia())
+        || !is_finite_matrix(rigid_body.inertia_inv())
+    {
+        Some(HealthQuantity::Inertia)
+    } else if !is_finite_vector(&rigid_body.force) {
+        Some(HealthQuantity::Force)
+    } else if !is_finite_vector(&rigid_body.torque) {
+        Some(HealthQuantity::Torque)
+    } else {
+        None
+    }
+}
+
+fn is_finite_matrix(matrix: &Matrix3x3) -> bool {
+    (0..3).all(|i| (0..3).all(|j| matrix[i][j].is_finite()))
+}
+
+fn is_finite_vector(vector: &Vector3d) -> bool {
+    (0..3).all(|i| vector[i].is_finite())
+}
@@ -0,0 +1,172 @@
+use super::{collision_table::CollisionTable, rigid_body::RigidBody};
+
+#[derive(Clone, Copy, Debug)]
+pub struct SleepSettings {
+    pub linear_velocity: f64,
+    pub angular_velocity: f64,
+    pub time: f64,
+}
+
+impl Default for SleepSettings {
+    fn default() -> Self {
+        Self {
+            linear_velocity: 0.05,
+            angular_velocity: 0.05,
+            time: 0.5,
+        }
+    }
+}
+
+impl SleepSettings {
+    pub fn is_below_threshold(&self, rigid_body: &RigidBody) -> bool {
+        rigid_body.velocity().mag_sq()
+            < self.linear_velocity * self.linear_velocity
+            && rigid_body.angular_velocity().mag_sq()
+                < self.angular_velocity * self.angular_velocity
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Island {
+    rigid_body_indices: Vec<usize>,
+    pairs: Vec<(usize, usize)>,
+}
+
+impl Island {
+    pub fn pairs(&self) -> &[(usize, usize)] {
+        &self.pairs
+    }
+
+    pub fn rigid_body_indices(&self) -> &[usize] {
+        &self.rigid_body_indices
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Islands {
+    islands: Vec<Island>,
+    rigid_body_islands: Vec<Option<usize>>,
+}
+
+impl Islands {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_pairs(
+        pairs: &[(usize, usize)],
+        rigid_bodies: &[RigidBody],
+    ) -> Self {
+        let mut parents = (0..rigid_bodies.len()).collect::<Vec<usize>>();
+        for (i, j) in pairs {
+            if !rigid_bodies[*i].is_immovable()
+                && !rigid_bodies[*j].is_immovable()
+            {
+                let root_i = find(&mut parents, *i);
+                let root_j = find(&mut parents, *j);
+                parents[root_i.max(root_j)] = root_i.min(root_j);
+            }
+        }
+
+        let mut ret = Self {
+            islands: Vec::new(),
+            rigid_body_islands: vec![None; rigid_bodies.len()],
+        };
+        for (i, rigid_body) in rigid_bodies.iter().enumerate() {
+            if rigid_body.is_immovable() {
+                continue;
+            }
+            let root = find(&mut parents, i);
+            let island_index = match ret.rigid_body_islands[root] {
+                Some(island_index) => island_index,
+                None => {
+                    ret.islands.push(Island::default());
+                    ret.islands.len() - 1
+                }
+            };
+            ret.rigid_body_islands[root] = Some(island_index);
+            ret.rigid_body_islands[i] = Some(island_index);
+            ret.islands[island_index].rigid_body_indices.push(i);
+        }
+        for pair in pairs {
+            let island_index = if rigid_bodies[pair.0].is_immovable() {
+                ret.rigid_body_islands[pair.1]
+            } else {
+                ret.rigid_body_islands[pair.0]
+            };
+            if let Some(island_index) = island_index {
+                ret.islands[island_index].pairs.push(*pair);
+            }
+        }
+        ret
+    }
+
+    pub fn generate(
+        &mut self,
+        collision_table: &CollisionTable,
+        rigid_bodies: &[RigidBody],
+    ) {
+        let mut pairs = Vec::new();
+        for j in 0..rigid_bodies.len() {
+            for i in j + 1..rigid_bodies.len() {
+                if collision_table.get(i, j).colliding {
+                    pairs.push((i, j));
+                }
+            }
+        }
+        *self = Self::from_pairs(&pairs, rigid_bodies);
+    }
+
+    pub fn island_index(&self, rigid_body_index: usize) -> Option<usize> {
+        self.rigid_body_islands
+            .get(rigid_body_index)
+            .copied()
+            .flatten()
+    }
+
+    pub fn islands(&self) -> &[Island] {
+        &self.islands
+    }
+
+    pub fn update_sleep(
+        &self,
+        sleep_settings: &SleepSettings,
+        below_threshold: &[bool],
+        delta_t: f64,
+        rigid_bodies: &mut [RigidBody],
+    ) {
+        for island in &self.islands {
+            let mut ready = true;
+            for rigid_body_index in &island.rigid_body_indices {
+                let rigid_body = &mut rigid_bodies[*rigid_body_index];
+                if rigid_body.is_sleeping() {
+                    continue;
+                }
+                if below_threshold[*rigid_body_index]
+                    && sleep_settings.is_below_threshold(rigid_body)
+                {
+                    rigid_body.sleep_time += delta_t;
+                } else {
+                    rigid_body.sleep_time = 0.;
+                }
+                ready &= rigid_body.sleep_time >= sleep_settings.time;
+            }
+            for rigid_body_index in &island.rigid_body_indices {
+                let rigid_body = &mut rigid_bodies[*rigid_body_index];
+                if ready {
+                    rigid_body.sleep();
+                } else if rigid_body.is_sleeping() {
+                    rigid_body.wake();
+                }
+            }
+        }
+    }
+}
+
+fn find(parents: &mut [usize], mut index: usize) -> usize {
+    while parents[index] != index {
+        parents[index] = parents[parents[index]];
+        index = parents[index];
+    }
+    index
+}
@@ -1,43 +1,210 @@
+mod barnes_hut;
 pub mod bounding_box;
-mod bounding_box_collision_manager;
+mod broadphase;
 mod collision_manager;
 mod collision_table;
-mod force_manager;
+pub mod diagnostics;
+pub mod event_log;
+pub mod explosion;
+pub mod force_manager;
+pub mod half_space;
+pub mod health;
+pub mod island;
 mod ode;
+pub mod profiler;
 pub mod rigid_body;
+pub mod shape;
+mod simulation_config;
 
-use crate::math::vector::Vector3d;
-use bounding_box::BoundingBox;
+#[cfg(test)]
+mod test;
+
+use crate::{
+    error::{Error, Result},
+    math::{vector::Vector3d, Quarternion},
+    utility::fnv::FnvHasher,
+    UID,
+};
+use bounding_box::{BoundingBox, BoundingBoxMotion, PeriodicDomain};
+pub use broadphase::BroadphaseKind;
 use collision_manager::CollisionManager;
 pub use collision_manager::SeparatingPlane;
 pub use collision_table::Contact;
-use force_manager::ForceManager;
+use diagnostics::{Diagnostics, DiagnosticsSample};
+use event_log::{EventLog, Severity};
+use explosion::Falloff;
+use force_manager::{ForceGenerator, ForceManager};
+use half_space::HalfSpace;
+use health::{HealthIssue, HealthPolicy, HealthStage};
+use island::{Islands, SleepSettings};
+pub use ode::Integrator;
+use profiler::{ProfileStage, Profiler};
 use rigid_body::RigidBody;
+pub use simulation_config::SimulationConfig;
+use std::mem;
 
-#[derive(Default)]
+#[derive(Clone)]
 pub struct Simulation {
     pub collision_manager: CollisionManager,
     rigid_bodies: Vec<RigidBody>,
     initial_rigid_bodies: Vec<RigidBody>,
     force_manager: ForceManager,
     bounding_box: BoundingBox,
+    periodic_domain_opt: Option<PeriodicDomain>,
+    half_spaces: Vec<HalfSpace>,
+    islands: Islands,
+    integrator: Integrator,
+    solver_iterations: usize,
+    sleep_settings_opt: Option<SleepSettings>,
+    diagnostics_opt: Option<Diagnostics>,
+    health_policy: HealthPolicy,
+    health_report: Vec<HealthIssue>,
+    valid_poses: Vec<(Vector3d, Quarternion)>,
+    time: f64,
     generated: bool,
+    next_uid: UID,
 }
 
 impl Simulation {
     pub fn new() -> Self {
-        Self::default()
+        Self::with_config(SimulationConfig::default())
+    }
+
+    pub fn with_config(config: SimulationConfig) -> Self {
+        let mut next_uid = 0;
+        let mut bounding_box = BoundingBox::default();
+        bounding_box.uid = new_uid(&mut next_uid);
+        Self {
+            collision_manager: CollisionManager::with_config(config),
+            rigid_bodies: Vec::new(),
+            initial_rigid_bodies: Vec::new(),
+            force_manager: ForceManager::default(),
+            bounding_box,
+            periodic_domain_opt: None,
+            half_spaces: Vec::new(),
+            islands: Islands::default(),
+            integrator: Integrator::default(),
+            solver_iterations: 1,
+            sleep_settings_opt: None,
+            diagnostics_opt: None,
+            health_policy: HealthPolicy::default(),
+            health_report: Vec::new(),
+            valid_poses: Vec::new(),
+            time: 0.,
+            generated: false,
+            next_uid,
+        }
+    }
+
+    pub fn add_force_generator(
+        &mut self,
+        force_generator: Box<dyn ForceGenerator>,
+    ) {
+        self.force_manager.add(force_generator);
     }
 
-    pub fn add_rigid_body(&mut self, rigid_body: RigidBody) {
+    pub fn add_half_space(&mut self, mut half_space: HalfSpace) -> UID {
+        half_space.uid = new_uid(&mut self.next_uid);
+        self.half_spaces.push(half_space);
+        half_space.uid
+    }
+
+    pub fn add_rigid_body(&mut self, mut rigid_body: RigidBody) -> UID {
+        rigid_body.uid = new_uid(&mut self.next_uid);
+        let uid = rigid_body.uid;
         self.initial_rigid_bodies.push(rigid_body.clone());
         self.rigid_bodies.push(rigid_body);
         self.generated = false;
+        uid
+    }
+
+    pub fn explode(
+        &mut self,
+        center: &Vector3d,
+        radius: f64,
+        strength: f64,
+        falloff: Falloff,
+    ) {
+        if self.generated {
+            self.collision_manager.update_broadphase(&self.rigid_bodies);
+        } else {
+            self.collision_manager.generate(&self.rigid_bodies);
+            self.generated = true;
+        }
+        let extent = Vector3d::new(radius, radius, radius);
+        for i in self.collision_manager.query_bounding_box(
+            &[center.sub(&extent), center.add(&extent)],
+            &self.rigid_bodies,
+        ) {
+            let rigid_body = &mut self.rigid_bodies[i];
+            if let Some((impulse, position)) = explosion::impulse(
+                rigid_body, center, radius, strength, falloff,
+            ) {
+                rigid_body.apply_impulse_at_point(&impulse, &position);
+            }
+        }
     }
 
     pub fn reset(&mut self) {
         self.rigid_bodies = self.initial_rigid_bodies.clone();
+        self.bounding_box.reset(&mut self.half_spaces);
+        self.time = 0.;
         self.generated = false;
+        self.health_report.clear();
+        self.valid_poses.clear();
+        self.event_log_mut().clear();
+        if let Some(diagnostics) = &mut self.diagnostics_opt {
+            diagnostics.clear();
+        }
+        self.record_diagnostics();
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        let mut simulation = self.clone();
+        simulation.collision_manager.profiler_opt = None;
+        simulation.diagnostics_opt = None;
+        simulation.event_log_mut().clear();
+        Snapshot { simulation }
+    }
+
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        let profiler_opt = self.collision_manager.profiler_opt.take();
+        let diagnostics_opt = self.diagnostics_opt.take();
+        let event_log = mem::take(self.event_log_mut());
+        *self = snapshot.simulation.clone();
+        self.collision_manager.profiler_opt = profiler_opt;
+        self.diagnostics_opt = diagnostics_opt;
+        *self.event_log_mut() = event_log;
+    }
+
+    pub fn state_hash(&self) -> u64 {
+        let mut hasher = FnvHasher::new();
+        for rigid_body in &self.rigid_bodies {
+            for vector in &[
+                &rigid_body.position,
+                rigid_body.quarternion.v(),
+                &rigid_body.momentum,
+                &rigid_body.angular_momentum,
+            ] {
+                for value in vector.iter() {
+                    hasher.write_f64(*value);
+                }
+            }
+            hasher.write_f64(rigid_body.quarternion.s());
+        }
+        hasher.finish()
+    }
+
+    pub fn periodic_domain(&self) -> Option<&PeriodicDomain> {
+        self.periodic_domain_opt.as_ref()
+    }
+
+    pub fn profiler(&self) -> Option<&Profiler> {
+        self.collision_manager.profiler_opt.as_ref()
+    }
+
+    pub fn profiler_mut(&mut self) -> Option<&mut Profiler> {
+        self.collision_manager.profiler_opt.as_mut()
     }
 
     pub fn rigid_bodies(&self) -> &[RigidBody] {
@@ -52,35 +219,325 @@ impl Simulation {
         &self.bounding_box
     }
 
+    pub fn config(&self) -> &SimulationConfig {
+        self.collision_manager.config()
+    }
+
+    pub fn diagnostics(&self) -> Option<&Diagnostics> {
+        self.diagnostics_opt.as_ref()
+    }
+
+    pub fn event_log(&self) -> &EventLog {
+        self.collision_manager.event_log()
+    }
+
+    pub fn event_log_mut(&mut self) -> &mut EventLog {
+        self.collision_manager.event_log_mut()
+    }
+
+    pub fn health_policy(&self) -> HealthPolicy {
+        self.health_policy
+    }
+
+    pub fn health_report(&self) -> &[HealthIssue] {
+        &self.health_report
+    }
+
+    pub fn half_spaces(&self) -> &[HalfSpace] {
+        &self.half_spaces
+    }
+
+    pub fn integrator(&self) -> Integrator {
+        self.integrator
+    }
+
+    pub fn islands(&self) -> &Islands {
+        &self.islands
+    }
+
     pub fn set_bounding_box(
         &mut self,
         dimensions_opt: &Option<(&Vector3d, &Vector3d)>,
     ) {
+        if dimensions_opt.is_some() {
+            self.periodic_domain_opt = None;
+        }
         self.bounding_box.set(
             dimensions_opt,
-            &mut self.initial_rigid_bodies,
-            &mut self.rigid_bodies,
+            &mut self.half_spaces,
+            &mut self.next_uid,
         );
     }
 
+    pub fn set_bounding_box_motion(
+        &mut self,
+        motion_opt: Option<Box<dyn BoundingBoxMotion>>,
+    ) {
+        self.bounding_box
+            .set_motion(motion_opt, &mut self.half_spaces);
+    }
+
+    pub fn set_broadphase(
+        &mut self,
+        broadphase_kind: BroadphaseKind,
+    ) -> Result<()> {
+        if let BroadphaseKind::SpatialHashGrid { cell_size } = broadphase_kind {
+            if cell_size <= 0. || !cell_size.is_finite() {
+                return Err(Error::InvalidValue(format!(
+                    "Invalid cell size {}.",
+                    cell_size
+                )));
+            }
+        }
+        self.collision_manager.set_broadphase(broadphase_kind);
+        self.generated = false;
+        Ok(())
+    }
+
+    pub fn set_config(&mut self, config: SimulationConfig) {
+        self.collision_manager.set_config(config);
+        self.generated = false;
+    }
+
     pub fn set_debug(&mut self, set: bool) {
-        self.collision_manager.debug = set;
+        self.event_log_mut().set_min_severity(if set {
+            Severity::Debug
+        } else {
+            Severity::Warning
+        });
+    }
+
+    pub fn set_diagnostics(&mut self, diagnostics_opt: Option<Diagnostics>) {
+        self.diagnostics_opt = diagnostics_opt;
+        if let Some(diagnostics) = &mut self.diagnostics_opt {
+            diagnostics.clear();
+        }
+        self.record_diagnostics();
+    }
+
+    pub fn set_sleep(&mut self, sleep_settings_opt: Option<SleepSettings>) {
+        if sleep_settings_opt.is_none() {
+            for rigid_body in &mut self.rigid_bodies {
+                rigid_body.wake();
+            }
+        }
+        self.sleep_settings_opt = sleep_settings_opt;
+    }
+
+    pub fn set_health_policy(&mut self, health_policy: HealthPolicy) {
+        self.health_policy = health_policy;
+    }
+
+    pub fn set_integrator(&mut self, integrator: Integrator) {
+        self.integrator = integrator;
+    }
+
+    pub fn set_periodic_domain(
+        &mut self,
+        periodic_domain_opt: Option<PeriodicDomain>,
+    ) {
+        if periodic_domain_opt.is_some() {
+            self.bounding_box.set(
+                &None,
+                &mut self.half_spaces,
+                &mut self.next_uid,
+            );
+        }
+        self.periodic_domain_opt = periodic_domain_opt;
+    }
+
+    pub fn set_profiler(&mut self, profiler_opt: Option<Profiler>) {
+        self.collision_manager.profiler_opt = profiler_opt;
+    }
+
+    pub fn set_solver_iterations(&mut self, solver_iterations: usize) {
+        self.solver_iterations = solver_iterations.max(1);
+    }
+
+    pub fn tick(&mut self, delta_t: f64) -> Result<()> {
+        let result = self.step(delta_t);
+        if let Some(profiler) = self.profiler_mut() {
+            profiler.end_frame();
+        }
+        result
+    }
+
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
+    fn check_health(&mut self, stage: HealthStage) -> Result<()> {
+        for i in 0..self.rigid_bodies.len() {
+            let rigid_body = &self.rigid_bodies[i];
+            let quantity = match health::check(rigid_body) {
+                Some(quantity) => quantity,
+                None => continue,
+            };
+            let issue = HealthIssue {
+                uid: rigid_body.uid(),
+                time: self.time,
+                stage,
+                quantity,
+            };
+            self.health_report.push(issue);
+            let initial_opt = self
+                .initial_rigid_bodies
+                .iter()
+                .find(|initial| initial.uid() == issue.uid);
+            match self.health_policy {
+                HealthPolicy::Panic => panic!("{}", issue),
+                HealthPolicy::Error => return Err(Error::Health(issue)),
+                HealthPolicy::Freeze => {
+                    let (position, quarternion) =
+                        match (self.valid_poses.get(i), initial_opt) {
+                            (Some(pose), _) => *pose,
+                            (None, Some(initial)) => {
+                                (initial.position, initial.quarternion)
+                            }
+                            (None, None) => (
+                                Vector3d::default(),
+                                Quarternion::new(1., &Vector3d::default()),
+                            ),
+                        };
+                    self.rigid_bodies[i].freeze(&position, &quarternion);
+                }
+                HealthPolicy::Reset => {
+                    if let Some(initial) = initial_opt {
+                        self.rigid_bodies[i] = initial.clone();
+                    } else {
+                        return Err(Error::Health(issue));
+                    }
+                }
+            }
+            self.generated = false;
+        }
+        Ok(())
+    }
+
+    fn profile_begin(&mut self, stage: ProfileStage) {
+        profiler::begin(&mut self.collision_manager.profiler_opt, stage);
+    }
+
+    fn profile_end(&mut self) {
+        profiler::end(&mut self.collision_manager.profiler_opt);
     }
 
-    pub fn tick(&mut self, delta_t: f64) {
+    fn record_diagnostics(&mut self) {
+        if let Some(diagnostics) = &mut self.diagnostics_opt {
+            diagnostics.record(DiagnosticsSample::new(
+                self.time,
+                self.force_manager.potential_energy(&self.rigid_bodies),
+                &self.rigid_bodies,
+            ));
+        }
+    }
+
+    fn step(&mut self, delta_t: f64) -> Result<()> {
+        self.health_report.clear();
+        self.check_health(HealthStage::External)?;
         if !self.generated {
+            self.profile_begin(ProfileStage::Broadphase);
             self.collision_manager.generate(&self.rigid_bodies);
+            self.profile_end();
             self.generated = true;
         }
+        self.profile_begin(ProfileStage::Forces);
         self.force_manager.resultant(&mut self.rigid_bodies);
-        ode::euler(delta_t, &mut self.rigid_bodies);
-        if let Some(bounding_box) = &self.bounding_box.inner_opt {
-            bounding_box.contain(&mut self.rigid_bodies);
+        self.profile_end();
+        self.check_health(HealthStage::Forces)?;
+        self.profile_begin(ProfileStage::Integration);
+        ode::integrate(self.integrator, delta_t, &mut self.rigid_bodies);
+        self.profile_end();
+        self.check_health(HealthStage::Integration)?;
+        self.profile_begin(ProfileStage::Containment);
+        if let Some(periodic_domain) = &self.periodic_domain_opt {
+            periodic_domain.wrap(&mut self.rigid_bodies);
         }
+        self.bounding_box.advance(
+            self.time + delta_t,
+            delta_t,
+            &mut self.half_spaces,
+        );
+        self.profile_end();
         self.collision_manager
             .collide_simple(&mut self.rigid_bodies);
+        self.check_health(HealthStage::Narrowphase)?;
+        self.profile_begin(ProfileStage::Containment);
+        if let Some(periodic_domain) = &self.periodic_domain_opt {
+            self.collision_manager
+                .collide_periodic(periodic_domain, &mut self.rigid_bodies);
+        }
+        self.profile_end();
+        if self.periodic_domain_opt.is_some() {
+            self.check_health(HealthStage::Periodic)?;
+        }
+        self.profile_begin(ProfileStage::Containment);
+        self.collision_manager
+            .collide_half_spaces(&self.half_spaces, &mut self.rigid_bodies);
+        self.bounding_box
+            .contain(self.collision_manager.config(), &mut self.rigid_bodies);
+        self.profile_end();
+        self.check_health(HealthStage::HalfSpace)?;
+        self.profile_begin(ProfileStage::ContactResolution);
+        self.islands.generate(
+            self.collision_manager.collision_table(),
+            &self.rigid_bodies,
+        );
+        let below_threshold_opt =
+            self.sleep_settings_opt.as_ref().map(|sleep_settings| {
+                self.rigid_bodies
+                    .iter()
+                    .map(|rigid_body| {
+                        sleep_settings.is_below_threshold(rigid_body)
+                    })
+                    .collect::<Vec<_>>()
+            });
+        self.collision_manager.solve_islands(
+            &self.islands,
+            self.solver_iterations,
+            &self.half_spaces,
+            &mut self.rigid_bodies,
+        );
+        if let (Some(sleep_settings), Some(below_threshold)) =
+            (&self.sleep_settings_opt, &below_threshold_opt)
+        {
+            self.islands.update_sleep(
+                sleep_settings,
+                below_threshold,
+                delta_t,
+                &mut self.rigid_bodies,
+            );
+        }
+        self.profile_end();
+        self.check_health(HealthStage::Solver)?;
         for rigid_body in &mut self.rigid_bodies {
             rigid_body.clear_forces();
         }
+        self.valid_poses = self
+            .rigid_bodies
+            .iter()
+            .map(|rigid_body| (rigid_body.position, rigid_body.quarternion))
+            .collect();
+        self.time += delta_t;
+        self.event_log_mut().advance();
+        self.record_diagnostics();
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+pub struct Snapshot {
+    simulation: Simulation,
+}
+
+fn new_uid(next_uid: &mut UID) -> UID {
+    let ret = *next_uid;
+    *next_uid += 1;
+    ret
+}
+
+impl Default for Simulation {
+    fn default() -> Self {
+        Self::new()
     }
 }
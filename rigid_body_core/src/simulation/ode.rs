@@ -1,23 +1,142 @@
 use super::rigid_body::RigidBody;
-use crate::math::Quarternion;
+use crate::math::{
+    matrix::Matrix3x3, matrix_vector, vector::Vector3d, Quarternion,
+};
 
-pub fn euler(delta_t: f64, rigid_bodies: &mut [RigidBody]) {
+const MIDPOINT_ITERATIONS: usize = 4;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Integrator {
+    #[default]
+    Euler,
+    ExponentialMap,
+    ImplicitGyroscopic,
+}
+
+pub fn integrate(
+    integrator: Integrator,
+    delta_t: f64,
+    rigid_bodies: &mut [RigidBody],
+) {
+    #[cfg(feature = "parallel")]
+    crate::utility::parallel::for_each_mut(rigid_bodies, |rigid_body| {
+        step(integrator, delta_t, rigid_body)
+    });
+    #[cfg(not(feature = "parallel"))]
     for rigid_body in rigid_bodies {
-        rigid_body
-            .position
-            .add_assign(&rigid_body.velocity().scale(delta_t));
-        rigid_body
-            .momentum
-            .add_assign(&rigid_body.force.scale(delta_t));
-
-        rigid_body.quarternion.add_assign(
-            &Quarternion::new(0., rigid_body.angular_velocity())
-                .mult(&rigid_body.quarternion)
-                .scale(0.5 * delta_t),
+        step(integrator, delta_t, rigid_body);
+    }
+}
+
+fn step(integrator: Integrator, delta_t: f64, rigid_body: &mut RigidBody) {
+    if rigid_body.is_sleeping() {
+        return;
+    }
+    rigid_body
+        .position
+        .add_assign(&rigid_body.velocity().scale(delta_t));
+    rigid_body
+        .momentum
+        .add_assign(&rigid_body.force.scale(delta_t));
+    match integrator {
+        Integrator::Euler => euler_step(delta_t, rigid_body),
+        Integrator::ExponentialMap => exponential_map_step(delta_t, rigid_body),
+        Integrator::ImplicitGyroscopic => {
+            implicit_gyroscopic_step(delta_t, rigid_body)
+        }
+    }
+}
+
+fn euler_step(delta_t: f64, rigid_body: &mut RigidBody) {
+    rigid_body.quarternion.add_assign(
+        &Quarternion::new(0., rigid_body.angular_velocity())
+            .mult(&rigid_body.quarternion)
+            .scale(0.5 * delta_t),
+    );
+    rigid_body
+        .angular_momentum
+        .add_assign(&rigid_body.torque.scale(delta_t));
+    rigid_body.update();
+}
+
+fn exponential_map_step(delta_t: f64, rigid_body: &mut RigidBody) {
+    rigid_body
+        .angular_momentum
+        .add_assign(&rigid_body.torque.scale(delta_t));
+    rigid_body.update_angular_velocity();
+    let mut angular_velocity = *rigid_body.angular_velocity();
+    for _ in 0..MIDPOINT_ITERATIONS {
+        let rotation = exponential(
+            &angular_velocity,
+            0.5 * delta_t,
+            &rigid_body.quarternion,
+        )
+        .to_matrix();
+        angular_velocity = matrix_vector::mult_3(
+            &rotation
+                .mult(rigid_body.inertia_body_inv())
+                .mult_t(&rotation),
+            &rigid_body.angular_momentum,
         );
-        rigid_body
-            .angular_momentum
-            .add_assign(&rigid_body.torque.scale(delta_t));
-        rigid_body.update();
     }
+    rigid_body.quarternion =
+        exponential(&angular_velocity, delta_t, &rigid_body.quarternion);
+    rigid_body.update_conserving_angular_momentum();
+}
+
+fn implicit_gyroscopic_step(delta_t: f64, rigid_body: &mut RigidBody) {
+    rigid_body
+        .angular_momentum
+        .add_assign(&rigid_body.torque.scale(delta_t));
+    rigid_body.update_angular_velocity();
+    let rotation = *rigid_body.rotation();
+    let inertia_body = rigid_body.inertia_body();
+    let angular_velocity_body =
+        matrix_vector::mult_3t(&rotation, rigid_body.angular_velocity());
+    let angular_momentum_body =
+        matrix_vector::mult_3(inertia_body, &angular_velocity_body);
+    let residual = angular_velocity_body
+        .cross(&angular_momentum_body)
+        .scale(delta_t);
+    let jacobian = inertia_body.add(
+        &skew(&angular_velocity_body)
+            .mult(inertia_body)
+            .add(&skew(&angular_momentum_body).scale(-1.))
+            .scale(delta_t),
+    );
+    let angular_velocity_body = match jacobian.inverse() {
+        Some(jacobian_inv) => angular_velocity_body
+            .sub(&matrix_vector::mult_3(&jacobian_inv, &residual)),
+        None => angular_velocity_body,
+    };
+    let angular_velocity =
+        matrix_vector::mult_3(&rotation, &angular_velocity_body);
+    rigid_body.quarternion =
+        exponential(&angular_velocity, delta_t, &rigid_body.quarternion);
+    rigid_body.update_conserving_angular_momentum();
+}
+
+fn exponential(
+    angular_velocity: &Vector3d,
+    delta_t: f64,
+    quarternion: &Quarternion,
+) -> Quarternion {
+    let angular_speed = angular_velocity.mag();
+    if angular_speed < f64::EPSILON {
+        return *quarternion;
+    }
+    let half_angle = 0.5 * angular_speed * delta_t;
+    Quarternion::new(
+        half_angle.cos(),
+        &angular_velocity.scale(half_angle.sin() / angular_speed),
+    )
+    .mult(quarternion)
+}
+
+fn skew(vector: &Vector3d) -> Matrix3x3 {
+    Matrix3x3::new(&[
+        [0., -vector[2], vector[1]],
+        [vector[2], 0., -vector[0]],
+        [-vector[1], vector[0], 0.],
+    ])
 }
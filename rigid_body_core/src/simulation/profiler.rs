@@ -0,0 +1,227 @@
+use std::{
+    collections::VecDeque,
+    fmt::Write,
+    time::{Duration, Instant},
+};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ProfileStage {
+    Forces,
+    Integration,
+    Containment,
+    Broadphase,
+    Narrowphase,
+    DePenetration,
+    ContactResolution,
+    Rendering,
+}
+
+impl ProfileStage {
+    pub const ALL: [Self; 8] = [
+        Self::Forces,
+        Self::Integration,
+        Self::Containment,
+        Self::Broadphase,
+        Self::Narrowphase,
+        Self::DePenetration,
+        Self::ContactResolution,
+        Self::Rendering,
+    ];
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ProfileCounter {
+    PairsTested,
+    ContactsFound,
+    BisectionIterations,
+}
+
+impl ProfileCounter {
+    pub const ALL: [Self; 3] = [
+        Self::PairsTested,
+        Self::ContactsFound,
+        Self::BisectionIterations,
+    ];
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ProfileFrame {
+    durations: [Duration; ProfileStage::ALL.len()],
+    counters: [u64; ProfileCounter::ALL.len()],
+}
+
+impl ProfileFrame {
+    pub fn count(&self, counter: ProfileCounter) -> u64 {
+        self.counters[counter as usize]
+    }
+
+    pub fn duration(&self, stage: ProfileStage) -> Duration {
+        self.durations[stage as usize]
+    }
+
+    pub fn total(&self) -> Duration {
+        self.durations.iter().sum()
+    }
+
+    fn add(&mut self, other: &ProfileFrame) {
+        for (sum, duration) in self.durations.iter_mut().zip(&other.durations) {
+            *sum += *duration;
+        }
+        for (sum, count) in self.counters.iter_mut().zip(&other.counters) {
+            *sum += count;
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Profiler {
+    current: ProfileFrame,
+    frames: VecDeque<ProfileFrame>,
+    window: usize,
+    summary_interval_opt: Option<usize>,
+    frames_since_summary: usize,
+    frame_ended: bool,
+    stack: Vec<(ProfileStage, Instant, Duration)>,
+}
+
+impl Profiler {
+    pub fn new(window: usize) -> Self {
+        Self {
+            current: ProfileFrame::default(),
+            frames: VecDeque::new(),
+            window: window.max(1),
+            summary_interval_opt: None,
+            frames_since_summary: 0,
+            frame_ended: false,
+            stack: Vec::new(),
+        }
+    }
+
+    pub fn amend_frame(&mut self) {
+        match self.frames.back_mut() {
+            Some(frame) if self.frame_ended => {
+                frame.add(&self.current);
+                self.current = ProfileFrame::default();
+            }
+            _ => self.end_frame(),
+        }
+        self.frame_ended = false;
+    }
+
+    pub fn average(&self) -> ProfileFrame {
+        let mut ret = ProfileFrame::default();
+        if self.frames.is_empty() {
+            return ret;
+        }
+        for frame in &self.frames {
+            ret.add(frame);
+        }
+        let n = self.frames.len();
+        for duration in &mut ret.durations {
+            *duration /= n as u32;
+        }
+        for count in &mut ret.counters {
+            *count /= n as u64;
+        }
+        ret
+    }
+
+    pub fn begin(&mut self, stage: ProfileStage) {
+        self.stack
+            .push((stage, Instant::now(), Duration::default()));
+    }
+
+    pub fn count(&mut self, counter: ProfileCounter, n: usize) {
+        self.current.counters[counter as usize] += n as u64;
+    }
+
+    pub fn current_frame(&self) -> &ProfileFrame {
+        &self.current
+    }
+
+    pub fn end(&mut self) {
+        if let Some((stage, start, nested)) = self.stack.pop() {
+            let elapsed = start.elapsed();
+            self.current.durations[stage as usize] +=
+                elapsed.saturating_sub(nested);
+            if let Some(parent) = self.stack.last_mut() {
+                parent.2 += elapsed;
+            }
+        }
+    }
+
+    pub fn end_frame(&mut self) {
+        if self.frames.len() == self.window {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(self.current);
+        self.current = ProfileFrame::default();
+        self.frame_ended = true;
+        self.frames_since_summary += 1;
+        if let Some(summary_interval) = self.summary_interval_opt {
+            if self.frames_since_summary >= summary_interval {
+                println!("{}", self.summary());
+                self.frames_since_summary = 0;
+            }
+        }
+    }
+
+    pub fn frames(&self) -> impl Iterator<Item = &ProfileFrame> {
+        self.frames.iter()
+    }
+
+    pub fn set_summary_interval(
+        &mut self,
+        summary_interval_opt: Option<usize>,
+    ) {
+        self.summary_interval_opt =
+            summary_interval_opt.filter(|interval| *interval > 0);
+    }
+
+    pub fn summary(&self) -> String {
+        let average = self.average();
+        let mut ret =
+            format!("profile, average of {} ticks:", self.frames.len());
+        for stage in &ProfileStage::ALL {
+            let _ = write!(
+                ret,
+                " {:?} {:.3}ms,",
+                stage,
+                average.duration(*stage).as_secs_f64() * 1000.
+            );
+        }
+        for counter in &ProfileCounter::ALL {
+            let _ = write!(ret, " {:?} {},", counter, average.count(*counter));
+        }
+        ret.pop();
+        ret
+    }
+}
+
+impl Default for Profiler {
+    fn default() -> Self {
+        Self::new(60)
+    }
+}
+
+pub(super) fn begin(profiler_opt: &mut Option<Profiler>, stage: ProfileStage) {
+    if let Some(profiler) = profiler_opt {
+        profiler.begin(stage);
+    }
+}
+
+pub(super) fn count(
+    profiler_opt: &mut Option<Profiler>,
+    counter: ProfileCounter,
+    n: usize,
+) {
+    if let Some(profiler) = profiler_opt {
+        profiler.count(counter, n);
+    }
+}
+
+pub(super) fn end(profiler_opt: &mut Option<Profiler>) {
+    if let Some(profiler) = profiler_opt {
+        profiler.end();
+    }
+}
@@ -1,5 +1,8 @@
+use super::shape::Shape;
 use crate::{
+    error::{Error, Result},
     math::{
+        geometry::{self, FiniteLine3d},
         matrix::Matrix3x3,
         matrix_vector, moment_of_inertia,
         polyhedron::{Edge, Polyhedron},
@@ -16,13 +19,14 @@ use std::{
 
 pub type BoundingBox = [Vector3d; 2];
 
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct RigidBody {
-    uid: UID,
+    pub(super) uid: UID,
     mass_inv: f64,
     inertia_body: Matrix3x3,
     inertia_body_inv: Matrix3x3,
     polyhedron_body: Polyhedron,
+    shape: Shape,
 
     pub position: Vector3d,
     pub quarternion: Quarternion,
@@ -39,6 +43,9 @@ pub struct RigidBody {
 
     pub force: Vector3d,
     pub torque: Vector3d,
+
+    sleeping: bool,
+    pub(super) sleep_time: f64,
 }
 
 impl RigidBody {
@@ -50,17 +57,29 @@ impl RigidBody {
         rotation: &Matrix3x3,
         momentum: &Vector3d,
         angular_momentum: &Vector3d,
-    ) -> Self {
+    ) -> Result<Self> {
+        if !mass_inv.is_finite() || mass_inv < 0. {
+            return Err(Error::InvalidValue(format!(
+                "Inverse mass {} is not a finite non-negative number.",
+                mass_inv
+            )));
+        }
+        let inertia_body = if inertia_body_inv.is_zero() {
+            *inertia_body_inv
+        } else {
+            inertia_body_inv.inverse().ok_or_else(|| {
+                Error::SingularInertia(
+                    "Inverse body inertia is not invertible.".into(),
+                )
+            })?
+        };
         let mut ret = Self {
-            uid: crate::get_new_uid(),
+            uid: 0,
             mass_inv,
-            inertia_body: if inertia_body_inv.is_zero() {
-                *inertia_body_inv
-            } else {
-                inertia_body_inv.inverse().expect("inertia_body")
-            },
+            inertia_body,
             inertia_body_inv: *inertia_body_inv,
             polyhedron_body: polyhedron.clone(),
+            shape: Shape::Polyhedron,
 
             position: *position,
             quarternion: Quarternion::from_matrix(&rotation),
@@ -77,10 +96,13 @@ impl RigidBody {
 
             force: Vector3d::default(),
             torque: Vector3d::default(),
+
+            sleeping: false,
+            sleep_time: 0.,
         };
         ret.update_rotation();
         ret.update();
-        ret
+        Ok(ret)
     }
 
     pub fn cuboid(
@@ -90,17 +112,60 @@ impl RigidBody {
         rotation: &Matrix3x3,
         momentum: &Vector3d,
         angular_momentum: &Vector3d,
-    ) -> Self {
+    ) -> Result<Self> {
         Self::new(
             mass_inv,
             &if mass_inv >= EPSILON {
                 moment_of_inertia::aligned_cuboid(dimensions, 1. / mass_inv)
                     .inverse()
-                    .expect("cuboid - inertia_body inverse")
+                    .ok_or_else(|| {
+                        Error::SingularInertia(format!(
+                            "Cuboid dimensions {}.",
+                            dimensions
+                        ))
+                    })?
             } else {
                 Matrix3x3::default()
             },
-            Polyhedron::cuboid(dimensions),
+            Polyhedron::cuboid(dimensions)?,
+            position,
+            rotation,
+            momentum,
+            angular_momentum,
+        )
+    }
+
+    pub fn capsule(
+        radius: f64,
+        height: f64,
+        mass_inv: f64,
+        position: &Vector3d,
+        rotation: &Matrix3x3,
+        momentum: &Vector3d,
+        angular_momentum: &Vector3d,
+    ) -> Result<Self> {
+        Self::from_shape_mass(
+            Shape::Capsule { radius, height },
+            mass_inv,
+            position,
+            rotation,
+            momentum,
+            angular_momentum,
+        )
+    }
+
+    pub fn cylinder(
+        radius: f64,
+        height: f64,
+        mass_inv: f64,
+        position: &Vector3d,
+        rotation: &Matrix3x3,
+        momentum: &Vector3d,
+        angular_momentum: &Vector3d,
+    ) -> Result<Self> {
+        Self::from_shape_mass(
+            Shape::Cylinder { radius, height },
+            mass_inv,
             position,
             rotation,
             momentum,
@@ -116,54 +181,143 @@ impl RigidBody {
         rotation: &Matrix3x3,
         momentum: &Vector3d,
         angular_momentum: &Vector3d,
-    ) -> Result<Self, String> {
-        let mesh_len = mesh.mesh_triangles.len();
-        let mut edges = Vec::with_capacity(mesh_len * 3);
-        let mut face_vertex_indices = Vec::with_capacity(mesh_len);
-        let mut edge_set = HashSet::<(usize, usize)>::default();
-
-        for mesh_triangle in &mesh.mesh_triangles {
-            let mut vertex_indices = Vec::with_capacity(3);
-            for vertex_index in &mesh_triangle.vertex_indices {
-                vertex_indices.push(*vertex_index);
+    ) -> Result<Self> {
+        Self::new(
+            mass_inv,
+            inertia_body_inv,
+            polyhedron_from_mesh(mesh)?,
+            position,
+            rotation,
+            momentum,
+            angular_momentum,
+        )
+    }
+
+    pub fn from_shape(
+        shape: Shape,
+        mass_inv: f64,
+        inertia_body_inv: &Matrix3x3,
+        position: &Vector3d,
+        rotation: &Matrix3x3,
+        momentum: &Vector3d,
+        angular_momentum: &Vector3d,
+    ) -> Result<Self> {
+        let polyhedron = match (shape.polyhedron()?, shape.mesh()) {
+            (Some(polyhedron), _) => polyhedron,
+            (None, Some(mesh)) => polyhedron_from_mesh(&mesh)?,
+            (None, None) => {
+                return Err(Error::InvalidValue(
+                    "Polyhedron shape requires a polyhedron.".into(),
+                ))
             }
-            let mut add_edge = |i: usize, j: usize| {
-                if edge_set.insert((i, j)) && edge_set.insert((j, i)) {
-                    edges.push(Edge::new(i, j, &mesh.vertices));
-                }
-            };
-            add_edge(vertex_indices[2], vertex_indices[1]);
-            add_edge(vertex_indices[1], vertex_indices[0]);
-            add_edge(vertex_indices[0], vertex_indices[2]);
-            face_vertex_indices.push(vertex_indices);
-        }
-        Ok(Self::new(
+        };
+        let mut ret = Self::new(
             mass_inv,
             inertia_body_inv,
-            Polyhedron::new(face_vertex_indices, edges, mesh.vertices.clone())?,
+            polyhedron,
             position,
             rotation,
             momentum,
             angular_momentum,
-        ))
+        )?;
+        ret.shape = shape;
+        ret.update_geometry();
+        Ok(ret)
+    }
+
+    pub fn sphere(
+        radius: f64,
+        mass_inv: f64,
+        position: &Vector3d,
+        rotation: &Matrix3x3,
+        momentum: &Vector3d,
+        angular_momentum: &Vector3d,
+    ) -> Result<Self> {
+        Self::from_shape_mass(
+            Shape::Sphere { radius },
+            mass_inv,
+            position,
+            rotation,
+            momentum,
+            angular_momentum,
+        )
     }
 
     pub fn angular_velocity(&self) -> &Vector3d {
         &self.angular_velocity
     }
 
+    pub fn apply_force_at_point(&mut self, force: &Vector3d, point: &Vector3d) {
+        if self.is_immovable() {
+            return;
+        }
+        self.force.add_assign(force);
+        self.torque
+            .add_assign(&point.sub(&self.position).cross(force));
+        self.wake();
+    }
+
+    pub fn apply_impulse_at_point(
+        &mut self,
+        impulse: &Vector3d,
+        point: &Vector3d,
+    ) {
+        if self.is_immovable() {
+            return;
+        }
+        self.momentum.add_assign(impulse);
+        self.angular_momentum
+            .add_assign(&point.sub(&self.position).cross(impulse));
+        self.update_velocity();
+        self.update_angular_velocity();
+        self.wake();
+    }
+
+    pub fn apply_torque_impulse(&mut self, impulse: &Vector3d) {
+        if self.is_immovable() {
+            return;
+        }
+        self.angular_momentum.add_assign(impulse);
+        self.update_angular_velocity();
+        self.wake();
+    }
+
     pub fn bounding_box(&self) -> &BoundingBox {
         &self.bounding_box
     }
 
+    pub fn cylinder_core(&self) -> Option<(FiniteLine3d, f64)> {
+        self.shape.cylinder_core(&self.position, &self.rotation)
+    }
+
+    pub fn inertia(&self) -> &Matrix3x3 {
+        &self.inertia
+    }
+
+    pub fn inertia_body(&self) -> &Matrix3x3 {
+        &self.inertia_body
+    }
+
+    pub fn inertia_body_inv(&self) -> &Matrix3x3 {
+        &self.inertia_body_inv
+    }
+
     pub fn inertia_inv(&self) -> &Matrix3x3 {
         &self.inertia_inv
     }
 
+    pub fn is_active(&self) -> bool {
+        !self.is_immovable() && !self.sleeping
+    }
+
     pub fn is_immovable(&self) -> bool {
         self.mass_inv < EPSILON
     }
 
+    pub fn is_sleeping(&self) -> bool {
+        self.sleeping
+    }
+
     pub fn ki_total(&self) -> f64 {
         self.ki_translational() + self.ki_rotational()
     }
@@ -208,10 +362,52 @@ impl RigidBody {
         &self.rotation
     }
 
+    pub fn rounded_core(&self) -> Option<(FiniteLine3d, f64)> {
+        self.shape.rounded_core(&self.position, &self.rotation)
+    }
+
+    pub fn shape(&self) -> &Shape {
+        &self.shape
+    }
+
+    pub fn support(&self, direction: &Vector3d) -> Vector3d {
+        if let Some((core, radius)) = self.rounded_core() {
+            let end = if core.end.sub(&core.start).dot(direction) > 0. {
+                core.end
+            } else {
+                core.start
+            };
+            let mag = direction.mag();
+            return if mag > 0. {
+                end.add(&direction.scale(radius / mag))
+            } else {
+                end
+            };
+        }
+        if let Some((core, radius)) = self.cylinder_core() {
+            return geometry::cylinder_support(&core, radius, direction);
+        }
+        let mut ret = self.position;
+        let mut max_dist = f64::MIN;
+        for vertex in self.polyhedron_world.vertices() {
+            let dist = vertex.dot(direction);
+            if dist > max_dist {
+                max_dist = dist;
+                ret = *vertex;
+            }
+        }
+        ret
+    }
+
     pub fn uid(&self) -> UID {
         self.uid
     }
 
+    pub fn wake(&mut self) {
+        self.sleeping = false;
+        self.sleep_time = 0.;
+    }
+
     pub fn update_angular_velocity(&mut self) {
         self.angular_velocity =
             matrix_vector::mult_3(&self.inertia_inv, &self.angular_momentum);
@@ -236,6 +432,24 @@ impl RigidBody {
             }
         }
         self.polyhedron_world.update();
+        if let Some((core, radius)) = self.rounded_core() {
+            for j in 0..3 {
+                self.bounding_box[0][j] =
+                    core.start[j].min(core.end[j]) - radius;
+                self.bounding_box[1][j] =
+                    core.start[j].max(core.end[j]) + radius;
+            }
+        }
+        if let Some((core, radius)) = self.cylinder_core() {
+            let axis = core.end.sub(&core.start).normal();
+            for j in 0..3 {
+                let extent = radius * (1. - axis[j] * axis[j]).max(0.).sqrt();
+                self.bounding_box[0][j] =
+                    core.start[j].min(core.end[j]) - extent;
+                self.bounding_box[1][j] =
+                    core.start[j].max(core.end[j]) + extent;
+            }
+        }
     }
 
     pub fn update_velocity(&mut self) {
@@ -251,17 +465,95 @@ impl RigidBody {
         self.torque = Vector3d::default();
     }
 
+    pub fn set_angular_velocity(&mut self, angular_velocity: &Vector3d) {
+        if self.is_immovable() {
+            return;
+        }
+        self.angular_velocity = *angular_velocity;
+        self.update_angular_momentum();
+        self.wake();
+    }
+
+    pub fn set_linear_velocity(&mut self, velocity: &Vector3d) {
+        if self.is_immovable() {
+            return;
+        }
+        self.momentum = velocity.scale(1. / self.mass_inv);
+        self.update_velocity();
+        self.wake();
+    }
+
+    pub(super) fn freeze(
+        &mut self,
+        position: &Vector3d,
+        quarternion: &Quarternion,
+    ) {
+        self.mass_inv = 0.;
+        self.inertia_body = Matrix3x3::default();
+        self.inertia_body_inv = Matrix3x3::default();
+        self.position = *position;
+        self.quarternion = *quarternion;
+        self.momentum = Vector3d::default();
+        self.angular_momentum = Vector3d::default();
+        self.clear_forces();
+        self.update_rotation();
+        self.update();
+    }
+
     pub fn set_rotation(&mut self, rotation: &Matrix3x3) {
         self.quarternion = Quarternion::from_matrix(&rotation);
         self.update_angular();
     }
 
+    pub(super) fn sleep(&mut self) {
+        self.sleeping = true;
+        self.momentum = Vector3d::default();
+        self.angular_momentum = Vector3d::default();
+        self.update_velocity();
+        self.update_angular_velocity();
+    }
+
     pub fn update(&mut self) {
         self.update_velocity();
         self.update_angular();
         self.update_geometry();
     }
 
+    pub fn update_conserving_angular_momentum(&mut self) {
+        self.update_velocity();
+        self.update_rotation();
+        self.update_angular_velocity();
+        self.update_geometry();
+    }
+
+    fn from_shape_mass(
+        shape: Shape,
+        mass_inv: f64,
+        position: &Vector3d,
+        rotation: &Matrix3x3,
+        momentum: &Vector3d,
+        angular_momentum: &Vector3d,
+    ) -> Result<Self> {
+        Self::from_shape(
+            shape,
+            mass_inv,
+            &if mass_inv >= f64::EPSILON {
+                shape
+                    .inertia_body(1. / mass_inv)
+                    .and_then(|inertia_body| inertia_body.inverse())
+                    .ok_or_else(|| {
+                        Error::SingularInertia(format!("{:?}.", shape))
+                    })?
+            } else {
+                Matrix3x3::default()
+            },
+            position,
+            rotation,
+            momentum,
+            angular_momentum,
+        )
+    }
+
     fn update_angular(&mut self) {
         self.update_angular_velocity();
         self.update_rotation();
@@ -310,3 +602,37 @@ pub fn ki_total(rigid_bodies: &[RigidBody]) -> f64 {
     }
     ret
 }
+
+pub(crate) fn polyhedron_from_mesh(mesh: &Mesh) -> Result<Polyhedron> {
+    let mesh_len = mesh.mesh_triangles.len();
+    let mut edges = Vec::with_capacity(mesh_len * 3);
+    let mut face_vertex_indices = Vec::with_capacity(mesh_len);
+    let mut edge_set = HashSet::<(usize, usize)>::default();
+
+    for mesh_triangle in &mesh.mesh_triangles {
+        if let Some(index) = mesh_triangle
+            .vertex_indices
+            .iter()
+            .find(|index| **index >= mesh.vertices.len())
+        {
+            return Err(Error::DegenerateGeometry(format!(
+                "Mesh vertex index {} out of range.",
+                index
+            )));
+        }
+        let mut vertex_indices = Vec::with_capacity(3);
+        for vertex_index in &mesh_triangle.vertex_indices {
+            vertex_indices.push(*vertex_index);
+        }
+        let mut add_edge = |i: usize, j: usize| {
+            if edge_set.insert((i, j)) && edge_set.insert((j, i)) {
+                edges.push(Edge::new(i, j, &mesh.vertices));
+            }
+        };
+        add_edge(vertex_indices[2], vertex_indices[1]);
+        add_edge(vertex_indices[1], vertex_indices[0]);
+        add_edge(vertex_indices[0], vertex_indices[2]);
+        face_vertex_indices.push(vertex_indices);
+    }
+    Polyhedron::new(face_vertex_indices, edges, mesh.vertices.clone())
+}
@@ -0,0 +1,118 @@
+use crate::{
+    error::Result,
+    math::{
+        geometry::FiniteLine3d, matrix::Matrix3x3, matrix_vector,
+        moment_of_inertia, polyhedron::Polyhedron, vector::Vector3d,
+    },
+    mesh::{polyhedron_meshes, Mesh},
+};
+
+const SEGMENTS: usize = 12;
+const SPHERE_SUBDIVISIONS: u8 = 1;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Shape {
+    #[default]
+    Polyhedron,
+    Sphere {
+        radius: f64,
+    },
+    Capsule {
+        radius: f64,
+        height: f64,
+    },
+    Cylinder {
+        radius: f64,
+        height: f64,
+    },
+}
+
+impl Shape {
+    pub fn cylinder_core(
+        &self,
+        position: &Vector3d,
+        rotation: &Matrix3x3,
+    ) -> Option<(FiniteLine3d, f64)> {
+        match self {
+            Self::Cylinder { radius, height } => {
+                let offset = matrix_vector::mult_3(
+                    rotation,
+                    &Vector3d::new(0., height / 2., 0.),
+                );
+                Some((
+                    FiniteLine3d::new(
+                        &position.add(&offset),
+                        &position.sub(&offset),
+                    ),
+                    *radius,
+                ))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn inertia_body(&self, mass: f64) -> Option<Matrix3x3> {
+        match self {
+            Self::Polyhedron => None,
+            Self::Sphere { radius } => {
+                Some(moment_of_inertia::solid_sphere(*radius, mass))
+            }
+            Self::Capsule { radius, height } => {
+                Some(moment_of_inertia::solid_capsule(*radius, *height, mass))
+            }
+            Self::Cylinder { radius, height } => {
+                Some(moment_of_inertia::solid_cylinder(*radius, *height, mass))
+            }
+        }
+    }
+
+    pub fn mesh(&self) -> Option<Mesh> {
+        match self {
+            Self::Polyhedron => None,
+            Self::Sphere { radius } => {
+                Some(polyhedron_meshes::icosphere(*radius, SPHERE_SUBDIVISIONS))
+            }
+            Self::Capsule { radius, height } => {
+                Some(polyhedron_meshes::capsule(*radius, *height, SEGMENTS))
+            }
+            Self::Cylinder { radius, height } => {
+                Some(polyhedron_meshes::cylinder(*radius, *height, SEGMENTS))
+            }
+        }
+    }
+
+    pub fn polyhedron(&self) -> Result<Option<Polyhedron>> {
+        Ok(match self {
+            Self::Cylinder { radius, height } => {
+                Some(Polyhedron::cylinder(*radius, *height, SEGMENTS)?)
+            }
+            _ => None,
+        })
+    }
+
+    pub fn rounded_core(
+        &self,
+        position: &Vector3d,
+        rotation: &Matrix3x3,
+    ) -> Option<(FiniteLine3d, f64)> {
+        match self {
+            Self::Sphere { radius } => {
+                Some((FiniteLine3d::new(position, position), *radius))
+            }
+            Self::Capsule { radius, height } => {
+                let offset = matrix_vector::mult_3(
+                    rotation,
+                    &Vector3d::new(0., height / 2., 0.),
+                );
+                Some((
+                    FiniteLine3d::new(
+                        &position.add(&offset),
+                        &position.sub(&offset),
+                    ),
+                    *radius,
+                ))
+            }
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,23 @@
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "scene",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(default)
+)]
+pub struct SimulationConfig {
+    pub collision_epsilon: f64,
+    pub coefficient_of_restitution: f64,
+    pub de_penetrate_iterations: usize,
+    pub delta_t: f64,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        Self {
+            collision_epsilon: 1e-3,
+            coefficient_of_restitution: 1.,
+            de_penetrate_iterations: 4,
+            delta_t: 1. / 60.,
+        }
+    }
+}
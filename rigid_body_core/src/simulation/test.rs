@@ -0,0 +1,1523 @@
+use super::{
+    bounding_box::{PeriodicDomain, Shake, Spin},
+    diagnostics::{Diagnostics, DiagnosticsQuantity, DiagnosticsTolerance},
+    event_log::{EventLog, FailureKind, LogEntry, Severity},
+    explosion::Falloff,
+    force_manager::{
+        AerodynamicDrag, Attractor, Damping, FluidVolume, ForceGenerator,
+        Gravity, MutualGravity, Targets,
+    },
+    half_space::HalfSpace,
+    health::{self, HealthIssue, HealthPolicy, HealthQuantity, HealthStage},
+    island::SleepSettings,
+    profiler::{ProfileCounter, ProfileStage, Profiler},
+    rigid_body::RigidBody,
+    BroadphaseKind, Integrator, Simulation, SimulationConfig,
+};
+use crate::{
+    config,
+    math::{
+        matrix::Matrix3x3, matrix_vector, moment_of_inertia, rotation_matrix,
+        vector::Vector3d,
+    },
+    mesh::obj_loader,
+    Error, RigidBodySimulationCore, RigidBodySimulationTrait,
+};
+use std::{f64::consts::FRAC_PI_2, io, time::Duration};
+
+fn colliding_pairs(simulation: &Simulation) -> Vec<(usize, usize)> {
+    let rigid_bodies = simulation.rigid_bodies();
+    let collision_table = simulation.collision_manager.collision_table();
+    let mut ret = Vec::new();
+    for i in 1..rigid_bodies.len() {
+        for j in 0..i {
+            if rigid_bodies[i].is_immovable() && rigid_bodies[j].is_immovable()
+            {
+                continue;
+            }
+            if collision_table.get(i, j).bounding_box_collision() {
+                ret.push((j, i));
+            }
+        }
+    }
+    ret
+}
+
+#[test]
+fn broadphases_agree() {
+    let mut reference = None;
+    for broadphase_kind in &[
+        BroadphaseKind::SweepAndPrune,
+        BroadphaseKind::AabbTree { margin: 0.2 },
+        BroadphaseKind::SpatialHashGrid { cell_size: 2. },
+    ] {
+        let mut core = RigidBodySimulationCore::new((1, 1));
+        config::test::container_debris(6, &mut core).unwrap();
+        core.set_broadphase(*broadphase_kind).unwrap();
+        let simulation = core.simulation_mut();
+        let mut pairs = Vec::new();
+        for _ in 0..60 {
+            simulation.tick(1. / 60.).unwrap();
+            pairs.push(colliding_pairs(simulation));
+        }
+        match &reference {
+            Some(reference) => assert_eq!(reference, &pairs),
+            None => {
+                assert!(pairs.iter().any(|tick_pairs| !tick_pairs.is_empty()));
+                reference = Some(pairs);
+            }
+        }
+    }
+}
+
+#[test]
+fn spatial_hash_grid_rejects_invalid_cell_size() {
+    let mut simulation = Simulation::new();
+    for cell_size in &[0., -1., f64::NAN, f64::INFINITY] {
+        assert!(matches!(
+            simulation.set_broadphase(BroadphaseKind::SpatialHashGrid {
+                cell_size: *cell_size
+            }),
+            Err(Error::InvalidValue(_))
+        ));
+    }
+    assert!(simulation
+        .set_broadphase(BroadphaseKind::SpatialHashGrid { cell_size: 2. })
+        .is_ok());
+}
+
+#[test]
+fn islands_follow_contacts() {
+    let mut simulation = Simulation::new();
+    let dim = Vector3d::new(3., 3., 3.);
+    for x in &[0., 2.9, 5.8, 20.] {
+        simulation.add_rigid_body(
+            RigidBody::cuboid(
+                &dim,
+                1.,
+                &Vector3d::new(*x, 0., 0.),
+                &Matrix3x3::identity(),
+                &Vector3d::default(),
+                &Vector3d::default(),
+            )
+            .unwrap(),
+        );
+    }
+    simulation.tick(1. / 60.).unwrap();
+    let islands = simulation.islands();
+    assert_eq!(islands.islands().len(), 2);
+    assert_eq!(islands.islands()[0].rigid_body_indices(), &[0, 1, 2]);
+    assert_eq!(islands.islands()[1].rigid_body_indices(), &[3]);
+    assert_eq!(islands.island_index(1), Some(0));
+}
+
+#[test]
+fn state_hash_is_reproducible() {
+    let run = || {
+        let mut core = RigidBodySimulationCore::new((1, 1));
+        config::test::floor_debris(6, &mut core).unwrap();
+        let simulation = core.simulation_mut();
+        for _ in 0..60 {
+            simulation.tick(1. / 60.).unwrap();
+        }
+        let hash = simulation.state_hash();
+        simulation.reset();
+        for _ in 0..60 {
+            simulation.tick(1. / 60.).unwrap();
+        }
+        assert_eq!(hash, simulation.state_hash());
+        let uids = simulation
+            .rigid_bodies()
+            .iter()
+            .map(|rigid_body| rigid_body.uid())
+            .collect::<Vec<_>>();
+        (hash, uids)
+    };
+    let (hash, uids) = run();
+    assert_eq!((hash, uids.clone()), run());
+    assert_eq!((hash, uids), std::thread::spawn(run).join().unwrap());
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn parallel_narrowphase_matches_serial() {
+    type Scene = fn(usize, &mut RigidBodySimulationCore) -> crate::Result<()>;
+    let scenes: [(Scene, usize); 2] =
+        [(config::test::container_debris, 6), (config::default, 6)];
+    for (scene, n) in &scenes {
+        let mut core = RigidBodySimulationCore::new((1, 1));
+        scene(*n, &mut core).unwrap();
+        let simulation = core.simulation_mut();
+        for _ in 0..300 {
+            simulation.tick(1. / 60.).unwrap();
+            let mut serial = simulation.clone();
+            serial
+                .collision_manager
+                .update_broadphase(&serial.rigid_bodies);
+            serial
+                .collision_manager
+                .collide_pairs(&mut serial.rigid_bodies);
+            let mut parallel = simulation.clone();
+            parallel
+                .collision_manager
+                .update_broadphase(&parallel.rigid_bodies);
+            parallel
+                .collision_manager
+                .collide_pairs_parallel(&mut parallel.rigid_bodies);
+            assert_eq!(serial.state_hash(), parallel.state_hash());
+        }
+    }
+}
+
+fn resting_stack(solver_iterations: usize) -> Simulation {
+    let mut simulation = Simulation::with_config(SimulationConfig {
+        coefficient_of_restitution: 0.,
+        ..SimulationConfig::default()
+    });
+    simulation.add_rigid_body(
+        RigidBody::cuboid(
+            &Vector3d::new(10., 1., 10.),
+            0.,
+            &Vector3d::new(0., -0.5, 0.),
+            &Matrix3x3::identity(),
+            &Vector3d::default(),
+            &Vector3d::default(),
+        )
+        .unwrap(),
+    );
+    for k in 0..3 {
+        simulation.add_rigid_body(
+            RigidBody::cuboid(
+                &Vector3d::new(1., 1., 1.),
+                1.,
+                &Vector3d::new(0., 0.5 + k as f64, 0.),
+                &Matrix3x3::identity(),
+                &Vector3d::default(),
+                &Vector3d::default(),
+            )
+            .unwrap(),
+        );
+    }
+    simulation.set_solver_iterations(solver_iterations);
+    simulation
+}
+
+#[test]
+fn resting_stack_sleeps_and_wakes() {
+    let is_asleep = |simulation: &Simulation| {
+        simulation.rigid_bodies()[1..]
+            .iter()
+            .all(|rigid_body| rigid_body.is_sleeping())
+    };
+    let mut simulation = resting_stack(4);
+    simulation.set_sleep(Some(SleepSettings::default()));
+    for _ in 0..20 {
+        simulation.tick(1. / 60.).unwrap();
+    }
+    assert!(!is_asleep(&simulation));
+    for _ in 0..20 {
+        simulation.tick(1. / 60.).unwrap();
+    }
+    assert!(is_asleep(&simulation));
+
+    simulation.rigid_bodies[3].apply_impulse_at_point(
+        &Vector3d::new(0., -1., 0.),
+        &Vector3d::new(0., 2.5, 0.),
+    );
+    for _ in 0..3 {
+        simulation.tick(1. / 60.).unwrap();
+    }
+    assert!(simulation.rigid_bodies()[1..]
+        .iter()
+        .all(|rigid_body| !rigid_body.is_sleeping()));
+
+    let mut simulation = resting_stack(4);
+    simulation.set_sleep(Some(SleepSettings::default()));
+    for _ in 0..40 {
+        simulation.tick(1. / 60.).unwrap();
+    }
+    assert!(is_asleep(&simulation));
+    simulation.add_rigid_body(
+        RigidBody::cuboid(
+            &Vector3d::new(1., 1., 1.),
+            1.,
+            &Vector3d::new(0., 4., 0.),
+            &Matrix3x3::identity(),
+            &Vector3d::new(0., -3., 0.),
+            &Vector3d::default(),
+        )
+        .unwrap(),
+    );
+    let mut woken = false;
+    for _ in 0..30 {
+        simulation.tick(1. / 60.).unwrap();
+        woken |= !simulation.rigid_bodies()[3].is_sleeping();
+    }
+    assert!(woken);
+
+    simulation.set_sleep(None);
+    assert!(simulation
+        .rigid_bodies()
+        .iter()
+        .all(|rigid_body| !rigid_body.is_sleeping()));
+}
+
+#[test]
+fn solver_iterations_reduce_approach_velocity() {
+    let residual = |solver_iterations: usize| {
+        let mut simulation = resting_stack(solver_iterations);
+        simulation.add_force_generator(Box::new(Gravity::earth()));
+        simulation.tick(1. / 60.).unwrap();
+        simulation
+            .rigid_bodies()
+            .iter()
+            .map(|rigid_body| -rigid_body.velocity()[1])
+            .fold(0., f64::max)
+    };
+    let single = residual(1);
+    let few = residual(2);
+    let many = residual(10);
+    assert!(few < single);
+    assert!(many < few);
+    assert!(many < 0.1 * single);
+}
+
+#[test]
+fn snapshot_restores_mid_run_state() {
+    let mut core = RigidBodySimulationCore::new((1, 1));
+    config::test::floor_debris(6, &mut core).unwrap();
+    core.set_broadphase(BroadphaseKind::AabbTree { margin: 0.2 })
+        .unwrap();
+    let simulation = core.simulation_mut();
+    simulation.set_diagnostics(Some(Diagnostics::new(None)));
+    simulation.set_profiler(Some(Profiler::new(200)));
+    let mut snapshots = Vec::new();
+    let mut hashes = Vec::new();
+    for _ in 0..3 {
+        snapshots.push(simulation.snapshot());
+        for _ in 0..20 {
+            simulation.tick(1. / 60.).unwrap();
+        }
+        hashes.push(simulation.state_hash());
+    }
+    for (snapshot, hash) in snapshots.iter().zip(&hashes).rev() {
+        simulation.restore(snapshot);
+        for _ in 0..20 {
+            simulation.tick(1. / 60.).unwrap();
+        }
+        assert_eq!(*hash, simulation.state_hash());
+    }
+    assert!(snapshots[0].simulation.diagnostics().is_none());
+    assert_eq!(simulation.diagnostics().unwrap().sample_count(), 121);
+    assert_eq!(simulation.profiler().unwrap().frames().count(), 120);
+}
+
+#[test]
+fn diagnostics_track_conserved_quantities() {
+    let mut simulation = Simulation::new();
+    simulation.add_rigid_body(
+        RigidBody::cuboid(
+            &Vector3d::new(1., 1., 1.),
+            1.,
+            &Vector3d::default(),
+            &Matrix3x3::identity(),
+            &Vector3d::new(1., 0., 0.),
+            &Vector3d::new(0., 0.5, 0.),
+        )
+        .unwrap(),
+    );
+    simulation.set_diagnostics(Some(Diagnostics::new(Some(
+        DiagnosticsTolerance {
+            energy: 1e-9,
+            linear_momentum: 1e-9,
+            angular_momentum: 1e-9,
+        },
+    ))));
+    for _ in 0..60 {
+        simulation.tick(1. / 60.).unwrap();
+    }
+    let diagnostics = simulation.diagnostics().unwrap();
+    assert_eq!(diagnostics.sample_count(), 61);
+    assert_eq!(diagnostics.samples().count(), 0);
+    assert!(diagnostics.warnings().is_empty());
+
+    simulation.add_force_generator(Box::new(Gravity::earth()));
+    let mut diagnostics = simulation.diagnostics().unwrap().clone();
+    diagnostics.set_history(64);
+    simulation.set_diagnostics(Some(diagnostics));
+    simulation.reset();
+    for _ in 0..60 {
+        simulation.tick(1. / 60.).unwrap();
+    }
+    let diagnostics = simulation.diagnostics().unwrap();
+    let initial = diagnostics.initial().unwrap();
+    for quantity in &DiagnosticsQuantity::ALL {
+        let drifts = diagnostics
+            .samples()
+            .map(|sample| sample.drift(initial, *quantity))
+            .collect::<Vec<_>>();
+        let n = drifts.len() as f64;
+        let statistics = diagnostics.statistics(*quantity);
+        assert_eq!(statistics.max, drifts.iter().copied().fold(0., f64::max));
+        assert!(
+            (statistics.mean - drifts.iter().sum::<f64>() / n).abs() < 1e-9
+        );
+        assert!(
+            (statistics.rms
+                - (drifts.iter().map(|drift| drift * drift).sum::<f64>() / n)
+                    .sqrt())
+            .abs()
+                < 1e-9
+        );
+    }
+    let energy = diagnostics.statistics(DiagnosticsQuantity::Energy);
+    let kinetic_energy = diagnostics.latest().unwrap().kinetic_energy;
+    assert!(energy.max < 0.02 * kinetic_energy);
+    let linear_momentum =
+        diagnostics.statistics(DiagnosticsQuantity::LinearMomentum);
+    assert!((linear_momentum.current - 9.81).abs() < 1e-9);
+    let warnings = diagnostics
+        .warnings()
+        .iter()
+        .filter(|warning| {
+            warning.quantity == DiagnosticsQuantity::LinearMomentum
+        })
+        .count();
+    assert_eq!(warnings, 1);
+
+    let mut diagnostics = diagnostics.clone();
+    diagnostics.set_history(8);
+    assert_eq!(diagnostics.samples().count(), 8);
+    assert_eq!(diagnostics.sample_count(), 61);
+}
+
+#[test]
+fn half_space_bounces_falling_body() {
+    let mut simulation = Simulation::new();
+    simulation.add_half_space(
+        HalfSpace::new(&Vector3d::default(), &Vector3d::new(0., 1., 0.))
+            .unwrap(),
+    );
+    simulation.add_rigid_body(
+        RigidBody::cuboid(
+            &Vector3d::new(1., 1., 1.),
+            1.,
+            &Vector3d::new(0., 2., 0.),
+            &Matrix3x3::identity(),
+            &Vector3d::default(),
+            &Vector3d::default(),
+        )
+        .unwrap(),
+    );
+    simulation.add_force_generator(Box::new(Gravity::earth()));
+    let mut bounced = false;
+    for _ in 0..240 {
+        simulation.tick(1. / 60.).unwrap();
+        let rigid_body = &simulation.rigid_bodies()[0];
+        for vertex in rigid_body.polyhedron_world().vertices() {
+            assert!(vertex[1] > -1e-9);
+        }
+        bounced |= rigid_body.velocity()[1] > 0.;
+    }
+    assert!(bounced);
+}
+
+#[test]
+fn spheres_exchange_momentum() {
+    let mut simulation = Simulation::new();
+    for (x, momentum) in &[(-2., 1.), (2., -1.)] {
+        simulation.add_rigid_body(
+            RigidBody::sphere(
+                0.5,
+                1.,
+                &Vector3d::new(*x, 0., 0.),
+                &Matrix3x3::identity(),
+                &Vector3d::new(*momentum, 0., 0.),
+                &Vector3d::default(),
+            )
+            .unwrap(),
+        );
+    }
+    for _ in 0..240 {
+        simulation.tick(1. / 60.).unwrap();
+        let rigid_bodies = simulation.rigid_bodies();
+        assert!(
+            rigid_bodies[0].position.dist(&rigid_bodies[1].position)
+                > 1. - 1e-9
+        );
+    }
+    let rigid_bodies = simulation.rigid_bodies();
+    assert!((rigid_bodies[0].velocity()[0] + 1.).abs() < 1e-9);
+    assert!((rigid_bodies[1].velocity()[0] - 1.).abs() < 1e-9);
+    assert!(rigid_bodies[0].angular_velocity().mag() < 1e-9);
+}
+
+#[test]
+fn restitution_comes_from_config() {
+    let mut simulation = Simulation::with_config(SimulationConfig {
+        coefficient_of_restitution: 0.,
+        ..SimulationConfig::default()
+    });
+    for (x, momentum) in &[(-2., 1.), (2., -1.)] {
+        simulation.add_rigid_body(
+            RigidBody::sphere(
+                0.5,
+                1.,
+                &Vector3d::new(*x, 0., 0.),
+                &Matrix3x3::identity(),
+                &Vector3d::new(*momentum, 0., 0.),
+                &Vector3d::default(),
+            )
+            .unwrap(),
+        );
+    }
+    for _ in 0..240 {
+        simulation.tick(simulation.config().delta_t).unwrap();
+    }
+    for rigid_body in simulation.rigid_bodies() {
+        assert!(rigid_body.velocity().mag() < 1e-9);
+    }
+    simulation.set_config(SimulationConfig {
+        coefficient_of_restitution: 0.5,
+        ..*simulation.config()
+    });
+    simulation.reset();
+    for _ in 0..240 {
+        simulation.tick(simulation.config().delta_t).unwrap();
+    }
+    let rigid_bodies = simulation.rigid_bodies();
+    assert!((rigid_bodies[0].velocity()[0] + 0.5).abs() < 1e-9);
+    assert!((rigid_bodies[1].velocity()[0] - 0.5).abs() < 1e-9);
+}
+
+#[test]
+fn capsule_lands_flat_on_cuboid() {
+    let mut simulation = Simulation::new();
+    simulation.add_rigid_body(
+        RigidBody::cuboid(
+            &Vector3d::new(10., 1., 10.),
+            0.,
+            &Vector3d::new(0., -0.5, 0.),
+            &Matrix3x3::identity(),
+            &Vector3d::default(),
+            &Vector3d::default(),
+        )
+        .unwrap(),
+    );
+    simulation.add_rigid_body(
+        RigidBody::capsule(
+            0.5,
+            2.,
+            1.,
+            &Vector3d::new(0., 1., 0.),
+            &rotation_matrix::z(FRAC_PI_2),
+            &Vector3d::new(0., -1., 0.),
+            &Vector3d::default(),
+        )
+        .unwrap(),
+    );
+    let mut contacts = 0;
+    for _ in 0..60 {
+        simulation.tick(1. / 60.).unwrap();
+        let capsule = &simulation.rigid_bodies()[1];
+        assert!(capsule.position[1] > 0.5 - 1e-6);
+        contacts = contacts.max(
+            simulation
+                .collision_manager
+                .collision_table()
+                .get(0, 1)
+                .contacts
+                .len(),
+        );
+    }
+    assert_eq!(contacts, 2);
+    let capsule = &simulation.rigid_bodies()[1];
+    assert!(capsule.velocity()[1] > 0.);
+}
+
+fn cylinder_on_floor(
+    half_space: bool,
+    rotation: &Matrix3x3,
+    velocity: &Vector3d,
+    angular_velocity: &Vector3d,
+) -> Simulation {
+    let mut simulation = Simulation::with_config(SimulationConfig {
+        coefficient_of_restitution: 0.,
+        ..SimulationConfig::default()
+    });
+    if half_space {
+        simulation.add_half_space(
+            HalfSpace::new(&Vector3d::default(), &Vector3d::new(0., 1., 0.))
+                .unwrap(),
+        );
+    } else {
+        simulation.add_rigid_body(
+            RigidBody::cuboid(
+                &Vector3d::new(20., 1., 20.),
+                0.,
+                &Vector3d::new(0., -0.5, 0.),
+                &Matrix3x3::identity(),
+                &Vector3d::default(),
+                &Vector3d::default(),
+            )
+            .unwrap(),
+        );
+    }
+    let mut cylinder = RigidBody::cylinder(
+        0.5,
+        1.,
+        1.,
+        &Vector3d::new(0., 0.5, 0.),
+        rotation,
+        velocity,
+        &Vector3d::default(),
+    )
+    .unwrap();
+    cylinder.set_angular_velocity(angular_velocity);
+    simulation.add_rigid_body(cylinder);
+    simulation.add_force_generator(Box::new(Gravity::earth()));
+    simulation
+}
+
+#[test]
+fn cylinder_rolls_smoothly() {
+    for half_space in &[false, true] {
+        let mut simulation = cylinder_on_floor(
+            *half_space,
+            &rotation_matrix::x(FRAC_PI_2),
+            &Vector3d::new(1., 0., 0.),
+            &Vector3d::new(0., 0., -2.),
+        );
+        let (mut min_height, mut max_height) = (f64::MAX, f64::MIN);
+        for _ in 0..240 {
+            simulation.tick(1. / 60.).unwrap();
+            let cylinder = simulation.rigid_bodies().last().unwrap();
+            min_height = min_height.min(cylinder.position[1]);
+            max_height = max_height.max(cylinder.position[1]);
+        }
+        assert!(min_height > 0.5 - 1e-6);
+        assert!(max_height < 0.5 + 5e-3);
+        let cylinder = simulation.rigid_bodies().last().unwrap();
+        assert!((cylinder.position[0] - 4.).abs() < 1e-3);
+        assert!(cylinder.angular_velocity()[2] < -1.8);
+    }
+}
+
+#[test]
+fn cylinder_stands_on_cap() {
+    for half_space in &[false, true] {
+        let mut simulation = cylinder_on_floor(
+            *half_space,
+            &rotation_matrix::z(0.05),
+            &Vector3d::new(0., -1., 0.),
+            &Vector3d::default(),
+        );
+        for _ in 0..240 {
+            simulation.tick(1. / 60.).unwrap();
+        }
+        let cylinder = simulation.rigid_bodies().last().unwrap();
+        let axis = matrix_vector::mult_3(
+            cylinder.rotation(),
+            &Vector3d::new(0., 1., 0.),
+        );
+        assert!(axis[1] > 0.999);
+        assert!((cylinder.position[1] - 0.5).abs() < 1e-2);
+    }
+}
+
+#[test]
+fn sphere_bounces_on_half_space() {
+    let mut simulation = Simulation::new();
+    simulation.add_half_space(
+        HalfSpace::new(&Vector3d::default(), &Vector3d::new(0., 1., 0.))
+            .unwrap(),
+    );
+    simulation.add_rigid_body(
+        RigidBody::sphere(
+            0.5,
+            1.,
+            &Vector3d::new(0., 2., 0.),
+            &Matrix3x3::identity(),
+            &Vector3d::default(),
+            &Vector3d::default(),
+        )
+        .unwrap(),
+    );
+    simulation.add_force_generator(Box::new(Gravity::earth()));
+    let mut min_height = f64::MAX;
+    for _ in 0..240 {
+        simulation.tick(1. / 60.).unwrap();
+        min_height = min_height.min(simulation.rigid_bodies()[0].position[1]);
+    }
+    assert!((min_height - 0.5).abs() < 1e-9);
+    assert!(simulation.rigid_bodies()[0].position[1] < 2.1);
+}
+
+#[test]
+fn shapes_stay_inside_bounding_box() {
+    let mut core = RigidBodySimulationCore::new((1, 1));
+    config::test::shapes(&mut core).unwrap();
+    let simulation = core.simulation_mut();
+    for _ in 0..120 {
+        simulation.tick(1. / 60.).unwrap();
+        for rigid_body in simulation.rigid_bodies() {
+            let bounding_box = rigid_body.bounding_box();
+            for (min, max) in bounding_box[0].iter().zip(bounding_box[1].iter())
+            {
+                assert!(*min > -10. - 1e-6);
+                assert!(*max < 10. + 1e-6);
+            }
+        }
+    }
+}
+
+fn assert_inside(simulation: &Simulation) {
+    for rigid_body in simulation.rigid_bodies() {
+        for half_space in simulation.half_spaces() {
+            assert!(half_space.signed_dist(&rigid_body.position) > 0.5 - 1e-6);
+        }
+    }
+}
+
+#[test]
+fn spinning_container_tumbles_contents() {
+    let mut simulation = Simulation::new();
+    simulation.set_bounding_box(&Some((
+        &Vector3d::new(-3., -3., -1.),
+        &Vector3d::new(3., 3., 1.),
+    )));
+    simulation.set_bounding_box_motion(Some(Box::new(Spin {
+        axis: Vector3d::new(0., 0., 1.),
+        angular_speed: 2.,
+    })));
+    simulation.add_rigid_body(
+        RigidBody::sphere(
+            0.5,
+            1.,
+            &Vector3d::new(0., -2.5, 0.),
+            &Matrix3x3::identity(),
+            &Vector3d::default(),
+            &Vector3d::default(),
+        )
+        .unwrap(),
+    );
+    simulation.add_force_generator(Box::new(Gravity::earth()));
+    let mut max_height = f64::MIN;
+    for _ in 0..300 {
+        simulation.tick(1. / 60.).unwrap();
+        assert_inside(&simulation);
+        max_height = max_height.max(simulation.rigid_bodies()[0].position[1]);
+    }
+    let bounding_box = simulation.bounding_box().inner_opt.as_ref().unwrap();
+    assert!(
+        bounding_box
+            .angular_velocity
+            .dist(&Vector3d::new(0., 0., 2.))
+            < 1e-9
+    );
+    assert!(max_height > 0.);
+}
+
+#[test]
+fn shaken_container_transfers_momentum() {
+    let mut simulation = Simulation::new();
+    simulation.set_bounding_box(&Some((
+        &Vector3d::new(-2., -2., -2.),
+        &Vector3d::new(2., 2., 2.),
+    )));
+    simulation.set_bounding_box_motion(Some(Box::new(Shake {
+        amplitude: Vector3d::new(1.5, 0., 0.),
+        frequency: 1.,
+    })));
+    simulation.add_rigid_body(
+        RigidBody::sphere(
+            0.5,
+            1.,
+            &Vector3d::default(),
+            &Matrix3x3::identity(),
+            &Vector3d::default(),
+            &Vector3d::default(),
+        )
+        .unwrap(),
+    );
+    let mut max_speed = 0f64;
+    for _ in 0..120 {
+        simulation.tick(1. / 60.).unwrap();
+        assert_inside(&simulation);
+        max_speed =
+            max_speed.max(simulation.rigid_bodies()[0].velocity().mag());
+    }
+    assert!(max_speed > 1.);
+    simulation.reset();
+    let bounding_box = simulation.bounding_box().inner_opt.as_ref().unwrap();
+    assert!(bounding_box.position.is_zero());
+}
+
+#[test]
+fn container_catches_escaped_sleeping_body() {
+    let mut simulation = Simulation::new();
+    simulation.set_bounding_box(&Some((
+        &Vector3d::new(-2., -2., -2.),
+        &Vector3d::new(2., 2., 2.),
+    )));
+    simulation.set_bounding_box_motion(Some(Box::new(Spin {
+        axis: Vector3d::new(0., 0., 1.),
+        angular_speed: 1.,
+    })));
+    simulation.add_rigid_body(
+        RigidBody::sphere(
+            0.5,
+            1.,
+            &Vector3d::new(3., 0.5, 0.),
+            &Matrix3x3::identity(),
+            &Vector3d::default(),
+            &Vector3d::default(),
+        )
+        .unwrap(),
+    );
+    let rigid_body = &mut simulation.rigid_bodies_mut()[0];
+    rigid_body.sleep();
+    rigid_body.momentum = Vector3d::new(2., 0., 0.);
+    rigid_body.update_velocity();
+    simulation.tick(1. / 60.).unwrap();
+    assert_inside(&simulation);
+    let rigid_body = &simulation.rigid_bodies()[0];
+    assert!(!rigid_body.is_sleeping());
+    let bounding_box = simulation.bounding_box().inner_opt.as_ref().unwrap();
+    let wall_velocity = bounding_box
+        .angular_velocity
+        .cross(&rigid_body.position)
+        .add(&bounding_box.velocity);
+    let normal = matrix_vector::mult_3(
+        &bounding_box.rotation,
+        &Vector3d::new(-1., 0., 0.),
+    );
+    assert!(rigid_body.velocity().sub(&wall_velocity).dot(&normal) > 0.);
+}
+
+#[test]
+fn periodic_domain_collides_across_faces() {
+    for sphere in &[true, false] {
+        let mut simulation = Simulation::new();
+        simulation.set_periodic_domain(Some(
+            PeriodicDomain::new(
+                &Vector3d::new(-5., -5., -5.),
+                &Vector3d::new(5., 5., 5.),
+                [true, false, false],
+            )
+            .unwrap(),
+        ));
+        for (x, momentum) in &[(-4., -1.), (4., 1.)] {
+            let position = Vector3d::new(*x, 0., 0.);
+            let momentum = Vector3d::new(*momentum, 0., 0.);
+            simulation.add_rigid_body(if *sphere {
+                RigidBody::sphere(
+                    0.5,
+                    1.,
+                    &position,
+                    &Matrix3x3::identity(),
+                    &momentum,
+                    &Vector3d::default(),
+                )
+                .unwrap()
+            } else {
+                RigidBody::cuboid(
+                    &Vector3d::new(1., 1., 1.),
+                    1.,
+                    &position,
+                    &Matrix3x3::identity(),
+                    &momentum,
+                    &Vector3d::default(),
+                )
+                .unwrap()
+            });
+        }
+        let mut crossed = false;
+        for _ in 0..120 {
+            simulation.tick(1. / 60.).unwrap();
+            let rigid_bodies = simulation.rigid_bodies();
+            for rigid_body in rigid_bodies {
+                assert!(rigid_body.position[0] >= -5.);
+                assert!(rigid_body.position[0] < 5.);
+            }
+            crossed |= rigid_bodies[0].position[0] > 0.;
+            let gap =
+                10. - rigid_bodies[1].position[0] + rigid_bodies[0].position[0];
+            assert!(gap > 1. - 1e-3);
+            let collision_table =
+                simulation.collision_manager.collision_table();
+            assert!(!collision_table.get(0, 1).colliding);
+        }
+        assert!(!crossed);
+        let rigid_bodies = simulation.rigid_bodies();
+        assert!((rigid_bodies[0].velocity()[0] - 1.).abs() < 1e-3);
+        assert!((rigid_bodies[1].velocity()[0] + 1.).abs() < 1e-3);
+    }
+
+    let mut simulation = Simulation::new();
+    simulation.set_periodic_domain(Some(
+        PeriodicDomain::new(
+            &Vector3d::new(-5., -5., -5.),
+            &Vector3d::new(5., 5., 5.),
+            [true, true, true],
+        )
+        .unwrap(),
+    ));
+    simulation.add_rigid_body(
+        RigidBody::sphere(
+            0.5,
+            1.,
+            &Vector3d::new(4., 0., 0.),
+            &Matrix3x3::identity(),
+            &Vector3d::new(2., 0., 0.),
+            &Vector3d::default(),
+        )
+        .unwrap(),
+    );
+    let wall_position = Vector3d::new(0., 7., 0.);
+    simulation.add_rigid_body(
+        RigidBody::cuboid(
+            &Vector3d::new(1., 1., 1.),
+            0.,
+            &wall_position,
+            &Matrix3x3::identity(),
+            &Vector3d::default(),
+            &Vector3d::default(),
+        )
+        .unwrap(),
+    );
+    for _ in 0..60 {
+        simulation.tick(1. / 60.).unwrap();
+    }
+    let rigid_bodies = simulation.rigid_bodies();
+    assert!((rigid_bodies[0].position[0] + 4.).abs() < 1e-9);
+    assert!(rigid_bodies[1].position.sub(&wall_position).is_zero());
+
+    let (min, max) = (Vector3d::new(-5., 0., -5.), Vector3d::new(5., 0., 5.));
+    assert!(PeriodicDomain::new(&min, &max, [true, false, true]).is_ok());
+    assert!(matches!(
+        PeriodicDomain::new(&min, &max, [false, true, false]),
+        Err(Error::InvalidValue(_))
+    ));
+    assert!(matches!(
+        PeriodicDomain::new(
+            &min,
+            &Vector3d::new(f64::INFINITY, 0., 5.),
+            [false, false, true]
+        ),
+        Err(Error::InvalidValue(_))
+    ));
+}
+
+#[test]
+fn attractors_pull_only_targets() {
+    let mut simulation = Simulation::new();
+    for x in &[-5., 5.] {
+        simulation.add_rigid_body(
+            RigidBody::sphere(
+                0.5,
+                0.5,
+                &Vector3d::new(*x, 0., 0.),
+                &Matrix3x3::identity(),
+                &Vector3d::default(),
+                &Vector3d::default(),
+            )
+            .unwrap(),
+        );
+    }
+    let uid = simulation.rigid_bodies()[0].uid();
+    let mut attractor = Attractor::point(&Vector3d::default(), 25.);
+    attractor.targets = Targets::Uids(vec![uid]);
+    let (force, _) = attractor.force(&simulation.rigid_bodies()[0]);
+    assert!((force[0] - 2.).abs() < 1e-9);
+    simulation.add_force_generator(Box::new(attractor));
+    let mut line = Attractor::line(
+        &Vector3d::new(0., 0., 3.),
+        &Vector3d::new(1., 0., 0.),
+        1.,
+    );
+    line.targets = Targets::Uids(vec![]);
+    simulation.add_force_generator(Box::new(line));
+    simulation.tick(1. / 60.).unwrap();
+    let rigid_bodies = simulation.rigid_bodies();
+    assert!(rigid_bodies[0].velocity()[0] > 0.);
+    assert!(rigid_bodies[1].velocity().is_zero());
+
+    let line = Attractor::line(
+        &Vector3d::new(0., 0., 3.),
+        &Vector3d::new(1., 0., 0.),
+        9.,
+    );
+    let (force, _) = line.force(&rigid_bodies[1]);
+    assert!(force.sub(&Vector3d::new(0., 0., 2.)).mag() < 1e-9);
+}
+
+#[test]
+fn barnes_hut_approximates_mutual_gravity() {
+    let mut simulation = Simulation::new();
+    for i in 0..64 {
+        let phase = i as f64;
+        simulation.add_rigid_body(
+            RigidBody::sphere(
+                0.1,
+                1. / (1. + (phase * 0.7).sin().abs()),
+                &Vector3d::new(
+                    (phase * 1.3).sin() * 10.,
+                    (phase * 2.1).cos() * 10.,
+                    (phase * 0.9).sin() * 10.,
+                ),
+                &Matrix3x3::identity(),
+                &Vector3d::default(),
+                &Vector3d::default(),
+            )
+            .unwrap(),
+        );
+    }
+    let rigid_bodies = simulation.rigid_bodies();
+    let mut direct = MutualGravity::new(1., None);
+    let mut exact = MutualGravity::new(1., Some(0.));
+    let mut approximate = MutualGravity::new(1., Some(0.5));
+    for gravity in &mut [&mut direct, &mut exact, &mut approximate] {
+        gravity.softening = 0.1;
+        gravity.prepare(rigid_bodies);
+    }
+    let mut total = Vector3d::default();
+    let mut max_force = 0f64;
+    let mut max_error = 0f64;
+    for rigid_body in rigid_bodies {
+        let (force, _) = direct.force(rigid_body);
+        total.add_assign(&force);
+        max_force = max_force.max(force.mag());
+        let (force_exact, _) = exact.force(rigid_body);
+        assert!(force_exact.sub(&force).mag() < 1e-9 * force.mag().max(1.));
+        let (force_approximate, _) = approximate.force(rigid_body);
+        max_error = max_error.max(force_approximate.sub(&force).mag());
+    }
+    assert!(max_error < 0.02 * max_force);
+    assert!(total.mag() < 1e-9);
+}
+
+#[test]
+fn light_cuboid_floats_at_half_depth() {
+    let mut simulation = Simulation::new();
+    simulation.add_rigid_body(
+        RigidBody::cuboid(
+            &Vector3d::new(1., 1., 1.),
+            2.,
+            &Vector3d::new(0., 2., 0.),
+            &rotation_matrix::z(0.3),
+            &Vector3d::default(),
+            &Vector3d::default(),
+        )
+        .unwrap(),
+    );
+    simulation.add_force_generator(Box::new(Gravity::earth()));
+    simulation.add_force_generator(Box::new(FluidVolume::new(0., 1., 0.5)));
+    for _ in 0..1200 {
+        simulation.tick(1. / 60.).unwrap();
+    }
+    let rigid_body = &simulation.rigid_bodies()[0];
+    assert!(rigid_body.position[1].abs() < 0.02);
+    assert!(rigid_body.velocity().mag() < 0.05);
+    assert!(rigid_body.angular_velocity().mag() < 0.05);
+}
+
+#[test]
+fn damping_slows_spinning_bodies() {
+    let mut simulation = Simulation::new();
+    for z in &[0., 10.] {
+        simulation.add_rigid_body(
+            RigidBody::cuboid(
+                &Vector3d::new(1., 1., 1.),
+                1.,
+                &Vector3d::new(0., 0., *z),
+                &Matrix3x3::identity(),
+                &Vector3d::new(1., 0., 0.),
+                &Vector3d::new(0., 1., 0.),
+            )
+            .unwrap(),
+        );
+    }
+    let uid = simulation.rigid_bodies()[0].uid();
+    simulation.add_force_generator(Box::new(Damping {
+        linear: 0.5,
+        quadratic: 0.5,
+        angular: 0.5,
+        targets: Targets::Uids(vec![uid]),
+    }));
+    for _ in 0..120 {
+        simulation.tick(1. / 60.).unwrap();
+    }
+    let rigid_bodies = simulation.rigid_bodies();
+    assert!(rigid_bodies[0].velocity().mag() < 0.5);
+    assert!(rigid_bodies[0].angular_velocity().mag() < 0.5);
+    assert!((rigid_bodies[1].velocity().mag() - 1.).abs() < 1e-9);
+    assert!((rigid_bodies[1].angular_velocity().mag() - 6.).abs() < 1e-9);
+}
+
+#[test]
+fn aerodynamic_drag_reaches_terminal_velocity() {
+    let mut simulation = Simulation::new();
+    simulation.add_rigid_body(
+        RigidBody::cuboid(
+            &Vector3d::new(1., 1., 1.),
+            1.,
+            &Vector3d::default(),
+            &Matrix3x3::identity(),
+            &Vector3d::default(),
+            &Vector3d::default(),
+        )
+        .unwrap(),
+    );
+    let gravity = Gravity::earth();
+    let drag = AerodynamicDrag::new(1.2, 1.);
+    let terminal_velocity =
+        (2. * -gravity.acceleration[1] / (drag.density * 1.)).sqrt();
+    simulation.add_force_generator(Box::new(gravity));
+    simulation.add_force_generator(Box::new(drag));
+    for _ in 0..600 {
+        simulation.tick(1. / 60.).unwrap();
+    }
+    let velocity = simulation.rigid_bodies()[0].velocity();
+    assert!((velocity[1] + terminal_velocity).abs() < 0.01 * terminal_velocity);
+    assert!(simulation.rigid_bodies()[0].angular_velocity().mag() < 1e-9);
+}
+
+#[test]
+fn impulses_update_derived_state_and_wake() {
+    let mut rigid_body = RigidBody::cuboid(
+        &Vector3d::new(1., 1., 1.),
+        0.5,
+        &Vector3d::new(1., 0., 0.),
+        &Matrix3x3::identity(),
+        &Vector3d::default(),
+        &Vector3d::default(),
+    )
+    .unwrap();
+    rigid_body.sleep();
+    rigid_body.apply_impulse_at_point(
+        &Vector3d::new(0., 2., 0.),
+        &Vector3d::new(1.5, 0., 0.),
+    );
+    assert!(!rigid_body.is_sleeping());
+    assert!(rigid_body.velocity().sub(&Vector3d::new(0., 1., 0.)).mag() < 1e-9);
+    assert!(
+        rigid_body
+            .angular_velocity()
+            .sub(&Vector3d::new(0., 0., 3.))
+            .mag()
+            < 1e-9
+    );
+
+    rigid_body.apply_torque_impulse(&Vector3d::new(0., 0., -1.));
+    assert!(
+        rigid_body
+            .angular_velocity()
+            .sub(&Vector3d::default())
+            .mag()
+            < 1e-9
+    );
+
+    rigid_body.set_linear_velocity(&Vector3d::new(3., 0., 0.));
+    rigid_body.set_angular_velocity(&Vector3d::new(0., 1., 0.));
+    assert!(rigid_body.momentum.sub(&Vector3d::new(6., 0., 0.)).mag() < 1e-9);
+    assert!(
+        rigid_body
+            .angular_momentum
+            .sub(&Vector3d::new(0., 1. / 3., 0.))
+            .mag()
+            < 1e-9
+    );
+
+    rigid_body.sleep();
+    rigid_body.apply_force_at_point(
+        &Vector3d::new(0., 0., 1.),
+        &Vector3d::new(1., 1., 0.),
+    );
+    assert!(!rigid_body.is_sleeping());
+    assert!(rigid_body.torque.sub(&Vector3d::new(1., 0., 0.)).mag() < 1e-9);
+}
+
+#[test]
+fn explosion_pushes_and_spins_bodies_in_range() {
+    for broadphase_kind in &[
+        BroadphaseKind::SweepAndPrune,
+        BroadphaseKind::AabbTree { margin: 0.2 },
+        BroadphaseKind::SpatialHashGrid { cell_size: 2. },
+    ] {
+        for strength in &[5., -5.] {
+            let mut simulation = Simulation::new();
+            simulation.set_broadphase(*broadphase_kind).unwrap();
+            for (position, rotation) in &[
+                (Vector3d::new(3., 0.5, 0.), rotation_matrix::z(0.4)),
+                (Vector3d::new(-3., 0., 0.), Matrix3x3::identity()),
+                (Vector3d::new(50., 0., 0.), Matrix3x3::identity()),
+            ] {
+                simulation.add_rigid_body(
+                    RigidBody::cuboid(
+                        &Vector3d::new(1., 1., 1.),
+                        1.,
+                        position,
+                        rotation,
+                        &Vector3d::default(),
+                        &Vector3d::default(),
+                    )
+                    .unwrap(),
+                );
+            }
+            simulation.explode(
+                &Vector3d::default(),
+                10.,
+                *strength,
+                Falloff::Linear,
+            );
+            let rigid_bodies = simulation.rigid_bodies();
+            assert!(rigid_bodies[0].velocity()[0] * strength > 0.);
+            assert!(rigid_bodies[0].angular_velocity().mag() > 1e-3);
+            assert!(rigid_bodies[1].velocity()[0] * strength < 0.);
+            assert!(rigid_bodies[1].angular_velocity().mag() < 1e-9);
+            assert!(
+                (rigid_bodies[1].velocity().mag() - strength.abs() * 0.75)
+                    .abs()
+                    < 1e-9
+            );
+            assert!(rigid_bodies[2].velocity().is_zero());
+        }
+    }
+}
+
+#[test]
+fn rotation_integrators_stay_stable_on_intermediate_axis() {
+    for integrator in
+        &[Integrator::ExponentialMap, Integrator::ImplicitGyroscopic]
+    {
+        let mut simulation = Simulation::new();
+        simulation.set_integrator(*integrator);
+        let dimensions = Vector3d::new(1., 3., 0.3);
+        let angular_momentum = matrix_vector::mult_3(
+            &moment_of_inertia::aligned_cuboid(&dimensions, 1.),
+            &Vector3d::new(5., 0., 0.05),
+        );
+        simulation.add_rigid_body(
+            RigidBody::cuboid(
+                &dimensions,
+                1.,
+                &Vector3d::default(),
+                &Matrix3x3::identity(),
+                &Vector3d::default(),
+                &angular_momentum,
+            )
+            .unwrap(),
+        );
+        let energy = simulation.rigid_bodies()[0].ki_rotational();
+        let mut flipped = false;
+        let mut previous_ratio = 1.;
+        for _ in 0..1200 {
+            simulation.tick(1. / 60.).unwrap();
+            let rigid_body = &simulation.rigid_bodies()[0];
+            let angular_velocity_body = matrix_vector::mult_3t(
+                rigid_body.rotation(),
+                rigid_body.angular_velocity(),
+            );
+            flipped |= angular_velocity_body[0] < 0.;
+            let ratio = rigid_body.ki_rotational() / energy;
+            assert!(
+                rigid_body.angular_momentum.sub(&angular_momentum).mag() < 1e-9
+            );
+            match integrator {
+                Integrator::ExponentialMap => {
+                    assert!((ratio - 1.).abs() < 1e-2)
+                }
+                _ => {
+                    assert!(
+                        ratio < previous_ratio + 1e-9,
+                        "implicit gyroscopic integration is dissipative"
+                    );
+                    assert!(ratio > 0.9);
+                }
+            }
+            previous_ratio = ratio;
+        }
+        assert!(flipped);
+    }
+}
+
+#[test]
+fn invalid_input_returns_errors() {
+    let cuboid = |dimensions: &Vector3d, mass_inv: f64| {
+        RigidBody::cuboid(
+            dimensions,
+            mass_inv,
+            &Vector3d::default(),
+            &Matrix3x3::identity(),
+            &Vector3d::default(),
+            &Vector3d::default(),
+        )
+    };
+    assert!(cuboid(&Vector3d::new(1., 1., 1.), 1.).is_ok());
+    assert!(matches!(
+        cuboid(&Vector3d::new(1., 1., 1.), -1.),
+        Err(Error::InvalidValue(_))
+    ));
+    assert!(matches!(
+        cuboid(&Vector3d::new(1., 0., 1.), 0.).map_err(|e| e.root().clone()),
+        Err(Error::DegenerateGeometry(_))
+    ));
+    assert!(matches!(
+        RigidBody::sphere(
+            0.,
+            1.,
+            &Vector3d::default(),
+            &Matrix3x3::identity(),
+            &Vector3d::default(),
+            &Vector3d::default(),
+        ),
+        Err(Error::SingularInertia(_))
+    ));
+    let mesh_error =
+        |obj: &str| obj_loader::from_reader(obj.as_bytes()).err().unwrap();
+    assert_eq!(
+        mesh_error("v 0 0 0\nv 1 x 0\n"),
+        Error::Parse {
+            line: 2,
+            message: "Invalid vertex value x.".into()
+        }
+    );
+    assert!(matches!(
+        mesh_error("v 0 0 0\nf 1 2 0\n"),
+        Error::Parse { line: 2, .. }
+    ));
+    let error = obj_loader::simple("missing.obj").err().unwrap();
+    assert!(error.to_string().starts_with("missing.obj: io: "));
+    assert!(matches!(
+        error.root(),
+        Error::Io {
+            kind: io::ErrorKind::NotFound,
+            ..
+        }
+    ));
+    assert_eq!(
+        std::error::Error::source(&error).map(ToString::to_string),
+        Some(error.root().to_string())
+    );
+}
+
+#[test]
+fn health_policies_handle_non_finite_bodies() {
+    let position = Vector3d::new(0., 5., 0.);
+    let simulation_with_policy =
+        |health_policy: HealthPolicy| {
+            let mut simulation = Simulation::new();
+            simulation.set_health_policy(health_policy);
+            simulation.add_rigid_body(
+                RigidBody::sphere(
+                    1.,
+                    1.,
+                    &position,
+                    &Matrix3x3::identity(),
+                    &Vector3d::new(1., 0., 0.),
+                    &Vector3d::default(),
+                )
+                .unwrap(),
+            );
+            simulation.tick(1. / 60.).unwrap();
+            simulation.rigid_bodies_mut()[0]
+                .set_linear_velocity(&Vector3d::new(f64::NAN, 0., 0.));
+            simulation
+        };
+
+    let mut simulation = simulation_with_policy(HealthPolicy::default());
+    let uid = simulation.rigid_bodies()[0].uid();
+    let issue = HealthIssue {
+        uid,
+        time: 1. / 60.,
+        stage: HealthStage::External,
+        quantity: HealthQuantity::Momentum,
+    };
+    assert_eq!(simulation.tick(1. / 60.), Err(Error::Health(issue)));
+    assert_eq!(simulation.health_report(), &[issue]);
+
+    let mut core = RigidBodySimulationCore::new((1, 1));
+    *core.simulation_mut() = simulation_with_policy(HealthPolicy::default());
+    core.input.advance_simulation = true;
+    core.tick();
+    assert!(!core.input.advance_simulation);
+    assert_eq!(core.health_issue(), Some(&issue));
+    assert_eq!(core.take_health_issue(), Some(issue));
+    assert!(core.health_issue().is_none());
+
+    let mut simulation = simulation_with_policy(HealthPolicy::Freeze);
+    let valid_position = simulation.rigid_bodies()[0].position;
+    simulation.tick(1. / 60.).unwrap();
+    assert_eq!(simulation.health_report().len(), 1);
+    let rigid_body = &simulation.rigid_bodies()[0];
+    assert!(rigid_body.is_immovable());
+    assert!(rigid_body.position.sub(&valid_position).is_zero());
+    assert!(health::check(rigid_body).is_none());
+
+    let mut simulation = simulation_with_policy(HealthPolicy::Reset);
+    simulation.tick(1. / 60.).unwrap();
+    assert_eq!(simulation.health_report().len(), 1);
+    simulation.tick(1. / 60.).unwrap();
+    assert!(simulation.health_report().is_empty());
+    assert!(health::check(&simulation.rigid_bodies()[0]).is_none());
+
+    let mut simulation = Simulation::new();
+    for x in &[-0.9, 0.9] {
+        simulation.add_rigid_body(
+            RigidBody::sphere(
+                1.,
+                1.,
+                &Vector3d::new(*x, 0., 0.),
+                &Matrix3x3::identity(),
+                &Vector3d::new(-x * f64::MAX, 0., 0.),
+                &Vector3d::default(),
+            )
+            .unwrap(),
+        );
+    }
+    match simulation.tick(1e-310) {
+        Err(Error::Health(issue)) => {
+            assert_eq!(issue.stage, HealthStage::Narrowphase)
+        }
+        result => panic!("{:?}", result),
+    }
+}
+
+#[test]
+fn event_log_records_structured_entries() {
+    let mut event_log = EventLog::new(2);
+    event_log.push(Severity::Debug, (0, 1), FailureKind::MissingContacts);
+    assert_eq!(event_log.entries().count(), 0);
+    event_log.set_min_severity(Severity::Debug);
+    event_log.push(Severity::Debug, (0, 1), FailureKind::MissingContacts);
+    event_log.advance();
+    event_log.push(Severity::Warning, (1, 2), FailureKind::DePenetrateBisect);
+    event_log.push(Severity::Warning, (2, 3), FailureKind::DePenetratePlane);
+    let entries = event_log.drain();
+    assert_eq!(
+        entries,
+        vec![
+            LogEntry {
+                severity: Severity::Warning,
+                tick: 1,
+                uids: (1, 2),
+                kind: FailureKind::DePenetrateBisect,
+            },
+            LogEntry {
+                severity: Severity::Warning,
+                tick: 1,
+                uids: (2, 3),
+                kind: FailureKind::DePenetratePlane,
+            },
+        ]
+    );
+    assert_eq!(
+        entries[0].to_string(),
+        "Warning tick 1: DePenetrateBisect, uids: (1 2)"
+    );
+    assert_eq!(event_log.entries().count(), 0);
+
+    let mut simulation = Simulation::new();
+    simulation.set_debug(true);
+    assert_eq!(simulation.event_log().min_severity(), Severity::Debug);
+    for _ in 0..3 {
+        simulation.tick(1. / 60.).unwrap();
+    }
+    assert_eq!(simulation.event_log().tick(), 3);
+    simulation.reset();
+    assert_eq!(simulation.event_log().tick(), 0);
+}
+
+#[test]
+fn profiler_records_stages_and_counters() {
+    let cuboid = |position: Vector3d, velocity: Vector3d| {
+        RigidBody::cuboid(
+            &Vector3d::new(1., 1., 1.),
+            1.,
+            &position,
+            &Matrix3x3::identity(),
+            &velocity,
+            &Vector3d::default(),
+        )
+        .unwrap()
+    };
+    let mut simulation = Simulation::new();
+    simulation.add_rigid_body(cuboid(
+        Vector3d::new(-1., 0., 0.),
+        Vector3d::new(5., 0., 0.),
+    ));
+    simulation.add_rigid_body(cuboid(
+        Vector3d::new(1., 0., 0.),
+        Vector3d::new(-5., 0., 0.),
+    ));
+    simulation.set_profiler(Some(Profiler::new(4)));
+    for _ in 0..9 {
+        simulation.tick(1. / 60.).unwrap();
+    }
+    let profiler = simulation.profiler().unwrap();
+    assert_eq!(profiler.frames().count(), 4);
+    let pairs_tested = profiler
+        .frames()
+        .map(|frame| frame.count(ProfileCounter::PairsTested))
+        .sum::<u64>();
+    assert!(pairs_tested > 0);
+    assert!(profiler
+        .frames()
+        .any(|frame| frame.count(ProfileCounter::ContactsFound) > 0));
+    assert_eq!(profiler.current_frame().total(), Duration::default());
+    assert!(profiler
+        .summary()
+        .starts_with("profile, average of 4 ticks:"));
+
+    let mut profiler = Profiler::new(1);
+    profiler.begin(ProfileStage::Narrowphase);
+    profiler.begin(ProfileStage::DePenetration);
+    profiler.count(ProfileCounter::BisectionIterations, 3);
+    profiler.end();
+    profiler.end();
+    profiler.end_frame();
+    let frame = profiler.frames().next().unwrap();
+    assert_eq!(frame.count(ProfileCounter::BisectionIterations), 3);
+    assert_eq!(
+        frame.total(),
+        frame.duration(ProfileStage::Narrowphase)
+            + frame.duration(ProfileStage::DePenetration)
+    );
+    assert_eq!(profiler.average(), *frame);
+}
+
+#[test]
+fn profiler_records_one_frame_per_tick() {
+    let mut core = RigidBodySimulationCore::new((4, 4));
+    config::default(2, &mut core).unwrap();
+    core.simulation_mut().set_profiler(Some(Profiler::new(16)));
+    for _ in 0..5 {
+        core.simulation_mut().tick(1. / 60.).unwrap();
+    }
+    let profiler = core.simulation().profiler().unwrap();
+    assert_eq!(profiler.frames().count(), 5);
+    assert!(profiler
+        .frames()
+        .all(|frame| frame.total() > Duration::default()));
+
+    core.render();
+    core.simulation_mut().tick(1. / 60.).unwrap();
+    core.render();
+    let profiler = core.simulation().profiler().unwrap();
+    assert_eq!(profiler.frames().count(), 6);
+    assert!(profiler.frames().skip(4).all(|frame| {
+        frame.duration(ProfileStage::Rendering) > Duration::default()
+    }));
+    core.render();
+    let profiler = core.simulation().profiler().unwrap();
+    assert_eq!(profiler.frames().count(), 7);
+    assert_eq!(profiler.current_frame().total(), Duration::default());
+}
+
+#[test]
+fn default_config_builds() {
+    let mut core = RigidBodySimulationCore::new((1, 1));
+    config::default(4, &mut core).unwrap();
+    assert_eq!(core.simulation().rigid_bodies().len(), 64);
+}
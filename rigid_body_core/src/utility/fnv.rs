@@ -0,0 +1,34 @@
+const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const PRIME: u64 = 0x0100_0000_01b3;
+
+#[derive(Clone, Copy)]
+pub struct FnvHasher {
+    hash: u64,
+}
+
+impl FnvHasher {
+    pub fn new() -> Self {
+        Self { hash: OFFSET_BASIS }
+    }
+
+    pub fn finish(&self) -> u64 {
+        self.hash
+    }
+
+    pub fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.hash ^= u64::from(*byte);
+            self.hash = self.hash.wrapping_mul(PRIME);
+        }
+    }
+
+    pub fn write_f64(&mut self, value: f64) {
+        self.write(&value.to_bits().to_le_bytes());
+    }
+}
+
+impl Default for FnvHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
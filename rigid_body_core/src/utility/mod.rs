@@ -1,4 +1,7 @@
+pub mod fnv;
 mod fps_manager;
 pub mod int_hash;
+#[cfg(feature = "parallel")]
+pub mod parallel;
 
 pub use fps_manager::FPSManager;
@@ -0,0 +1,49 @@
+use std::thread;
+
+fn chunk_size(len: usize) -> usize {
+    let threads = thread::available_parallelism()
+        .map(|threads| threads.get())
+        .unwrap_or(1);
+    len.div_ceil(threads).max(1)
+}
+
+pub fn for_each_mut<T, F>(items: &mut [T], f: F)
+where
+    T: Send,
+    F: Fn(&mut T) + Sync,
+{
+    let chunk_size = chunk_size(items.len());
+    let f = &f;
+    thread::scope(|scope| {
+        for chunk in items.chunks_mut(chunk_size) {
+            scope.spawn(move || {
+                for item in chunk {
+                    f(item);
+                }
+            });
+        }
+    });
+}
+
+pub fn map<T, R, F>(items: &[T], f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let chunk_size = chunk_size(items.len());
+    let f = &f;
+    thread::scope(|scope| {
+        let handles = items
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || chunk.iter().map(f).collect::<Vec<R>>())
+            })
+            .collect::<Vec<_>>();
+        let mut ret = Vec::with_capacity(items.len());
+        for handle in handles {
+            ret.append(&mut handle.join().expect("parallel map"));
+        }
+        ret
+    })
+}
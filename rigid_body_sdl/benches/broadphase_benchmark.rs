@@ -0,0 +1,43 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use rigid_body_core::{
+    config, BroadphaseKind, Result, RigidBodySimulationCore,
+    RigidBodySimulationTrait,
+};
+use std::time::Duration;
+
+type Scene = fn(usize, &mut RigidBodySimulationCore) -> Result<()>;
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let scenes: [(&str, Scene); 3] = [
+        ("aligned", config::test::aligned_debris),
+        ("clustered", config::test::clustered_debris),
+        ("uniform", config::test::uniform_debris),
+    ];
+    for (scene_name, scene) in &scenes {
+        let mut group = c.benchmark_group(format!("broadphase_{}", scene_name));
+        group.measurement_time(Duration::from_secs(10));
+        for (name, broadphase_kind) in &[
+            ("sweep_and_prune", BroadphaseKind::SweepAndPrune),
+            ("aabb_tree", BroadphaseKind::AabbTree { margin: 0.2 }),
+            (
+                "spatial_hash_grid",
+                BroadphaseKind::SpatialHashGrid { cell_size: 2. },
+            ),
+        ] {
+            let mut core = RigidBodySimulationCore::new((1, 1));
+            scene(512, &mut core).unwrap();
+            core.set_broadphase(*broadphase_kind).unwrap();
+            let simulation = core.simulation_mut();
+            let delta_t = simulation.config().delta_t;
+            group.bench_function(*name, |b| {
+                b.iter(|| {
+                    simulation.tick(delta_t).unwrap();
+                })
+            });
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -70,11 +70,16 @@ impl InputSDL {
             keyboard::Keycode::R => Some(keyboard_state::Keycode::R),
             keyboard::Keycode::S => Some(keyboard_state::Keycode::S),
             keyboard::Keycode::W => Some(keyboard_state::Keycode::W),
+            keyboard::Keycode::X => Some(keyboard_state::Keycode::X),
             keyboard::Keycode::Z => Some(keyboard_state::Keycode::Z),
             keyboard::Keycode::Return => Some(keyboard_state::Keycode::Return),
             keyboard::Keycode::Space => Some(keyboard_state::Keycode::Space),
             keyboard::Keycode::Tab => Some(keyboard_state::Keycode::Tab),
             keyboard::Keycode::Escape => Some(keyboard_state::Keycode::Escape),
+            keyboard::Keycode::Left => Some(keyboard_state::Keycode::Left),
+            keyboard::Keycode::Right => Some(keyboard_state::Keycode::Right),
+            keyboard::Keycode::Up => Some(keyboard_state::Keycode::Up),
+            keyboard::Keycode::Down => Some(keyboard_state::Keycode::Down),
             _ => None,
         }
     }
@@ -3,10 +3,13 @@ pub mod render;
 
 use input::InputSDL;
 use render::RendererSDL;
+#[cfg(feature = "scene")]
+pub use rigid_body_core::scene;
 pub use rigid_body_core::{
     config,
     input::camera_mover::{CameraMode, CameraMover},
-    math, mesh, rigid_body, RigidBodySimulationTrait,
+    math, mesh, recording, rigid_body, BroadphaseKind,
+    RigidBodySimulationTrait, Snapshot,
 };
 use rigid_body_core::{
     render::ScreenBufferTrait, RigidBodySimulationCore,
@@ -58,6 +61,21 @@ impl RigidBodySimulationSDL {
         if !self.rigid_body_simulation_core.tick() {
             return Ok(false);
         }
+        if let Some(issue) = self.rigid_body_simulation_core.take_health_issue()
+        {
+            eprintln!("{}", issue);
+        }
+        let debug = self.rigid_body_simulation_core.input.debug;
+        for entry in self
+            .rigid_body_simulation_core
+            .simulation_mut()
+            .event_log_mut()
+            .drain()
+        {
+            if debug {
+                eprintln!("{}", entry);
+            }
+        }
         self.renderer.present(
             self.rigid_body_simulation_core.renderer.pixel_buffer_mut(),
         )?;
@@ -1,6 +1,7 @@
-use rigid_body_sdl::{config, RigidBodySimulationSDL, StrResult};
+use rigid_body_sdl::{config, RigidBodySimulationSDL};
+use std::error::Error;
 
-fn main() -> StrResult<()> {
+fn main() -> Result<(), Box<dyn Error>> {
     let mut rigid_body_simulation = RigidBodySimulationSDL::new((800, 600))?;
     config::default(8, &mut rigid_body_simulation)?;
     while rigid_body_simulation.tick()? {}
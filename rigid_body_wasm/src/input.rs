@@ -11,11 +11,16 @@ pub fn key(key: u32, down: bool, input_core: &mut InputCore) {
         82 => Some(keyboard_state::Keycode::R),
         83 => Some(keyboard_state::Keycode::S),
         87 => Some(keyboard_state::Keycode::W),
+        88 => Some(keyboard_state::Keycode::X),
         90 => Some(keyboard_state::Keycode::Z),
         9 => Some(keyboard_state::Keycode::Tab),
         13 => Some(keyboard_state::Keycode::Return),
         32 => Some(keyboard_state::Keycode::Space),
         27 => Some(keyboard_state::Keycode::Escape),
+        37 => Some(keyboard_state::Keycode::Left),
+        39 => Some(keyboard_state::Keycode::Right),
+        38 => Some(keyboard_state::Keycode::Up),
+        40 => Some(keyboard_state::Keycode::Down),
         _ => None,
     } {
         input_core.handle_event(if down {
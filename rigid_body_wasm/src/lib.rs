@@ -70,7 +70,23 @@ impl RigidBodySimulationWAsm {
     }
 
     pub fn tick(&mut self) -> bool {
-        self.rigid_body_simulation_core.tick()
+        let ret = self.rigid_body_simulation_core.tick();
+        if let Some(issue) = self.rigid_body_simulation_core.take_health_issue()
+        {
+            log(&issue.to_string());
+        }
+        let debug = self.rigid_body_simulation_core.input.debug;
+        for entry in self
+            .rigid_body_simulation_core
+            .simulation_mut()
+            .event_log_mut()
+            .drain()
+        {
+            if debug {
+                log(&entry.to_string());
+            }
+        }
+        ret
     }
 }
 